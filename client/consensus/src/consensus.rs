@@ -0,0 +1,479 @@
+// Copyright 2019-2021 PureStake Inc.
+// This file is part of Moonbeam.
+
+// Moonbeam is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Moonbeam is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Moonbeam.  If not, see <http://www.gnu.org/licenses/>.
+
+//! The parachain consensus used by Moonbeam collators.
+//!
+//! This follows Cumulus' relay chain consensus
+//! (https://github.com/paritytech/cumulus/blob/rococo-v1/client/consensus/relay-chain/src/lib.rs)
+//! closely. The difference is that the block is sealed by its author after it is proposed, and
+//! it is the sealed block that is imported locally and handed to the collator.
+
+use crate::{author_key, pre_digest, seal, LOG_TARGET};
+use author_inherent::AuthorId;
+use cumulus_client_consensus_common::{ParachainCandidate, ParachainConsensus};
+use cumulus_primitives_core::{
+	relay_chain::v1::{Block as PBlock, Hash as PHash, ParachainHost},
+	ParaId, PersistedValidationData,
+};
+use cumulus_primitives_parachain_inherent::ParachainInherentData;
+use parity_scale_codec::Encode;
+use parking_lot::Mutex;
+use polkadot_service::ClientHandle;
+use sc_client_api::Backend;
+use sp_api::ProvideRuntimeApi;
+use sp_consensus::{
+	BlockImport, BlockImportParams, BlockOrigin, EnableProofRecording, Environment,
+	ForkChoiceStrategy, ProofRecording, Proposal, Proposer,
+};
+use sp_inherents::{InherentData, InherentDataProviders};
+use sp_keystore::SyncCryptoStorePtr;
+use sp_runtime::{
+	generic::BlockId,
+	traits::{Block as BlockT, HashFor, Header as HeaderT},
+};
+use sp_trie::StorageProof;
+use std::{marker::PhantomData, sync::Arc, time::Duration};
+
+/// The parachain consensus used by Moonbeam collators. Builds on top of the given parent,
+/// seals the block with the author's key and imports it.
+///
+/// The author key is looked up in the keystore for every block, so a collator that starts
+/// without one begins authoring as soon as a key is inserted (e.g. through `author_rotateKeys`).
+/// When `author` is set, only that key is used.
+pub struct MoonbeamConsensus<B, PF, BI, RClient, RBackend> {
+	para_id: ParaId,
+	_phantom: PhantomData<B>,
+	proposer_factory: Arc<Mutex<PF>>,
+	inherent_data_providers: InherentDataProviders,
+	block_import: Arc<futures::lock::Mutex<BI>>,
+	relay_chain_client: Arc<RClient>,
+	relay_chain_backend: Arc<RBackend>,
+	keystore: SyncCryptoStorePtr,
+	author: Option<AuthorId>,
+}
+
+impl<B, PF, BI, RClient, RBackend> Clone for MoonbeamConsensus<B, PF, BI, RClient, RBackend> {
+	fn clone(&self) -> Self {
+		Self {
+			para_id: self.para_id,
+			_phantom: PhantomData,
+			proposer_factory: self.proposer_factory.clone(),
+			inherent_data_providers: self.inherent_data_providers.clone(),
+			block_import: self.block_import.clone(),
+			relay_chain_backend: self.relay_chain_backend.clone(),
+			relay_chain_client: self.relay_chain_client.clone(),
+			keystore: self.keystore.clone(),
+			author: self.author.clone(),
+		}
+	}
+}
+
+impl<B, PF, BI, RClient, RBackend> MoonbeamConsensus<B, PF, BI, RClient, RBackend>
+where
+	B: BlockT,
+	RClient: ProvideRuntimeApi<PBlock>,
+	RClient::Api: ParachainHost<PBlock>,
+	RBackend: Backend<PBlock>,
+{
+	/// Create a new instance of the Moonbeam consensus.
+	#[allow(clippy::too_many_arguments)]
+	pub fn new(
+		para_id: ParaId,
+		proposer_factory: PF,
+		inherent_data_providers: InherentDataProviders,
+		block_import: BI,
+		polkadot_client: Arc<RClient>,
+		polkadot_backend: Arc<RBackend>,
+		keystore: SyncCryptoStorePtr,
+		author: Option<AuthorId>,
+	) -> Self {
+		Self {
+			para_id,
+			proposer_factory: Arc::new(Mutex::new(proposer_factory)),
+			inherent_data_providers,
+			block_import: Arc::new(futures::lock::Mutex::new(block_import)),
+			relay_chain_backend: polkadot_backend,
+			relay_chain_client: polkadot_client,
+			keystore,
+			author,
+			_phantom: PhantomData,
+		}
+	}
+
+	/// Get the inherent data with validation function parameters and the author injected
+	fn inherent_data(
+		&self,
+		validation_data: &PersistedValidationData,
+		relay_parent: PHash,
+		author: &AuthorId,
+	) -> Option<InherentData> {
+		let mut inherent_data = self
+			.inherent_data_providers
+			.create_inherent_data()
+			.map_err(|e| {
+				log::error!(
+					target: LOG_TARGET,
+					"Failed to create inherent data: {:?}",
+					e
+				)
+			})
+			.ok()?;
+
+		let parachain_inherent_data = ParachainInherentData::create_at(
+			relay_parent,
+			&*self.relay_chain_client,
+			&*self.relay_chain_backend,
+			validation_data,
+			self.para_id,
+		)?;
+
+		inherent_data
+			.put_data(
+				cumulus_primitives_parachain_inherent::INHERENT_IDENTIFIER,
+				&parachain_inherent_data,
+			)
+			.map_err(|e| {
+				log::error!(
+					target: LOG_TARGET,
+					"Failed to put the system inherent into inherent data: {:?}",
+					e
+				)
+			})
+			.ok()?;
+
+		let relay_randomness_proof = self.relay_randomness_proof(relay_parent)?;
+		inherent_data
+			.put_data(
+				pallet_relay_randomness::INHERENT_IDENTIFIER,
+				&relay_randomness_proof,
+			)
+			.map_err(|e| {
+				log::error!(
+					target: LOG_TARGET,
+					"Failed to put the relay randomness inherent into inherent data: {:?}",
+					e
+				)
+			})
+			.ok()?;
+
+		inherent_data
+			.put_data(author_inherent::INHERENT_IDENTIFIER, &author.encode())
+			.map_err(|e| {
+				log::error!(
+					target: LOG_TARGET,
+					"Failed to put the author inherent into inherent data: {:?}",
+					e
+				)
+			})
+			.ok()?;
+
+		Some(inherent_data)
+	}
+
+	/// Prove the BABE storage of the relay parent, from which the runtime takes its randomness.
+	fn relay_randomness_proof(&self, relay_parent: PHash) -> Option<StorageProof> {
+		let relay_parent_state = self
+			.relay_chain_backend
+			.state_at(BlockId::Hash(relay_parent))
+			.map_err(|e| {
+				log::error!(
+					target: LOG_TARGET,
+					"Failed to get the state of the relay parent {:?}: {:?}",
+					relay_parent,
+					e
+				)
+			})
+			.ok()?;
+
+		sp_state_machine::prove_read(
+			relay_parent_state,
+			pallet_relay_randomness::relay_storage_keys(),
+		)
+		.map_err(|e| {
+			log::error!(
+				target: LOG_TARGET,
+				"Failed to prove the BABE storage of the relay parent {:?}: {:?}",
+				relay_parent,
+				e
+			)
+		})
+		.ok()
+	}
+}
+
+#[async_trait::async_trait]
+impl<B, PF, BI, RClient, RBackend> ParachainConsensus<B>
+	for MoonbeamConsensus<B, PF, BI, RClient, RBackend>
+where
+	B: BlockT,
+	RClient: ProvideRuntimeApi<PBlock> + Send + Sync,
+	RClient::Api: ParachainHost<PBlock>,
+	RBackend: Backend<PBlock>,
+	BI: BlockImport<B> + Send + Sync,
+	PF: Environment<B> + Send + Sync,
+	PF::Proposer: Proposer<
+		B,
+		Transaction = BI::Transaction,
+		ProofRecording = EnableProofRecording,
+		Proof = <EnableProofRecording as ProofRecording>::Proof,
+	>,
+{
+	async fn produce_candidate(
+		&mut self,
+		parent: &B::Header,
+		relay_parent: PHash,
+		validation_data: &PersistedValidationData,
+	) -> Option<ParachainCandidate<B>> {
+		let author = match author_key(&self.keystore, self.author.as_ref()) {
+			Some(author) => author,
+			None => {
+				log::warn!(
+					target: LOG_TARGET,
+					"No author key found in the keystore. Not producing a candidate.",
+				);
+				return None;
+			}
+		};
+
+		let proposer_future = self.proposer_factory.lock().init(&parent);
+
+		let proposer = proposer_future
+			.await
+			.map_err(|e| log::error!(target: LOG_TARGET, "Could not create proposer: {:?}", e))
+			.ok()?;
+
+		let inherent_data = self.inherent_data(&validation_data, relay_parent, &author)?;
+
+		let Proposal {
+			block,
+			storage_changes,
+			proof,
+		} = proposer
+			.propose(
+				inherent_data,
+				pre_digest(&author),
+				//TODO: Fix this.
+				Duration::from_millis(500),
+				// Set the block limit to 50% of the maximum PoV size.
+				Some((validation_data.max_pov_size / 2) as usize),
+			)
+			.await
+			.map_err(|e| log::error!(target: LOG_TARGET, "Proposing failed: {:?}", e))
+			.ok()?;
+
+		let (header, extrinsics) = block.deconstruct();
+
+		// The runtime never sees the seal, so the pre-hash is the hash of the proposed header.
+		let seal = match seal(&self.keystore, &author, header.hash().as_ref()) {
+			Some(seal) => seal,
+			None => {
+				log::error!(
+					target: LOG_TARGET,
+					"Failed to seal the block with the author key"
+				);
+				return None;
+			}
+		};
+
+		let mut block_import_params = BlockImportParams::new(BlockOrigin::Own, header.clone());
+		block_import_params.post_digests.push(seal.clone());
+		block_import_params.body = Some(extrinsics.clone());
+		// Best block is determined by the relay chain.
+		block_import_params.fork_choice = Some(ForkChoiceStrategy::Custom(false));
+		block_import_params.storage_changes = Some(storage_changes);
+
+		if let Err(err) = self
+			.block_import
+			.lock()
+			.await
+			.import_block(block_import_params, Default::default())
+		{
+			log::error!(
+				target: LOG_TARGET,
+				"Error importing built block at {:?}: {:?}",
+				parent.hash(),
+				err
+			);
+
+			return None;
+		}
+
+		let mut sealed_header = header;
+		sealed_header.digest_mut().push(seal);
+
+		Some(ParachainCandidate {
+			block: B::new(sealed_header, extrinsics),
+			proof,
+		})
+	}
+}
+
+/// Parameters of [`build_moonbeam_consensus`].
+pub struct BuildMoonbeamConsensusParams<PF, BI, RBackend> {
+	pub para_id: ParaId,
+	pub proposer_factory: PF,
+	pub inherent_data_providers: InherentDataProviders,
+	pub block_import: BI,
+	pub relay_chain_client: polkadot_service::Client,
+	pub relay_chain_backend: Arc<RBackend>,
+	pub keystore: SyncCryptoStorePtr,
+	pub author: Option<AuthorId>,
+}
+
+/// Build the [`MoonbeamConsensus`].
+///
+/// Returns a boxed [`ParachainConsensus`].
+pub fn build_moonbeam_consensus<Block, PF, BI, RBackend>(
+	BuildMoonbeamConsensusParams {
+		para_id,
+		proposer_factory,
+		inherent_data_providers,
+		block_import,
+		relay_chain_client,
+		relay_chain_backend,
+		keystore,
+		author,
+	}: BuildMoonbeamConsensusParams<PF, BI, RBackend>,
+) -> Box<dyn ParachainConsensus<Block>>
+where
+	Block: BlockT,
+	PF: Environment<Block> + Send + Sync + 'static,
+	PF::Proposer: Proposer<
+		Block,
+		Transaction = BI::Transaction,
+		ProofRecording = EnableProofRecording,
+		Proof = <EnableProofRecording as ProofRecording>::Proof,
+	>,
+	BI: BlockImport<Block> + Send + Sync + 'static,
+	RBackend: Backend<PBlock> + 'static,
+	// Rust bug: https://github.com/rust-lang/rust/issues/24159
+	sc_client_api::StateBackendFor<RBackend, PBlock>: sc_client_api::StateBackend<HashFor<PBlock>>,
+{
+	MoonbeamConsensusBuilder::new(
+		para_id,
+		proposer_factory,
+		block_import,
+		inherent_data_providers,
+		relay_chain_client,
+		relay_chain_backend,
+		keystore,
+		author,
+	)
+	.build()
+}
+
+/// Moonbeam consensus builder.
+///
+/// Builds a [`MoonbeamConsensus`] for a parachain. As this requires
+/// a concrete relay chain client instance, the builder takes a [`polkadot_service::Client`]
+/// that wraps this concrete instance. By using [`polkadot_service::ExecuteWithClient`]
+/// the builder gets access to this concrete instance.
+struct MoonbeamConsensusBuilder<Block, PF, BI, RBackend> {
+	para_id: ParaId,
+	_phantom: PhantomData<Block>,
+	proposer_factory: PF,
+	inherent_data_providers: InherentDataProviders,
+	block_import: BI,
+	relay_chain_backend: Arc<RBackend>,
+	relay_chain_client: polkadot_service::Client,
+	keystore: SyncCryptoStorePtr,
+	author: Option<AuthorId>,
+}
+
+impl<Block, PF, BI, RBackend> MoonbeamConsensusBuilder<Block, PF, BI, RBackend>
+where
+	Block: BlockT,
+	// Rust bug: https://github.com/rust-lang/rust/issues/24159
+	sc_client_api::StateBackendFor<RBackend, PBlock>: sc_client_api::StateBackend<HashFor<PBlock>>,
+	PF: Environment<Block> + Send + Sync + 'static,
+	PF::Proposer: Proposer<
+		Block,
+		Transaction = BI::Transaction,
+		ProofRecording = EnableProofRecording,
+		Proof = <EnableProofRecording as ProofRecording>::Proof,
+	>,
+	BI: BlockImport<Block> + Send + Sync + 'static,
+	RBackend: Backend<PBlock> + 'static,
+{
+	/// Create a new instance of the builder.
+	#[allow(clippy::too_many_arguments)]
+	fn new(
+		para_id: ParaId,
+		proposer_factory: PF,
+		block_import: BI,
+		inherent_data_providers: InherentDataProviders,
+		relay_chain_client: polkadot_service::Client,
+		relay_chain_backend: Arc<RBackend>,
+		keystore: SyncCryptoStorePtr,
+		author: Option<AuthorId>,
+	) -> Self {
+		Self {
+			para_id,
+			_phantom: PhantomData,
+			proposer_factory,
+			block_import,
+			inherent_data_providers,
+			relay_chain_backend,
+			relay_chain_client,
+			keystore,
+			author,
+		}
+	}
+
+	/// Build the moonbeam consensus.
+	fn build(self) -> Box<dyn ParachainConsensus<Block>> {
+		self.relay_chain_client.clone().execute_with(self)
+	}
+}
+
+impl<Block, PF, BI, RBackend> polkadot_service::ExecuteWithClient
+	for MoonbeamConsensusBuilder<Block, PF, BI, RBackend>
+where
+	Block: BlockT,
+	// Rust bug: https://github.com/rust-lang/rust/issues/24159
+	sc_client_api::StateBackendFor<RBackend, PBlock>: sc_client_api::StateBackend<HashFor<PBlock>>,
+	PF: Environment<Block> + Send + Sync + 'static,
+	PF::Proposer: Proposer<
+		Block,
+		Transaction = BI::Transaction,
+		ProofRecording = EnableProofRecording,
+		Proof = <EnableProofRecording as ProofRecording>::Proof,
+	>,
+	BI: BlockImport<Block> + Send + Sync + 'static,
+	RBackend: Backend<PBlock> + 'static,
+{
+	type Output = Box<dyn ParachainConsensus<Block>>;
+
+	fn execute_with_client<PClient, Api, PBackend>(self, client: Arc<PClient>) -> Self::Output
+	where
+		<Api as sp_api::ApiExt<PBlock>>::StateBackend: sp_api::StateBackend<HashFor<PBlock>>,
+		PBackend: Backend<PBlock>,
+		PBackend::State: sp_api::StateBackend<sp_runtime::traits::BlakeTwo256>,
+		Api: polkadot_service::RuntimeApiCollection<StateBackend = PBackend::State>,
+		PClient: polkadot_service::AbstractClient<PBlock, PBackend, Api = Api> + 'static,
+	{
+		Box::new(MoonbeamConsensus::new(
+			self.para_id,
+			self.proposer_factory,
+			self.inherent_data_providers,
+			self.block_import,
+			client.clone(),
+			self.relay_chain_backend,
+			self.keystore,
+			self.author,
+		))
+	}
+}
@@ -0,0 +1,146 @@
+// Copyright 2019-2021 PureStake Inc.
+// This file is part of Moonbeam.
+
+// Moonbeam is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Moonbeam is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Moonbeam.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Detection of authors that seal two different blocks on the same parent.
+//!
+//! `SealedBlockImport` hands every sealed header it imports over to `report_equivocations`,
+//! which keeps them per height, author and parent for a while. When an author shows up with a
+//! second block on a parent, both headers are turned into an `EquivocationProof` that is
+//! submitted to the transaction pool through the runtime.
+
+use crate::LOG_TARGET;
+use author_inherent::{AuthorId, EquivocationProof};
+use futures::{channel::mpsc::UnboundedReceiver, StreamExt};
+use moonbeam_primitives_author_equivocation::AuthorEquivocationApi;
+use sp_api::ProvideRuntimeApi;
+use sp_blockchain::HeaderBackend;
+use sp_runtime::{
+	generic::BlockId,
+	traits::{Block as BlockT, Header as HeaderT, NumberFor, Saturating},
+};
+use std::{collections::BTreeMap, sync::Arc};
+
+/// How many heights below the highest imported one sealed headers are kept for.
+const HEIGHTS_TO_KEEP: u32 = 256;
+
+/// A sealed header imported at some height, and whether its author was already reported there.
+struct Sealed<Header> {
+	author: AuthorId,
+	header: Header,
+	reported: bool,
+}
+
+/// The sealed headers imported recently, per height.
+struct EquivocationTracker<B: BlockT> {
+	heights: BTreeMap<NumberFor<B>, Vec<Sealed<B::Header>>>,
+}
+
+impl<B: BlockT> EquivocationTracker<B> {
+	fn new() -> Self {
+		Self {
+			heights: BTreeMap::new(),
+		}
+	}
+
+	/// Note a sealed header that was imported. Returns a proof of equivocation the first time
+	/// its author is seen sealing a different header on the same parent.
+	fn note(
+		&mut self,
+		author: AuthorId,
+		header: B::Header,
+	) -> Option<EquivocationProof<B::Header>> {
+		let number = *header.number();
+		let highest = match self.heights.keys().next_back() {
+			Some(highest) if *highest > number => *highest,
+			_ => number,
+		};
+		let oldest = highest.saturating_sub(HEIGHTS_TO_KEEP.into());
+		if number < oldest {
+			return None;
+		}
+		self.heights = self.heights.split_off(&oldest);
+
+		let sealed = self.heights.entry(number).or_insert_with(Vec::new);
+		match sealed.iter_mut().find(|sealed| {
+			sealed.author == author && sealed.header.parent_hash() == header.parent_hash()
+		}) {
+			None => {
+				sealed.push(Sealed {
+					author,
+					header,
+					reported: false,
+				});
+				None
+			}
+			Some(first) if !first.reported && first.header.hash() != header.hash() => {
+				first.reported = true;
+				Some(EquivocationProof {
+					author,
+					first_header: first.header.clone(),
+					second_header: header,
+				})
+			}
+			Some(_) => None,
+		}
+	}
+}
+
+/// Watch the sealed headers coming out of `SealedBlockImport` and report equivocating authors.
+///
+/// Reports are submitted as unsigned extrinsics through the runtime at the best block.
+pub async fn report_equivocations<B, C>(
+	client: Arc<C>,
+	mut sealed_headers: UnboundedReceiver<(AuthorId, B::Header)>,
+) where
+	B: BlockT,
+	C: ProvideRuntimeApi<B> + HeaderBackend<B>,
+	C::Api: AuthorEquivocationApi<B>,
+{
+	let mut tracker = EquivocationTracker::<B>::new();
+
+	while let Some((author, header)) = sealed_headers.next().await {
+		let proof = match tracker.note(author, header) {
+			Some(proof) => proof,
+			None => continue,
+		};
+
+		log::warn!(
+			target: LOG_TARGET,
+			"Author {:?} sealed {:?} and {:?} on the same parent at height {:?}. Reporting it.",
+			proof.author,
+			proof.first_header.hash(),
+			proof.second_header.hash(),
+			proof.first_header.number(),
+		);
+
+		let best = BlockId::Hash(client.info().best_hash);
+		match client
+			.runtime_api()
+			.submit_report_equivocation_unsigned_extrinsic(&best, proof)
+		{
+			Ok(Some(())) => {}
+			Ok(None) => log::warn!(
+				target: LOG_TARGET,
+				"Could not submit the equivocation report to the transaction pool",
+			),
+			Err(e) => log::warn!(
+				target: LOG_TARGET,
+				"Could not report the equivocation: {:?}",
+				e,
+			),
+		}
+	}
+}
@@ -0,0 +1,126 @@
+// Copyright 2019-2021 PureStake Inc.
+// This file is part of Moonbeam.
+
+// Moonbeam is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Moonbeam is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Moonbeam.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Block import that checks the author's seal.
+
+use crate::LOG_TARGET;
+use author_inherent::{author_from_digests, verify_seal, AuthorId, ENGINE_ID};
+use futures::channel::mpsc::UnboundedSender;
+use sp_consensus::{
+	import_queue::CacheKeyId, BlockCheckParams, BlockImport, BlockImportParams,
+	Error as ConsensusError, ImportResult,
+};
+use sp_runtime::{
+	traits::{Block as BlockT, Header as HeaderT},
+	DigestItem,
+};
+use std::collections::HashMap;
+
+/// Block import wrapper that only lets sealed blocks through to the inner block import.
+///
+/// Blocks coming from the network carry the seal as their last header digest. It is checked
+/// against the author reported in the block and moved to the post digests, so the runtime
+/// executes the header it actually produced. Blocks we author ourselves already carry the seal
+/// in the post digests and are checked the same way.
+///
+/// Each sealed header that passes the check is sent, with its author, to `sealed_headers` so
+/// equivocations can be detected (see `report_equivocations`).
+pub struct SealedBlockImport<B: BlockT, I> {
+	inner: I,
+	sealed_headers: UnboundedSender<(AuthorId, B::Header)>,
+}
+
+impl<B: BlockT, I> SealedBlockImport<B, I> {
+	pub fn new(inner: I, sealed_headers: UnboundedSender<(AuthorId, B::Header)>) -> Self {
+		Self {
+			inner,
+			sealed_headers,
+		}
+	}
+}
+
+impl<B: BlockT, I: Clone> Clone for SealedBlockImport<B, I> {
+	fn clone(&self) -> Self {
+		Self {
+			inner: self.inner.clone(),
+			sealed_headers: self.sealed_headers.clone(),
+		}
+	}
+}
+
+impl<B, I> BlockImport<B> for SealedBlockImport<B, I>
+where
+	B: BlockT,
+	I: BlockImport<B, Error = ConsensusError>,
+{
+	type Error = ConsensusError;
+	type Transaction = I::Transaction;
+
+	fn check_block(&mut self, block: BlockCheckParams<B>) -> Result<ImportResult, Self::Error> {
+		self.inner.check_block(block)
+	}
+
+	fn import_block(
+		&mut self,
+		mut block: BlockImportParams<B, Self::Transaction>,
+		new_cache: HashMap<CacheKeyId, Vec<u8>>,
+	) -> Result<ImportResult, Self::Error> {
+		if block.post_digests.is_empty() {
+			if let Some(seal) = block.header.digest_mut().pop() {
+				block.post_digests.push(seal);
+			}
+		}
+
+		let signature = match block.post_digests.last().and_then(DigestItem::as_seal) {
+			Some((id, signature)) if id == ENGINE_ID => signature,
+			_ => {
+				return Err(ConsensusError::ClientImport(
+					"Block is not sealed by its author".into(),
+				))
+			}
+		};
+
+		let author = author_from_digests(
+			block
+				.header
+				.digest()
+				.logs()
+				.iter()
+				.filter_map(DigestItem::as_pre_runtime),
+		)
+		.ok_or_else(|| ConsensusError::ClientImport("Block does not report its author".into()))?;
+
+		if !verify_seal(&author, block.header.hash().as_ref(), signature) {
+			log::debug!(
+				target: LOG_TARGET,
+				"Rejecting block {:?}: the seal was not made by the reported author",
+				block.post_hash(),
+			);
+			return Err(ConsensusError::ClientImport(
+				"Block seal was not signed by the reported author".into(),
+			));
+		}
+
+		let mut sealed_header = block.header.clone();
+		sealed_header
+			.digest_mut()
+			.push(DigestItem::Seal(ENGINE_ID, signature.to_vec()));
+		// Nobody listening only means that equivocations are not being watched for.
+		let _ = self.sealed_headers.unbounded_send((author, sealed_header));
+
+		self.inner.import_block(block, new_cache)
+	}
+}
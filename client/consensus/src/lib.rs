@@ -0,0 +1,87 @@
+// Copyright 2019-2021 PureStake Inc.
+// This file is part of Moonbeam.
+
+// Moonbeam is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Moonbeam is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Moonbeam.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Client side of Moonbeam's block authorship.
+//!
+//! Every block is sealed by its author: once the block is built, the author signs its pre-hash
+//! with its author key (see `author_inherent::AuthorId`) from the node keystore and appends the
+//! signature as a `Seal` digest. The seal is checked against the author key reported in the
+//! block's pre-runtime digest. This crate contains the pieces of the node that produce and
+//! check those seals:
+//! * `MoonbeamConsensus` is the parachain consensus used by collators. It is Cumulus' relay chain
+//!   consensus with an additional sealing step.
+//! * `AuthorSealDataProvider` seals the blocks authored by the manual seal dev service.
+//! * `SealedBlockImport` wraps a block import, verifying the seal against the reported author
+//!   and moving it out of the header before the runtime executes the block.
+//! * `report_equivocations` reports authors that seal two different blocks at the same height.
+
+mod consensus;
+mod equivocation;
+mod import;
+mod manual_seal;
+
+pub use consensus::{build_moonbeam_consensus, BuildMoonbeamConsensusParams, MoonbeamConsensus};
+pub use equivocation::report_equivocations;
+pub use import::SealedBlockImport;
+pub use manual_seal::AuthorSealDataProvider;
+
+use author_inherent::{AuthorId, ENGINE_ID, KEY_TYPE};
+use parity_scale_codec::Encode;
+use sp_core::crypto::Public;
+use sp_keystore::{SyncCryptoStore, SyncCryptoStorePtr};
+use sp_runtime::{generic::Digest, DigestItem};
+
+const LOG_TARGET: &str = "author-seal";
+
+/// Find the author key to seal blocks with.
+///
+/// This is `requested` if that key is in the keystore. When no particular key is requested, it
+/// is any author key in the keystore. Returns `None` if there is no such key.
+pub fn author_key(keystore: &SyncCryptoStorePtr, requested: Option<&AuthorId>) -> Option<AuthorId> {
+	let mut available = SyncCryptoStore::ecdsa_public_keys(&**keystore, KEY_TYPE)
+		.into_iter()
+		.map(AuthorId::from);
+
+	match requested {
+		Some(requested) => available.find(|author| author == requested),
+		None => available.next(),
+	}
+}
+
+/// The pre-runtime digest that reports `author` as the author of the block.
+pub fn pre_digest<Hash>(author: &AuthorId) -> Digest<Hash> {
+	Digest {
+		logs: vec![DigestItem::PreRuntime(ENGINE_ID, author.encode())],
+	}
+}
+
+/// Sign `pre_hash` with `author`'s key from the keystore and wrap the signature in a seal digest.
+///
+/// Returns `None` if the keystore fails to sign, e.g. because it does not hold the key.
+pub fn seal<Hash>(
+	keystore: &SyncCryptoStorePtr,
+	author: &AuthorId,
+	pre_hash: &[u8],
+) -> Option<DigestItem<Hash>> {
+	SyncCryptoStore::sign_with(
+		&**keystore,
+		KEY_TYPE,
+		&author.to_public_crypto_pair(),
+		pre_hash,
+	)
+	.ok()
+	.map(|signature| DigestItem::Seal(ENGINE_ID, signature))
+}
@@ -0,0 +1,77 @@
+// Copyright 2019-2021 PureStake Inc.
+// This file is part of Moonbeam.
+
+// Moonbeam is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Moonbeam is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Moonbeam.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Sealing for blocks authored by the manual seal dev service.
+
+use crate::{pre_digest, seal};
+use author_inherent::AuthorId;
+use sc_consensus_manual_seal::{consensus::ConsensusDataProvider, Error};
+use sp_consensus::BlockImportParams;
+use sp_inherents::InherentData;
+use sp_keystore::SyncCryptoStorePtr;
+use sp_runtime::{
+	traits::{Block as BlockT, DigestFor, Header as HeaderT},
+	DigestItem,
+};
+use std::marker::PhantomData;
+
+/// Consensus data provider for manual seal that seals every block with the author's key.
+pub struct AuthorSealDataProvider<Transaction> {
+	keystore: SyncCryptoStorePtr,
+	author: AuthorId,
+	_phantom: PhantomData<Transaction>,
+}
+
+impl<Transaction> AuthorSealDataProvider<Transaction> {
+	pub fn new(keystore: SyncCryptoStorePtr, author: AuthorId) -> Self {
+		Self {
+			keystore,
+			author,
+			_phantom: PhantomData,
+		}
+	}
+}
+
+impl<B, Transaction> ConsensusDataProvider<B> for AuthorSealDataProvider<Transaction>
+where
+	B: BlockT,
+	Transaction: Send + Sync + 'static,
+{
+	type Transaction = Transaction;
+
+	fn create_digest(
+		&self,
+		_parent: &B::Header,
+		_inherents: &InherentData,
+	) -> Result<DigestFor<B>, Error> {
+		Ok(pre_digest(&self.author))
+	}
+
+	fn append_block_import(
+		&self,
+		_parent: &B::Header,
+		params: &mut BlockImportParams<B, Self::Transaction>,
+		_inherents: &InherentData,
+	) -> Result<(), Error> {
+		let seal: DigestItem<B::Hash> =
+			seal(&self.keystore, &self.author, params.header.hash().as_ref()).ok_or_else(|| {
+				Error::StringError("No key for the block author found in the keystore".into())
+			})?;
+		params.post_digests.push(seal);
+
+		Ok(())
+	}
+}
@@ -140,7 +140,7 @@ export const moonbeamDefinitions = {
       },
     },
     {
-      minmax: [19, undefined],
+      minmax: [19, 32],
       types: {
         AccountId: "EthereumAccountId",
         AccountInfo: "AccountInfoWithProviders",
@@ -237,6 +237,135 @@ export const moonbeamDefinitions = {
           first: "BlockNumber",
           length: "u32",
         },
+        AuthorId: "[u8; 33]",
+        RegistrationInfo: {
+          account: "AccountId",
+          deposit: "Balance",
+        },
+        EquivocationProof: {
+          author: "AuthorId",
+          first_header: "Header",
+          second_header: "Header",
+        },
+      },
+    },
+    {
+      minmax: [33, undefined],
+      types: {
+        AccountId: "EthereumAccountId",
+        AccountInfo: "AccountInfoWithProviders",
+        Address: "AccountId",
+        Balance: "u128",
+        LookupSource: "AccountId",
+        Account: {
+          nonce: "U256",
+          balance: "u128",
+        },
+        ExtrinsicSignature: "MultiEthereumSignature",
+        MultiEthereumSignature: {
+          _enum: {
+            Ethereum: "EthereumSignature",
+            Eip712: "EthereumSignature",
+          },
+        },
+        ProxyType: {
+          _enum: ["Any", "NonTransfer", "Governance", "Staking", "AuthorMapping"],
+        },
+        RoundIndex: "u32",
+        Candidate: {
+          id: "AccountId",
+          fee: "Perbill",
+          bond: "Balance",
+          nominators: "Vec<Bond>",
+          total: "Balance",
+          state: "CollatorStatus",
+        },
+        Nominator: {
+          nominations: "Vec<Bond>",
+          total: "Balance",
+        },
+        Bond: {
+          owner: "AccountId",
+          amount: "Balance",
+        },
+        CollatorStatus: {
+          _enum: ["Active", "Idle", { Leaving: "RoundIndex" }],
+        },
+        TxPoolResultContent: {
+          pending: "HashMap<H160, HashMap<U256, PoolTransaction>>",
+          queued: "HashMap<H160, HashMap<U256, PoolTransaction>>",
+        },
+        TxPoolResultInspect: {
+          pending: "HashMap<H160, HashMap<U256, Summary>>",
+          queued: "HashMap<H160, HashMap<U256, Summary>>",
+        },
+        TxPoolResultStatus: {
+          pending: "U256",
+          queued: "U256",
+        },
+        Summary: "Bytes",
+        PoolTransaction: {
+          hash: "H256",
+          nonce: "U256",
+          block_hash: "Option<H256>",
+          block_number: "Option<U256>",
+          from: "H160",
+          to: "Option<H160>",
+          value: "U256",
+          gas_price: "U256",
+          gas: "U256",
+          input: "Bytes",
+        },
+        // Staking inflation
+        Range: "RangeBalance",
+        RangeBalance: {
+          min: "Balance",
+          ideal: "Balance",
+          max: "Balance",
+        },
+        RangePerbill: {
+          min: "Perbill",
+          ideal: "Perbill",
+          max: "Perbill",
+        },
+        InflationInfo: {
+          expect: "RangeBalance",
+          round: "RangePerbill",
+        },
+        OrderedSet: "Vec<Bond>",
+        Collator: {
+          id: "AccountId",
+          bond: "Balance",
+          nominators: "Vec<Bond>",
+          total: "Balance",
+          state: "CollatorStatus",
+        },
+        CollatorSnapshot: {
+          bond: "Balance",
+          nominators: "Vec<Bond>",
+          total: "Balance",
+        },
+        SystemInherentData: {
+          validation_data: "PersistedValidationData",
+          relay_chain_state: "StorageProof",
+          downward_messages: "Vec<InboundDownwardMessage>",
+          horizontal_messages: "BTreeMap<ParaId, Vec<InboundHrmpMessage>>",
+        },
+        RoundInfo: {
+          current: "RoundIndex",
+          first: "BlockNumber",
+          length: "u32",
+        },
+        AuthorId: "[u8; 33]",
+        RegistrationInfo: {
+          account: "AccountId",
+          deposit: "Balance",
+        },
+        EquivocationProof: {
+          author: "AuthorId",
+          first_header: "Header",
+          second_header: "Header",
+        },
       },
     },
   ],
@@ -19,29 +19,42 @@
 //! Learn more about Substrate chain specifications at
 //! https://substrate.dev/docs/en/knowledgebase/integrate/chain-spec
 
+use author_inherent::AuthorId;
 use bip39::{Language, Mnemonic, Seed};
 use cumulus_primitives_core::ParaId;
 use log::debug;
 use moonbeam_runtime::{
-	AccountId, Balance, BalancesConfig, CouncilCollectiveConfig, DemocracyConfig, EVMConfig,
-	EthereumChainIdConfig, EthereumConfig, GenesisConfig, InflationInfo, ParachainInfoConfig,
-	ParachainStakingConfig, Range, SchedulerConfig, SudoConfig, SystemConfig,
-	TechComitteeCollectiveConfig, GLMR, WASM_BINARY,
+	AccountId, AuthorMappingConfig, Balance, BalancesConfig, CouncilCollectiveConfig,
+	DemocracyConfig, EVMConfig, EthereumChainIdConfig, EthereumConfig, FeeMarketConfig,
+	GenesisConfig, InflationInfo, ParachainInfoConfig, ParachainStakingConfig, Range,
+	SchedulerConfig, Signature, SudoConfig, SystemConfig, TechComitteeCollectiveConfig,
+	TreasuryConfig, GLMR, WASM_BINARY,
 };
 use sc_chain_spec::{ChainSpecExtension, ChainSpecGroup};
 use sc_service::ChainType;
 use serde::{Deserialize, Serialize};
-use sha3::{Digest, Keccak256};
 
-use sp_core::{ecdsa, Pair, Public, H160, H256};
+use sp_core::{ecdsa, Pair, Public};
 use sp_runtime::{
-	traits::{BlakeTwo256, Hash},
+	traits::{IdentifyAccount, Verify},
 	Perbill,
 };
-use std::convert::TryInto;
-use std::{collections::BTreeMap, str::FromStr};
+use std::{collections::BTreeMap, convert::TryFrom, str::FromStr};
 use tiny_hderive::bip32::ExtendedPrivKey;
 
+/// Private key of Gerald, the collator staked at genesis in the development specs. The dev
+/// service started with `--dev` also uses it as Gerald's author key.
+pub const GERALD_DEV_SEED: &str =
+	"0x99B3C12287537E38C90A9219D4CB074A89A16E9CDB20BF85728EBD97C343E342";
+
+/// Gerald's author key, registered to Gerald's account at genesis.
+pub fn gerald_author_id() -> AuthorId {
+	ecdsa::Pair::from_string(GERALD_DEV_SEED, None)
+		.expect("Gerald's seed is a valid private key")
+		.public()
+		.into()
+}
+
 /// Helper function to derive `num_accounts` child pairs from mnemonics
 /// Substrate derive function cannot be used because the derivation is different than Ethereum's
 /// https://substrate.dev/rustdocs/v2.0.0/src/sp_core/ecdsa.rs.html#460-470
@@ -55,12 +68,7 @@ pub fn derive_bip44_pairs_from_mnemonic<TPublic: Public>(
 
 	let mut childs = Vec::new();
 	for i in 0..num_accounts {
-		if let Some(child_pair) =
-			ExtendedPrivKey::derive(seed.as_bytes(), format!("m/44'/60'/0'/0/{}", i).as_ref())
-				.ok()
-				.map(|account| TPublic::Pair::from_seed_slice(&account.secret()).ok())
-				.flatten()
-		{
+		if let Some(child_pair) = derive_bip44_pair::<TPublic>(&seed, i) {
 			childs.push(child_pair);
 		} else {
 			log::error!("An error ocurred while deriving key {} from parent", i)
@@ -69,23 +77,44 @@ pub fn derive_bip44_pairs_from_mnemonic<TPublic: Public>(
 	childs
 }
 
+/// Helper function to derive the child pair at `index` from a mnemonic, which is the account
+/// MetaMask derives at that index. Returns `None` if the mnemonic is invalid.
+pub fn derive_bip44_pair_from_mnemonic<TPublic: Public>(
+	mnemonic: &str,
+	index: u32,
+) -> Option<TPublic::Pair> {
+	let seed = Mnemonic::from_phrase(mnemonic, Language::English)
+		.map(|x| Seed::new(&x, ""))
+		.ok()?;
+	derive_bip44_pair::<TPublic>(&seed, index)
+}
+
+/// The child pair of `seed` at `index` of the Ethereum BIP44 path.
+fn derive_bip44_pair<TPublic: Public>(seed: &Seed, index: u32) -> Option<TPublic::Pair> {
+	ExtendedPrivKey::derive(seed.as_bytes(), bip44_path(index).as_ref())
+		.ok()
+		.map(|account| TPublic::Pair::from_seed_slice(&account.secret()).ok())
+		.flatten()
+}
+
+/// The Ethereum BIP44 derivation path of the account at `index`.
+pub fn bip44_path(index: u32) -> String {
+	format!("m/44'/60'/0'/0/{}", index)
+}
+
+type AccountPublic = <Signature as Verify>::Signer;
+
 /// Helper function to get an AccountId from Key Pair
-/// We need the full decompressed public key to derive an ethereum-style account
-/// Substrate does not provide a method to obtain the full decompressed public key
-/// Therefore, this function uses the secp256k1_ecdsa_recover method to recover the full key
-/// A solution without using the private key would imply solving the secp256k1 curve equation
-/// The latter is currently not possible with current substrate methods
-pub fn get_account_id_from_pair<TPublic: Public>(pair: TPublic::Pair) -> Option<AccountId> {
-	let test_message = [1u8; 32];
-	let signature: [u8; 65] = pair.sign(&test_message).as_ref().try_into().ok()?;
-	let pubkey = sp_io::crypto::secp256k1_ecdsa_recover(
-		&signature,
-		BlakeTwo256::hash_of(&test_message).as_fixed_bytes(),
-	)
-	.ok()?;
-	Some(H160::from(H256::from_slice(
-		Keccak256::digest(&pubkey).as_slice(),
-	)))
+/// The account is derived from the public key as Ethereum does, so the accounts of the keys
+/// match the addresses MetaMask shows for them. Returns `None` if the public key is not a valid
+/// secp256k1 key.
+pub fn get_account_id_from_pair<TPublic: Public>(pair: TPublic::Pair) -> Option<AccountId>
+where
+	AccountPublic: TryFrom<TPublic>,
+{
+	AccountPublic::try_from(pair.public())
+		.ok()
+		.map(IdentifyAccount::into_account)
 }
 
 /// Specialized `ChainSpec`. This is a specialization of the general Substrate ChainSpec type.
@@ -117,11 +146,17 @@ pub fn generate_accounts(mnemonic: String, num_accounts: u32) -> Vec<AccountId>
 		.iter()
 		.map(|par| {
 			let account = get_account_id_from_pair::<ecdsa::Public>(par.clone());
-			debug!(
-				"private_key {} --------> Account {:x?}",
-				sp_core::hexdisplay::HexDisplay::from(&par.clone().seed()),
-				account
-			);
+			match account {
+				Some(account) => debug!(
+					"private_key {} --------> Account {:x?}",
+					sp_core::hexdisplay::HexDisplay::from(&par.clone().seed()),
+					account
+				),
+				None => log::error!(
+					"The public key 0x{} has no account, it is not a valid secp256k1 key",
+					sp_core::hexdisplay::HexDisplay::from(&par.public().as_ref())
+				),
+			}
 			account
 		})
 		.flatten()
@@ -150,6 +185,11 @@ pub fn development_chain_spec(mnemonic: Option<String>, num_accounts: Option<u32
 					None,
 					1_000 * GLMR,
 				)],
+				// Author keys
+				vec![(
+					gerald_author_id(),
+					AccountId::from_str("6Be02d1d3665660d22FF9624b7BE0551ee1Ac91b").unwrap(),
+				)],
 				moonbeam_inflation_config(),
 				accounts.clone(),
 				Default::default(), // para_id
@@ -186,6 +226,11 @@ pub fn get_chain_spec(para_id: ParaId) -> ChainSpec {
 					None,
 					1_000 * GLMR,
 				)],
+				// Author keys
+				vec![(
+					gerald_author_id(),
+					AccountId::from_str("6Be02d1d3665660d22FF9624b7BE0551ee1Ac91b").unwrap(),
+				)],
 				moonbeam_inflation_config(),
 				vec![AccountId::from_str("6Be02d1d3665660d22FF9624b7BE0551ee1Ac91b").unwrap()],
 				para_id,
@@ -222,6 +267,7 @@ pub fn moonbeam_inflation_config() -> InflationInfo<Balance> {
 pub fn testnet_genesis(
 	root_key: AccountId,
 	stakers: Vec<(AccountId, Option<AccountId>, Balance)>,
+	author_mappings: Vec<(AuthorId, AccountId)>,
 	inflation_config: InflationInfo<Balance>,
 	endowed_accounts: Vec<AccountId>,
 	para_id: ParaId,
@@ -250,12 +296,21 @@ pub fn testnet_genesis(
 			accounts: BTreeMap::new(),
 		},
 		pallet_ethereum: EthereumConfig {},
+		pallet_fee_market: FeeMarketConfig {
+			gas_price_floor: 1.into(),
+			target_fullness: Perbill::from_percent(50),
+			treasury_fee_share: Perbill::from_percent(20),
+		},
 		pallet_democracy: DemocracyConfig {},
+		pallet_treasury: TreasuryConfig {},
 		pallet_scheduler: SchedulerConfig {},
 		parachain_staking: ParachainStakingConfig {
 			stakers,
 			inflation_config,
 		},
+		pallet_author_mapping: AuthorMappingConfig {
+			mappings: author_mappings,
+		},
 		pallet_collective_Instance1: CouncilCollectiveConfig {
 			phantom: Default::default(),
 			members: vec![], // TODO : Set members
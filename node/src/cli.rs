@@ -20,7 +20,9 @@
 //! It is built using structopt and inherits behavior from Substrate's sc_cli crate.
 
 use crate::chain_spec;
-use sp_core::H160;
+use author_inherent::AuthorId;
+use sp_core::ecdsa;
+use std::convert::TryInto;
 use std::path::PathBuf;
 use std::str::FromStr;
 use structopt::StructOpt;
@@ -56,6 +58,9 @@ pub enum Subcommand {
 
 	/// Revert the chain to a previous state.
 	Revert(sc_cli::RevertCmd),
+
+	/// Key management utilities.
+	Key(crate::key::KeySubcommand),
 }
 
 #[derive(Debug, StructOpt)]
@@ -129,9 +134,11 @@ pub struct RunCmd {
 	#[structopt(long, default_value = "instant")]
 	pub sealing: Sealing,
 
-	/// Public identity for participating in staking and receiving rewards
-	#[structopt(long, parse(try_from_str = parse_h160))]
-	pub author_id: Option<H160>,
+	/// Author key (hex encoded public key) to seal blocks with. It must be in the keystore and
+	/// registered on chain to the account that participates in staking and receives rewards.
+	/// Defaults to any author key in the keystore, or to Gerald's with `--dev`.
+	#[structopt(long, parse(try_from_str = parse_author_id))]
+	pub author_id: Option<AuthorId>,
 
 	/// Enable EVM tracing module on a non-authority node.
 	#[structopt(
@@ -154,10 +161,14 @@ pub struct RunCmd {
 	pub ethapi_trace_cache_duration: u32,
 }
 
-fn parse_h160(input: &str) -> Result<H160, String> {
-	input
-		.parse::<H160>()
-		.map_err(|_| "Failed to parse H160".to_string())
+fn parse_author_id(input: &str) -> Result<AuthorId, String> {
+	let bytes = sp_core::bytes::from_hex(input).map_err(|_| "Failed to parse hex".to_string())?;
+	let raw: [u8; 33] = bytes
+		.as_slice()
+		.try_into()
+		.map_err(|_| "An author key is a 33 bytes compressed public key".to_string())?;
+
+	Ok(ecdsa::Public::from_raw(raw).into())
 }
 
 impl std::ops::Deref for RunCmd {
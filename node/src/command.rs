@@ -23,7 +23,7 @@ use crate::{
 use cumulus_client_service::genesis::generate_genesis_block;
 use cumulus_primitives_core::ParaId;
 use log::info;
-use moonbeam_runtime::{AccountId, Block};
+use moonbeam_runtime::Block;
 use parity_scale_codec::Encode;
 use polkadot_parachain::primitives::AccountIdConversion;
 use polkadot_service::RococoChainSpec;
@@ -36,9 +36,8 @@ use sc_service::{
 	PartialComponents,
 };
 use sp_core::hexdisplay::HexDisplay;
-use sp_core::H160;
 use sp_runtime::traits::Block as _;
-use std::{io::Write, net::SocketAddr, str::FromStr};
+use std::{io::Write, net::SocketAddr};
 
 fn load_spec(
 	id: &str,
@@ -199,7 +198,7 @@ pub fn run() -> Result<()> {
 					task_manager,
 					import_queue,
 					..
-				} = crate::service::new_partial(&config, None, dev_service)?;
+				} = crate::service::new_partial(&config, dev_service)?;
 				Ok((cmd.run(client, import_queue), task_manager))
 			})
 		}
@@ -210,7 +209,7 @@ pub fn run() -> Result<()> {
 					client,
 					task_manager,
 					..
-				} = crate::service::new_partial(&config, None, false)?;
+				} = crate::service::new_partial(&config, false)?;
 				Ok((cmd.run(client, config.database), task_manager))
 			})
 		}
@@ -221,7 +220,7 @@ pub fn run() -> Result<()> {
 					client,
 					task_manager,
 					..
-				} = crate::service::new_partial(&config, None, false)?;
+				} = crate::service::new_partial(&config, false)?;
 				Ok((cmd.run(client, config.chain_spec), task_manager))
 			})
 		}
@@ -238,7 +237,7 @@ pub fn run() -> Result<()> {
 					task_manager,
 					import_queue,
 					..
-				} = crate::service::new_partial(&config, None, dev_service)?;
+				} = crate::service::new_partial(&config, dev_service)?;
 				Ok((cmd.run(client, import_queue), task_manager))
 			})
 		}
@@ -283,10 +282,11 @@ pub fn run() -> Result<()> {
 					task_manager,
 					backend,
 					..
-				} = crate::service::new_partial(&config, None, false)?;
+				} = crate::service::new_partial(&config, false)?;
 				Ok((cmd.run(client, backend), task_manager))
 			})
 		}
+		Some(Subcommand::Key(cmd)) => cmd.run(&cli),
 		Some(Subcommand::ExportGenesisState(params)) => {
 			let mut builder = sc_cli::LoggerBuilder::new("");
 			builder.with_profiling(sc_tracing::TracingReceiver::Log, "");
@@ -335,10 +335,7 @@ pub fn run() -> Result<()> {
 		None => {
 			let runner = cli.create_runner(&*cli.run)?;
 			let collator = cli.run.base.validator || cli.collator;
-			let author_id: Option<H160> = cli.run.author_id;
-			if collator && author_id.is_none() {
-				return Err("Collator nodes must specify an author account id".into());
-			}
+			let author_id = cli.run.author_id.clone();
 
 			runner
 				.run_node_until_exit(|config| async move {
@@ -360,15 +357,6 @@ pub fn run() -> Result<()> {
 						// --dev implies --collator
 						let collator = collator || cli.run.shared_params.dev;
 
-						// If no author id was supplied, use the one that is staked at genesis
-						// in the default development spec.
-						let author_id = author_id.or_else(|| {
-							Some(
-								AccountId::from_str("6Be02d1d3665660d22FF9624b7BE0551ee1Ac91b")
-									.expect("Gerald is a valid account"),
-							)
-						});
-
 						return crate::service::new_dev(config, author_id, collator, cli.run);
 					}
 
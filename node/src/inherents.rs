@@ -27,19 +27,24 @@
 use cumulus_primitives_core::PersistedValidationData;
 use cumulus_primitives_parachain_inherent::{ParachainInherentData, INHERENT_IDENTIFIER};
 use parity_scale_codec::Encode;
-use sp_core::H160;
+use sp_core::H256;
 use sp_inherents::{InherentData, InherentDataProviders, InherentIdentifier, ProvideInherentData};
+use sp_runtime::traits::BlakeTwo256;
+use sp_state_machine::{Backend, TrieBackend};
 use sp_timestamp::InherentError;
+use sp_trie::StorageProof;
+use std::sync::atomic::{AtomicU64, Ordering};
 
 use cumulus_test_relay_sproof_builder::RelayStateSproofBuilder;
 
 /// Build the inherent data providers for the node.
 ///
 /// Not all nodes will need all inherent data providers:
-/// - The author provider is only necessary for block producing nodes
 /// - The validation data provider can be mocked.
+///
+/// The author inherent data is not provided here. Block producing nodes pick their author key
+/// from the keystore, which does not exist yet when the providers are built.
 pub fn build_inherent_data_providers(
-	author: Option<H160>,
 	mock: bool,
 ) -> Result<InherentDataProviders, sc_service::Error> {
 	let providers = InherentDataProviders::new();
@@ -50,14 +55,6 @@ pub fn build_inherent_data_providers(
 		.map_err(Into::into)
 		.map_err(sp_consensus::error::Error::InherentData)?;
 
-	// Author ID Provider for authoring node only.
-	if let Some(account) = author {
-		providers
-			.register_provider(author_inherent::InherentDataProvider(account.encode()))
-			.map_err(Into::into)
-			.map_err(sp_consensus::error::Error::InherentData)?;
-	}
-
 	// Parachain inherent provider, only for dev-service nodes.
 	if mock {
 		providers
@@ -73,7 +70,8 @@ pub fn build_inherent_data_providers(
 	Ok(providers)
 }
 
-/// Inherent data provider that supplies mocked validation data.
+/// Inherent data provider that supplies mocked validation data, and a proof of the mock relay
+/// chain BABE storage for the relay randomness inherent.
 ///
 /// This is useful when running a node that is not actually backed by any relay chain.
 /// For example when running a local node, or running integration tests.
@@ -88,9 +86,8 @@ impl ProvideInherentData for MockValidationDataInherentDataProvider {
 		&self,
 		inherent_data: &mut InherentData,
 	) -> Result<(), sp_inherents::Error> {
-		// Use the "sproof" (spoof proof) builder to build valid mock state root and proof.
-		let (relay_storage_root, proof) =
-			RelayStateSproofBuilder::default().into_state_root_and_proof();
+		let (relay_storage_root, proof) = mock_relay_state();
+		inherent_data.put_data(pallet_relay_randomness::INHERENT_IDENTIFIER, &proof)?;
 
 		let data = ParachainInherentData {
 			validation_data: PersistedValidationData {
@@ -111,3 +108,38 @@ impl ProvideInherentData for MockValidationDataInherentDataProvider {
 		InherentError::try_from(&INHERENT_IDENTIFIER, error).map(|e| format!("{:?}", e))
 	}
 }
+
+/// The number of mock relay blocks built so far, from which their VRF outputs are derived.
+static MOCK_RELAY_BLOCKS: AtomicU64 = AtomicU64::new(0);
+
+/// Build a mock relay chain state and a proof of all of it. It has the storage the parachain
+/// system reads, and the BABE storage the relay randomness pallet reads, with a VRF output of the
+/// relay block author that changes with every call.
+fn mock_relay_state() -> (H256, StorageProof) {
+	// Use the "sproof" (spoof proof) builder to build valid mock state root and proof. It proves
+	// all the keys it inserts, so its proof holds the whole state.
+	let (root, proof) = RelayStateSproofBuilder::default().into_state_root_and_proof();
+	let mut backend = TrieBackend::new(proof.into_memory_db::<BlakeTwo256>(), root);
+
+	let relay_block = MOCK_RELAY_BLOCKS.fetch_add(1, Ordering::Relaxed);
+	let author_vrf_randomness = Some(sp_core::blake2_256(&relay_block.encode()));
+	backend.insert(vec![(
+		None,
+		vec![
+			(
+				pallet_relay_randomness::babe_randomness_key(),
+				Some([0xee; 32].encode()),
+			),
+			(
+				pallet_relay_randomness::babe_author_vrf_randomness_key(),
+				Some(author_vrf_randomness.encode()),
+			),
+		],
+	)]);
+
+	let root = *backend.root();
+	let keys = backend.keys(&[]);
+	let proof =
+		sp_state_machine::prove_read(backend, keys).expect("The keys are in the mock state");
+	(root, proof)
+}
@@ -0,0 +1,316 @@
+// Copyright 2019-2021 PureStake Inc.
+// This file is part of Moonbeam.
+
+// Moonbeam is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Moonbeam is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Moonbeam.  If not, see <http://www.gnu.org/licenses/>.
+
+//! The `key` subcommand, managing the keys of Moonbeam accounts and author keys.
+//!
+//! Keys are secp256k1 keys derived from mnemonics along the Ethereum BIP44 path, so a mnemonic
+//! gives the same accounts as it does in MetaMask.
+
+use crate::chain_spec;
+use bip39::{Language, Mnemonic, MnemonicType};
+use sc_cli::{Error, KeystoreParams, OutputType, Result, SharedParams, SubstrateCli};
+use sc_keystore::LocalKeystore;
+use sc_service::config::{BasePath, KeystoreConfig};
+use serde::Serialize;
+use sp_core::{ecdsa, hexdisplay::HexDisplay, Pair};
+use sp_keystore::{SyncCryptoStore, SyncCryptoStorePtr};
+use std::sync::Arc;
+use structopt::StructOpt;
+
+/// Key management commands.
+#[derive(Debug, StructOpt)]
+pub enum KeySubcommand {
+	/// Generate a mnemonic and one of its accounts.
+	Generate(GenerateCmd),
+
+	/// Derive an account from a mnemonic.
+	Derive(DeriveCmd),
+
+	/// Show the account and public key of a private key.
+	Inspect(InspectCmd),
+
+	/// Insert an author key into the keystore of the node.
+	Insert(InsertCmd),
+}
+
+impl KeySubcommand {
+	/// Run the key command.
+	pub fn run<C: SubstrateCli>(&self, cli: &C) -> Result<()> {
+		match self {
+			KeySubcommand::Generate(cmd) => cmd.run(),
+			KeySubcommand::Derive(cmd) => cmd.run(),
+			KeySubcommand::Inspect(cmd) => cmd.run(),
+			KeySubcommand::Insert(cmd) => cmd.run(cli),
+		}
+	}
+}
+
+/// Command for generating a mnemonic.
+#[derive(Debug, StructOpt)]
+pub struct GenerateCmd {
+	/// Number of words of the mnemonic: 12, 15, 18, 21 or 24.
+	#[structopt(long, default_value = "24")]
+	pub words: usize,
+
+	/// Index of the account to show.
+	#[structopt(long, default_value = "0")]
+	pub account_index: u32,
+
+	#[allow(missing_docs)]
+	#[structopt(flatten)]
+	pub output: OutputTypeFlag,
+}
+
+impl GenerateCmd {
+	/// Run the command.
+	pub fn run(&self) -> Result<()> {
+		let mnemonic_type = MnemonicType::for_word_count(self.words)
+			.map_err(|_| Error::Input("A mnemonic has 12, 15, 18, 21 or 24 words".into()))?;
+		let mnemonic = Mnemonic::new(mnemonic_type, Language::English);
+
+		KeyInfo::from_mnemonic(mnemonic.phrase(), self.account_index)?
+			.print(self.output.output_type)
+	}
+}
+
+/// Command for deriving an account from a mnemonic.
+#[derive(Debug, StructOpt)]
+pub struct DeriveCmd {
+	/// The mnemonic to derive the account from.
+	pub mnemonic: String,
+
+	/// Index of the account in the BIP44 derivation path.
+	#[structopt(long, default_value = "0")]
+	pub account_index: u32,
+
+	#[allow(missing_docs)]
+	#[structopt(flatten)]
+	pub output: OutputTypeFlag,
+}
+
+impl DeriveCmd {
+	/// Run the command.
+	pub fn run(&self) -> Result<()> {
+		KeyInfo::from_mnemonic(&self.mnemonic, self.account_index)?.print(self.output.output_type)
+	}
+}
+
+/// Command for inspecting a private key.
+#[derive(Debug, StructOpt)]
+pub struct InspectCmd {
+	/// The hex encoded private key.
+	pub private_key: String,
+
+	#[allow(missing_docs)]
+	#[structopt(flatten)]
+	pub output: OutputTypeFlag,
+}
+
+impl InspectCmd {
+	/// Run the command.
+	pub fn run(&self) -> Result<()> {
+		KeyInfo::from_pair(&parse_private_key(&self.private_key)?)?.print(self.output.output_type)
+	}
+}
+
+/// Command for inserting an author key into the keystore.
+#[derive(Debug, StructOpt)]
+pub struct InsertCmd {
+	/// The hex encoded private key of the author key.
+	#[structopt(long, required_unless = "mnemonic", conflicts_with = "mnemonic")]
+	pub private_key: Option<String>,
+
+	/// A mnemonic to derive the author key from.
+	#[structopt(long)]
+	pub mnemonic: Option<String>,
+
+	/// Index of the author key in the BIP44 derivation path of the mnemonic.
+	#[structopt(long, default_value = "0")]
+	pub account_index: u32,
+
+	#[allow(missing_docs)]
+	#[structopt(flatten)]
+	pub shared_params: SharedParams,
+
+	#[allow(missing_docs)]
+	#[structopt(flatten)]
+	pub keystore_params: KeystoreParams,
+}
+
+impl InsertCmd {
+	/// Run the command.
+	pub fn run<C: SubstrateCli>(&self, cli: &C) -> Result<()> {
+		let pair = match (&self.private_key, &self.mnemonic) {
+			(Some(private_key), _) => parse_private_key(private_key)?,
+			(None, Some(mnemonic)) => derive_pair(mnemonic, self.account_index)?,
+			(None, None) => unreachable!("structopt requires the private key or the mnemonic; qed"),
+		};
+
+		let base_path = self
+			.shared_params
+			.base_path()
+			.unwrap_or_else(|| BasePath::from_project("", "", &C::executable_name()));
+		let chain_id = self.shared_params.chain_id(self.shared_params.is_dev());
+		let chain_spec = cli.load_spec(&chain_id)?;
+		let config_dir = base_path.config_dir(chain_spec.id());
+
+		let keystore: SyncCryptoStorePtr =
+			match self.keystore_params.keystore_config(&config_dir)? {
+				(_, KeystoreConfig::Path { path, password }) => {
+					Arc::new(LocalKeystore::open(path, password)?)
+				}
+				_ => unreachable!("keystore_config always returns a path and a password; qed"),
+			};
+
+		// The keystore loads keys from their seed as it would from a secret URI.
+		let suri = format!("0x{}", HexDisplay::from(&pair.seed()));
+		SyncCryptoStore::insert_unknown(
+			&*keystore,
+			author_inherent::KEY_TYPE,
+			&suri,
+			pair.public().as_ref(),
+		)
+		.map_err(|_| Error::KeyStoreOperation)?;
+
+		println!(
+			"Inserted author key 0x{}",
+			HexDisplay::from(&pair.public().as_ref())
+		);
+		Ok(())
+	}
+}
+
+/// The `--output-type` flag of the commands printing keys.
+#[derive(Debug, StructOpt)]
+pub struct OutputTypeFlag {
+	/// Output format, `text` or `json`.
+	#[structopt(
+		long,
+		value_name = "FORMAT",
+		possible_values = &OutputType::variants(),
+		case_insensitive = true,
+		default_value = "Text"
+	)]
+	pub output_type: OutputType,
+}
+
+/// A key as the key commands print it.
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct KeyInfo {
+	address: String,
+	#[serde(skip_serializing_if = "Option::is_none")]
+	mnemonic: Option<String>,
+	private_key: String,
+	/// The compressed public key, which is the author id when the key is an author key.
+	public_key: String,
+	#[serde(skip_serializing_if = "Option::is_none")]
+	path: Option<String>,
+}
+
+impl KeyInfo {
+	fn from_pair(pair: &ecdsa::Pair) -> Result<Self> {
+		let address = chain_spec::get_account_id_from_pair::<ecdsa::Public>(pair.clone())
+			.ok_or_else(|| Error::Input("The public key is not a valid secp256k1 key".into()))?;
+		Ok(KeyInfo {
+			address: format!("{:?}", address),
+			mnemonic: None,
+			private_key: format!("0x{}", HexDisplay::from(&pair.seed())),
+			public_key: format!("0x{}", HexDisplay::from(&pair.public().as_ref())),
+			path: None,
+		})
+	}
+
+	fn from_mnemonic(mnemonic: &str, index: u32) -> Result<Self> {
+		Ok(KeyInfo {
+			mnemonic: Some(mnemonic.to_string()),
+			path: Some(chain_spec::bip44_path(index)),
+			..Self::from_pair(&derive_pair(mnemonic, index)?)?
+		})
+	}
+
+	fn print(&self, output_type: OutputType) -> Result<()> {
+		match output_type {
+			OutputType::Json => println!(
+				"{}",
+				serde_json::to_string_pretty(self).expect("A key info serializes to JSON; qed")
+			),
+			OutputType::Text => {
+				println!("Address:      {}", self.address);
+				if let Some(mnemonic) = &self.mnemonic {
+					println!("Mnemonic:     {}", mnemonic);
+				}
+				println!("Private Key:  {}", self.private_key);
+				println!("Public Key:   {}", self.public_key);
+				if let Some(path) = &self.path {
+					println!("Path:         {}", path);
+				}
+			}
+		}
+		Ok(())
+	}
+}
+
+/// The key pair at `index` of the BIP44 derivation path of `mnemonic`.
+fn derive_pair(mnemonic: &str, index: u32) -> Result<ecdsa::Pair> {
+	chain_spec::derive_bip44_pair_from_mnemonic::<ecdsa::Public>(mnemonic, index)
+		.ok_or_else(|| Error::Input("Invalid mnemonic".into()))
+}
+
+/// The key pair of a hex encoded private key.
+fn parse_private_key(private_key: &str) -> Result<ecdsa::Pair> {
+	let seed = sp_core::bytes::from_hex(private_key)
+		.map_err(|_| Error::Input("The private key is not hex encoded".into()))?;
+	ecdsa::Pair::from_seed_slice(&seed)
+		.map_err(|_| Error::Input("A private key is 32 bytes".into()))
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	const MNEMONIC: &str = "bottom drive obey lake curtain smoke basket hold race lonely fit walk";
+
+	#[test]
+	fn derives_the_metamask_accounts_of_a_mnemonic() {
+		let first = KeyInfo::from_mnemonic(MNEMONIC, 0).unwrap();
+		assert_eq!(first.address, "0xf24ff3a9cf04c71dbc94d0b566f7a27b94566cac");
+		assert_eq!(first.path.as_deref(), Some("m/44'/60'/0'/0/0"));
+
+		let last = KeyInfo::from_mnemonic(MNEMONIC, 9).unwrap();
+		assert_eq!(last.address, "0x2898fe7a42be376c8bc7af536a940f7fd5add423");
+	}
+
+	#[test]
+	fn inspects_private_keys() {
+		let pair =
+			parse_private_key("0x99B3C12287537E38C90A9219D4CB074A89A16E9CDB20BF85728EBD97C343E342")
+				.unwrap();
+		let info = KeyInfo::from_pair(&pair).unwrap();
+		assert_eq!(info.address, "0x6be02d1d3665660d22ff9624b7be0551ee1ac91b");
+		assert_eq!(
+			info.public_key,
+			"0x03624f720eae676a04111631c9ca338c11d0f5a80ee42210c6be72983ceb620fbf"
+		);
+	}
+
+	#[test]
+	fn rejects_invalid_keys() {
+		assert!(parse_private_key("0x1234").is_err());
+		assert!(parse_private_key("not hex").is_err());
+		assert!(derive_pair("not a mnemonic", 0).is_err());
+	}
+}
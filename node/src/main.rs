@@ -27,6 +27,7 @@ mod service;
 mod cli;
 mod command;
 mod inherents;
+mod key;
 mod rpc;
 
 fn main() -> sc_cli::Result<()> {
@@ -24,13 +24,12 @@
 
 use crate::cli::EthApi as EthApiCmd;
 use crate::{
+	chain_spec::GERALD_DEV_SEED,
 	cli::{RunCmd, Sealing},
 	inherents::build_inherent_data_providers,
 };
 use async_io::Timer;
-use cumulus_client_consensus_relay_chain::{
-	build_relay_chain_consensus, BuildRelayChainConsensusParams,
-};
+use author_inherent::AuthorId;
 use cumulus_client_network::build_block_announce_validator;
 use cumulus_client_service::{
 	prepare_node_config, start_collator, start_full_node, StartCollatorParams, StartFullNodeParams,
@@ -40,8 +39,13 @@ use fc_mapping_sync::MappingSyncWorker;
 use fc_rpc::EthTask;
 use fc_rpc_core::types::{FilterPool, PendingTransactions};
 use futures::{Stream, StreamExt};
+use moonbeam_consensus::{
+	author_key, build_moonbeam_consensus, report_equivocations, AuthorSealDataProvider,
+	BuildMoonbeamConsensusParams, SealedBlockImport,
+};
 use moonbeam_rpc_trace::TraceFilterCache;
 use moonbeam_runtime::{opaque::Block, RuntimeApi};
+use parity_scale_codec::Encode;
 use polkadot_primitives::v0::CollatorPair;
 use sc_cli::SubstrateCli;
 use sc_client_api::BlockchainEvents;
@@ -52,7 +56,8 @@ use sc_service::{
 	error::Error as ServiceError, BasePath, Configuration, PartialComponents, Role, TFullBackend,
 	TFullClient, TaskManager,
 };
-use sp_core::{H160, H256};
+use sp_core::H256;
+use sp_keystore::SyncCryptoStore;
 use std::{
 	collections::{BTreeMap, HashMap},
 	sync::{Arc, Mutex},
@@ -70,6 +75,8 @@ use sc_telemetry::{Telemetry, TelemetryWorker, TelemetryWorkerHandle};
 type FullClient = TFullClient<Block, RuntimeApi, Executor>;
 type FullBackend = TFullBackend<Block>;
 type MaybeSelectChain = Option<sc_consensus::LongestChain<FullBackend, Block>>;
+type FullBlockImport =
+	SealedBlockImport<Block, FrontierBlockImport<Block, Arc<FullClient>, FullClient>>;
 
 // TODO This is copied from frontier. It should be imported instead after
 // https://github.com/paritytech/frontier/issues/333 is solved
@@ -101,7 +108,6 @@ pub fn open_frontier_backend(config: &Configuration) -> Result<Arc<fc_db::Backen
 #[allow(clippy::type_complexity)]
 pub fn new_partial(
 	config: &Configuration,
-	author: Option<H160>,
 	dev_service: bool,
 ) -> Result<
 	PartialComponents<
@@ -111,7 +117,7 @@ pub fn new_partial(
 		sp_consensus::DefaultImportQueue<Block, FullClient>,
 		sc_transaction_pool::FullPool<Block, FullClient>,
 		(
-			FrontierBlockImport<Block, Arc<FullClient>, FullClient>,
+			FullBlockImport,
 			PendingTransactions,
 			Option<FilterPool>,
 			Option<Telemetry>,
@@ -121,7 +127,7 @@ pub fn new_partial(
 	>,
 	ServiceError,
 > {
-	let inherent_data_providers = build_inherent_data_providers(author, dev_service)?;
+	let inherent_data_providers = build_inherent_data_providers(dev_service)?;
 
 	let telemetry = config
 		.telemetry_endpoints
@@ -172,12 +178,21 @@ pub fn new_partial(
 	let frontier_block_import =
 		FrontierBlockImport::new(client.clone(), client.clone(), frontier_backend.clone());
 
+	// Every block must be sealed by its author, whichever import queue it goes through. The
+	// sealed headers are then watched for authors sealing two blocks at the same height.
+	let (sealed_headers_sink, sealed_headers) = futures::channel::mpsc::unbounded();
+	let block_import = SealedBlockImport::new(frontier_block_import, sealed_headers_sink);
+	task_manager.spawn_handle().spawn(
+		"author-equivocation",
+		report_equivocations(client.clone(), sealed_headers),
+	);
+
 	// Depending whether we are
 	let import_queue = if dev_service {
 		// There is a bug in this import queue where it doesn't properly check inherents:
 		// https://github.com/paritytech/substrate/issues/8164
 		sc_consensus_manual_seal::import_queue(
-			Box::new(frontier_block_import.clone()),
+			Box::new(block_import.clone()),
 			&task_manager.spawn_essential_handle(),
 			config.prometheus_registry(),
 		)
@@ -187,7 +202,7 @@ pub fn new_partial(
 		// https://github.com/PureStake/moonbeam/pull/266
 		cumulus_client_consensus_relay_chain::import_queue(
 			client.clone(),
-			frontier_block_import.clone(),
+			block_import.clone(),
 			inherent_data_providers.clone(),
 			&task_manager.spawn_essential_handle(),
 			config.prometheus_registry(),
@@ -204,7 +219,7 @@ pub fn new_partial(
 		inherent_data_providers,
 		select_chain: maybe_select_chain,
 		other: (
-			frontier_block_import,
+			block_import,
 			pending_transactions,
 			filter_pool,
 			telemetry,
@@ -220,7 +235,7 @@ pub fn new_partial(
 async fn start_node_impl<RB>(
 	parachain_config: Configuration,
 	collator_key: CollatorPair,
-	author_id: Option<H160>,
+	author_id: Option<AuthorId>,
 	polkadot_config: Configuration,
 	id: polkadot_primitives::v0::Id,
 	collator: bool,
@@ -240,7 +255,7 @@ where
 
 	let parachain_config = prepare_node_config(parachain_config);
 
-	let params = new_partial(&parachain_config, author_id, false)?;
+	let params = new_partial(&parachain_config, false)?;
 	let (
 		block_import,
 		pending_transactions,
@@ -262,6 +277,7 @@ where
 
 	let client = params.client.clone();
 	let backend = params.backend.clone();
+	let keystore = params.keystore_container.sync_keystore();
 	let block_announce_validator = build_block_announce_validator(
 		polkadot_full_node.client.clone(),
 		id,
@@ -350,7 +366,7 @@ where
 		transaction_pool: transaction_pool.clone(),
 		task_manager: &mut task_manager,
 		config: parachain_config,
-		keystore: params.keystore_container.sync_keystore(),
+		keystore: keystore.clone(),
 		backend: backend.clone(),
 		network: network.clone(),
 		network_status_sinks,
@@ -403,13 +419,17 @@ where
 		);
 		let spawner = task_manager.spawn_handle();
 
-		let parachain_consensus = build_relay_chain_consensus(BuildRelayChainConsensusParams {
+		// The author key is looked up in the keystore for every block, so it may be inserted
+		// after the node has started.
+		let parachain_consensus = build_moonbeam_consensus(BuildMoonbeamConsensusParams {
 			para_id: id,
 			proposer_factory,
 			inherent_data_providers: params.inherent_data_providers,
 			block_import,
 			relay_chain_client: polkadot_full_node.client.clone(),
 			relay_chain_backend: polkadot_full_node.backend.clone(),
+			keystore,
+			author: author_id,
 		});
 
 		let params = StartCollatorParams {
@@ -447,7 +467,7 @@ where
 pub async fn start_node(
 	parachain_config: Configuration,
 	collator_key: CollatorPair,
-	author_id: Option<H160>,
+	author_id: Option<AuthorId>,
 	polkadot_config: Configuration,
 	id: polkadot_primitives::v0::Id,
 	collator: bool,
@@ -470,7 +490,7 @@ pub async fn start_node(
 /// the parachain inherent.
 pub fn new_dev(
 	config: Configuration,
-	author_id: Option<H160>,
+	author_id: Option<AuthorId>,
 	// TODO I guess we should use substrate-cli's validator flag for this.
 	// Resolve after https://github.com/paritytech/cumulus/pull/380 is reviewed.
 	collator: bool,
@@ -494,7 +514,7 @@ pub fn new_dev(
 				_telemetry_worker_handle,
 				frontier_backend,
 			),
-	} = new_partial(&config, author_id, true)?;
+	} = new_partial(&config, true)?;
 
 	let (network, network_status_sinks, system_rpc_tx, network_starter) =
 		sc_service::build_network(sc_service::BuildNetworkParams {
@@ -522,6 +542,33 @@ pub fn new_dev(
 	let mut command_sink = None;
 
 	if collator {
+		// With `--dev`, the well-known development author key is made available for sealing
+		// blocks, like Substrate does with `--alice`. Otherwise the author key must be inserted
+		// with `moonbeam key insert`.
+		let gerald: Option<AuthorId> = if cmd.shared_params.dev {
+			let gerald = SyncCryptoStore::ecdsa_generate_new(
+				&*keystore_container.sync_keystore(),
+				author_inherent::KEY_TYPE,
+				Some(GERALD_DEV_SEED),
+			)
+			.map_err(|e| format!("Failed to insert the development author key: {:?}", e))?;
+			Some(gerald.into())
+		} else {
+			None
+		};
+
+		// Unless another author key was requested, the dev service authors as Gerald with
+		// `--dev`, and with any author key in the keystore otherwise.
+		let author = author_key(
+			&keystore_container.sync_keystore(),
+			author_id.as_ref().or(gerald.as_ref()),
+		)
+		.ok_or("The author key is not in the keystore, insert it with `moonbeam key insert`")?;
+		inherent_data_providers
+			.register_provider(author_inherent::InherentDataProvider(author.encode()))
+			.map_err(Into::into)
+			.map_err(sp_consensus::error::Error::InherentData)?;
+
 		let env = sc_basic_authorship::ProposerFactory::new(
 			task_manager.spawn_handle(),
 			client.clone(),
@@ -579,7 +626,10 @@ pub fn new_dev(
 				pool: transaction_pool.pool().clone(),
 				commands_stream,
 				select_chain,
-				consensus_data_provider: None,
+				consensus_data_provider: Some(Box::new(AuthorSealDataProvider::new(
+					keystore_container.sync_keystore(),
+					author,
+				))),
 				inherent_data_providers,
 			}),
 		);
@@ -15,7 +15,9 @@
 // along with Moonbeam.  If not, see <http://www.gnu.org/licenses/>.
 
 //! Embedded specs for testing purposes, must be compiled with --features=test-spec
-use crate::chain_spec::{moonbeam_inflation_config, testnet_genesis, ChainSpec, Extensions};
+use crate::chain_spec::{
+	gerald_author_id, moonbeam_inflation_config, testnet_genesis, ChainSpec, Extensions,
+};
 use cumulus_primitives_core::ParaId;
 use moonbeam_runtime::{AccountId, GLMR};
 use sc_service::ChainType;
@@ -45,6 +47,11 @@ pub fn staking_spec(para_id: ParaId) -> ChainSpec {
 						1_000 * GLMR,
 					),
 				],
+				// Author keys
+				vec![(
+					gerald_author_id(),
+					AccountId::from_str("6Be02d1d3665660d22FF9624b7BE0551ee1Ac91b").unwrap(),
+				)],
 				// Inflation config
 				moonbeam_inflation_config(),
 				// Endowed accounts (each minted 1 << 80 balance)
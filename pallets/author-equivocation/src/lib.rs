@@ -0,0 +1,248 @@
+// Copyright 2019-2021 PureStake Inc.
+// This file is part of Moonbeam.
+
+// Moonbeam is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Moonbeam is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Moonbeam.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Reporting and punishment of block authors that equivocate.
+//!
+//! An author equivocates when it seals two different blocks on the same parent. Nodes keep
+//! track of the sealed headers they import and, when they see an author seal a second block on
+//! a parent, build an `EquivocationProof` out of both headers and submit it through the
+//! `report_equivocation_unsigned` extrinsic. The proof carries the author's own signatures, so
+//! it needs no signed origin and anyone can relay it. Each author can only be reported once per
+//! height, and only for the last `ReportWindow` blocks.
+//!
+//! The headers do not commit to the relay parent they were built on, so a proof cannot tell an
+//! equivocation from an honest author proposing again on a newer relay parent after its first
+//! block was not backed. Reports are therefore only recorded. `SlashOrigin` confirms the ones
+//! that are actual equivocations with `slash`, which passes the offending account on to
+//! `OnEquivocation`, where the consequences are decided. See `parachain-staking` for an
+//! implementation. Reports are pruned once they leave the window.
+
+#![cfg_attr(not(feature = "std"), no_std)]
+
+use frame_support::pallet;
+
+pub use pallet::*;
+
+#[cfg(test)]
+mod mock;
+#[cfg(test)]
+mod tests;
+
+#[pallet]
+pub mod pallet {
+	use author_inherent::{
+		check_equivocation_proof, AccountLookup, AuthorId, EquivocationProof, OnEquivocation,
+	};
+	use frame_support::pallet_prelude::*;
+	use frame_system::{
+		offchain::{SendTransactionTypes, SubmitTransaction},
+		pallet_prelude::*,
+	};
+	use sp_runtime::traits::{Header as HeaderT, Saturating};
+	use sp_std::boxed::Box;
+
+	/// How many blocks an equivocation report stays valid in the transaction pool.
+	const REPORT_LONGEVITY: u64 = 64;
+
+	/// The weight of checking an equivocation proof: two ECDSA seal verifications of about 50µs
+	/// each, and hashing headers of at most `MaxProofSize` bytes.
+	const CHECK_PROOF_WEIGHT: Weight = 120_000_000;
+
+	/// The Author Equivocation pallet
+	#[pallet::pallet]
+	pub struct Pallet<T>(PhantomData<T>);
+
+	/// Configuration trait of this pallet.
+	#[pallet::config]
+	pub trait Config: frame_system::Config + SendTransactionTypes<Call<Self>> {
+		/// The overarching event type
+		type Event: From<Event<Self>> + IsType<<Self as frame_system::Config>::Event>;
+		/// Finds the account that the equivocating author key is mapped to
+		type AccountLookup: AccountLookup<Self::AccountId>;
+		/// Decides the consequences for the equivocating account
+		type OnEquivocation: OnEquivocation<Self::AccountId>;
+		/// The origin that confirms reported equivocations and has the offenders slashed
+		type SlashOrigin: EnsureOrigin<Self::Origin>;
+		/// How many blocks back equivocations can be reported, and how long reports are kept
+		#[pallet::constant]
+		type ReportWindow: Get<Self::BlockNumber>;
+		/// The largest encoded equivocation proof that is accepted
+		#[pallet::constant]
+		type MaxProofSize: Get<u32>;
+		/// The priority of equivocation reports in the transaction pool
+		#[pallet::constant]
+		type UnsignedPriority: Get<TransactionPriority>;
+	}
+
+	#[pallet::error]
+	pub enum Error<T> {
+		/// The proof does not show two different blocks sealed by the author on the same parent
+		InvalidEquivocationProof,
+		/// The proof is larger than `MaxProofSize`
+		ProofTooLarge,
+		/// The height is in the future or older than `ReportWindow` blocks
+		OutsideReportWindow,
+		/// The author was already reported for this height
+		AlreadyReported,
+		/// The author key is not mapped to any account
+		UnknownAuthor,
+		/// The author was not reported for this height
+		NotReported,
+		/// The offender was already slashed for this report
+		AlreadySlashed,
+	}
+
+	#[pallet::event]
+	#[pallet::generate_deposit(pub(crate) fn deposit_event)]
+	pub enum Event<T: Config> {
+		/// Author key, Account it is mapped to, Height at which it equivocated
+		EquivocationReported(AuthorId, T::AccountId, T::BlockNumber),
+		/// Author key, Account it is mapped to, Height of the equivocation it was slashed for
+		EquivocationSlashed(AuthorId, T::AccountId, T::BlockNumber),
+	}
+
+	#[pallet::hooks]
+	impl<T: Config> Hooks<BlockNumberFor<T>> for Pallet<T> {
+		fn on_initialize(now: T::BlockNumber) -> Weight {
+			// Reports at this height can no longer be made nor slashed.
+			if now >= T::ReportWindow::get() {
+				<Reports<T>>::remove_prefix(now - T::ReportWindow::get());
+			}
+			T::DbWeight::get().writes(1)
+		}
+	}
+
+	#[pallet::call]
+	impl<T: Config> Pallet<T> {
+		/// Report an author that sealed two different blocks on the same parent. This is
+		/// submitted by nodes as an unsigned extrinsic, see `submit_unsigned_equivocation_report`.
+		#[pallet::weight(T::DbWeight::get().reads_writes(2, 1) + CHECK_PROOF_WEIGHT)]
+		pub fn report_equivocation_unsigned(
+			origin: OriginFor<T>,
+			equivocation_proof: Box<EquivocationProof<T::Header>>,
+		) -> DispatchResultWithPostInfo {
+			ensure_none(origin)?;
+			let (author, height, offender) = Self::check_report(*equivocation_proof)?;
+
+			<Reports<T>>::insert(height, &author, (offender.clone(), false));
+			Self::deposit_event(Event::EquivocationReported(author, offender, height));
+
+			Ok(Default::default())
+		}
+
+		/// Confirm that the report of `author` at `height` is an actual equivocation and pass the
+		/// offending account on to `OnEquivocation`.
+		#[pallet::weight(T::DbWeight::get().reads_writes(5, 5))]
+		pub fn slash(
+			origin: OriginFor<T>,
+			author: AuthorId,
+			height: T::BlockNumber,
+		) -> DispatchResultWithPostInfo {
+			T::SlashOrigin::ensure_origin(origin)?;
+			let (offender, slashed) =
+				<Reports<T>>::get(height, &author).ok_or(Error::<T>::NotReported)?;
+			ensure!(!slashed, Error::<T>::AlreadySlashed);
+
+			<Reports<T>>::insert(height, &author, (offender.clone(), true));
+			T::OnEquivocation::on_equivocation(&offender);
+			Self::deposit_event(Event::EquivocationSlashed(author, offender, height));
+
+			Ok(Default::default())
+		}
+	}
+
+	impl<T: Config> Pallet<T> {
+		/// The author key, height and offending account of a report that can be recorded.
+		fn check_report(
+			equivocation_proof: EquivocationProof<T::Header>,
+		) -> Result<(AuthorId, T::BlockNumber, T::AccountId), Error<T>> {
+			ensure!(
+				equivocation_proof.encoded_size() <= T::MaxProofSize::get() as usize,
+				Error::<T>::ProofTooLarge
+			);
+			let author = equivocation_proof.author.clone();
+			let height = *equivocation_proof.first_header.number();
+			let now = frame_system::Pallet::<T>::block_number();
+			ensure!(
+				height <= now && height.saturating_add(T::ReportWindow::get()) > now,
+				Error::<T>::OutsideReportWindow
+			);
+			ensure!(
+				check_equivocation_proof(equivocation_proof),
+				Error::<T>::InvalidEquivocationProof
+			);
+			ensure!(
+				!<Reports<T>>::contains_key(height, &author),
+				Error::<T>::AlreadyReported
+			);
+			let offender =
+				T::AccountLookup::lookup_account(&author).ok_or(Error::<T>::UnknownAuthor)?;
+
+			Ok((author, height, offender))
+		}
+
+		/// Submit an equivocation report to the transaction pool as an unsigned extrinsic.
+		/// This only works in an offchain context, such as a runtime API call from the node.
+		pub fn submit_unsigned_equivocation_report(
+			equivocation_proof: EquivocationProof<T::Header>,
+		) -> Option<()> {
+			let call = Call::report_equivocation_unsigned(Box::new(equivocation_proof));
+			SubmitTransaction::<T, Call<T>>::submit_unsigned_transaction(call.into()).ok()
+		}
+	}
+
+	#[pallet::validate_unsigned]
+	impl<T: Config> ValidateUnsigned for Pallet<T> {
+		type Call = Call<T>;
+
+		fn validate_unsigned(_source: TransactionSource, call: &Self::Call) -> TransactionValidity {
+			if let Call::report_equivocation_unsigned(equivocation_proof) = call {
+				// The proof is checked in full, so reports can come from any source.
+				let (author, height, _) =
+					Self::check_report(*equivocation_proof.clone()).map_err(|e| match e {
+						Error::<T>::AlreadyReported | Error::<T>::OutsideReportWindow => {
+							InvalidTransaction::Stale
+						}
+						Error::<T>::ProofTooLarge => InvalidTransaction::ExhaustsResources,
+						_ => InvalidTransaction::BadProof,
+					})?;
+
+				ValidTransaction::with_tag_prefix("AuthorEquivocation")
+					.priority(T::UnsignedPriority::get())
+					.and_provides((author, height))
+					.longevity(REPORT_LONGEVITY)
+					.propagate(true)
+					.build()
+			} else {
+				InvalidTransaction::Call.into()
+			}
+		}
+	}
+
+	/// The equivocations reported in the last `ReportWindow` blocks, by height and author key,
+	/// with the account the author key is mapped to and whether it was slashed.
+	#[pallet::storage]
+	#[pallet::getter(fn reports)]
+	pub type Reports<T: Config> = StorageDoubleMap<
+		_,
+		Twox64Concat,
+		T::BlockNumber,
+		Blake2_128Concat,
+		AuthorId,
+		(T::AccountId, bool),
+		OptionQuery,
+	>;
+}
@@ -0,0 +1,190 @@
+// Copyright 2019-2021 PureStake Inc.
+// This file is part of Moonbeam.
+
+// Moonbeam is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Moonbeam is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Moonbeam.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Test utilities
+use super::*;
+use crate as author_equivocation;
+use author_inherent::{
+	AccountLookup, AuthorId, AuthorPair, EquivocationProof, OnEquivocation, ENGINE_ID,
+};
+use frame_support::{construct_runtime, parameter_types};
+use parity_scale_codec::Encode;
+use sp_core::{Pair, H256};
+use sp_io;
+use sp_runtime::{
+	testing::Header,
+	traits::{BlakeTwo256, Header as HeaderT, IdentityLookup},
+	transaction_validity::TransactionPriority,
+	Digest, DigestItem,
+};
+use std::cell::RefCell;
+
+pub type AccountId = u64;
+pub type BlockNumber = u64;
+
+pub type UncheckedExtrinsic = frame_system::mocking::MockUncheckedExtrinsic<Test>;
+type Block = frame_system::mocking::MockBlock<Test>;
+
+// Configure a mock runtime to test the pallet.
+construct_runtime!(
+	pub enum Test where
+		Block = Block,
+		NodeBlock = Block,
+		UncheckedExtrinsic = UncheckedExtrinsic,
+	{
+		System: frame_system::{Pallet, Call, Config, Storage, Event<T>},
+		AuthorEquivocation: author_equivocation::{Pallet, Call, Storage, Event<T>, ValidateUnsigned},
+	}
+);
+
+parameter_types! {
+	pub const BlockHashCount: u64 = 250;
+	pub const SS58Prefix: u8 = 42;
+}
+impl frame_system::Config for Test {
+	type BaseCallFilter = ();
+	type DbWeight = ();
+	type Origin = Origin;
+	type Index = u64;
+	type BlockNumber = BlockNumber;
+	type Call = Call;
+	type Hash = H256;
+	type Hashing = BlakeTwo256;
+	type AccountId = AccountId;
+	type Lookup = IdentityLookup<Self::AccountId>;
+	type Header = Header;
+	type Event = Event;
+	type BlockHashCount = BlockHashCount;
+	type Version = ();
+	type PalletInfo = PalletInfo;
+	type AccountData = ();
+	type OnNewAccount = ();
+	type OnKilledAccount = ();
+	type SystemWeightInfo = ();
+	type BlockWeights = ();
+	type BlockLength = ();
+	type SS58Prefix = SS58Prefix;
+}
+impl<C> frame_system::offchain::SendTransactionTypes<C> for Test
+where
+	Call: From<C>,
+{
+	type OverarchingCall = Call;
+	type Extrinsic = UncheckedExtrinsic;
+}
+
+/// The author keys generated from seeds 1 and 2 belong to accounts 1 and 2 respectively.
+/// Any other author key is unmapped.
+pub struct SeedLookup;
+impl AccountLookup<AccountId> for SeedLookup {
+	fn lookup_account(author: &AuthorId) -> Option<AccountId> {
+		(1..=2u8)
+			.find(|seed| author_pair(*seed).public() == *author)
+			.map(AccountId::from)
+	}
+}
+
+thread_local! {
+	static OFFENDERS: RefCell<Vec<AccountId>> = RefCell::new(vec![]);
+}
+
+/// Records the accounts it is told about.
+pub struct RecordOffenders;
+impl OnEquivocation<AccountId> for RecordOffenders {
+	fn on_equivocation(offender: &AccountId) {
+		OFFENDERS.with(|offenders| offenders.borrow_mut().push(*offender));
+	}
+}
+
+/// The accounts that were reported for equivocating so far.
+pub(crate) fn offenders() -> Vec<AccountId> {
+	OFFENDERS.with(|offenders| offenders.borrow().clone())
+}
+
+parameter_types! {
+	pub const ReportWindow: BlockNumber = 10;
+	pub const MaxProofSize: u32 = 1_000;
+	pub const UnsignedPriority: TransactionPriority = 7;
+}
+impl Config for Test {
+	type Event = Event;
+	type AccountLookup = SeedLookup;
+	type OnEquivocation = RecordOffenders;
+	type SlashOrigin = frame_system::EnsureRoot<AccountId>;
+	type ReportWindow = ReportWindow;
+	type MaxProofSize = MaxProofSize;
+	type UnsignedPriority = UnsignedPriority;
+}
+
+pub(crate) fn author_pair(seed: u8) -> AuthorPair {
+	AuthorPair::from_seed(&[seed; 32])
+}
+
+/// A header at height `n` that reports and is sealed by the author key generated from `seed`.
+/// Different `extrinsics_root`s give different headers at the same height.
+pub(crate) fn sealed_header(n: BlockNumber, extrinsics_root: H256, seed: u8) -> Header {
+	sealed_header_with_logs(n, extrinsics_root, seed, vec![])
+}
+
+/// Same as `sealed_header`, with `logs` added to the digest before it is sealed.
+pub(crate) fn sealed_header_with_logs(
+	n: BlockNumber,
+	extrinsics_root: H256,
+	seed: u8,
+	logs: Vec<DigestItem<H256>>,
+) -> Header {
+	let author = author_pair(seed);
+	let mut header = Header::new(
+		n,
+		extrinsics_root,
+		Default::default(),
+		Default::default(),
+		Digest {
+			logs: vec![DigestItem::PreRuntime(ENGINE_ID, author.public().encode())]
+				.into_iter()
+				.chain(logs)
+				.collect(),
+		},
+	);
+	let seal = author.sign(header.hash().as_ref());
+	header
+		.digest_mut()
+		.push(DigestItem::Seal(ENGINE_ID, seal.encode()));
+	header
+}
+
+/// A valid proof of the author key generated from `seed` equivocating at height `n`.
+pub(crate) fn equivocation_proof(n: BlockNumber, seed: u8) -> EquivocationProof<Header> {
+	EquivocationProof {
+		author: author_pair(seed).public(),
+		first_header: sealed_header(n, H256::repeat_byte(1), seed),
+		second_header: sealed_header(n, H256::repeat_byte(2), seed),
+	}
+}
+
+pub(crate) fn new_test_ext() -> sp_io::TestExternalities {
+	let t = frame_system::GenesisConfig::default()
+		.build_storage::<Test>()
+		.expect("Frame system builds valid default genesis config");
+
+	let mut ext = sp_io::TestExternalities::new(t);
+	ext.execute_with(|| System::set_block_number(10));
+	ext
+}
+
+pub(crate) fn last_event() -> Event {
+	System::events().pop().expect("Event expected").event
+}
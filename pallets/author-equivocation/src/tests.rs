@@ -0,0 +1,283 @@
+// Copyright 2019-2021 PureStake Inc.
+// This file is part of Moonbeam.
+
+// Moonbeam is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Moonbeam is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Moonbeam.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Unit testing
+use crate::mock::{
+	author_pair, equivocation_proof, last_event, new_test_ext, offenders, sealed_header,
+	sealed_header_with_logs, AuthorEquivocation, Call, Event as MetaEvent, Origin, System, Test,
+};
+use crate::{Error, Event};
+use frame_support::{assert_noop, assert_ok, traits::OnInitialize, unsigned::ValidateUnsigned};
+use sp_core::{
+	offchain::{testing::TestTransactionPoolExt, TransactionPoolExt},
+	H256,
+};
+use sp_runtime::{
+	transaction_validity::{InvalidTransaction, TransactionSource},
+	DigestItem, DispatchError,
+};
+
+#[test]
+fn reporting_works() {
+	new_test_ext().execute_with(|| {
+		assert_ok!(AuthorEquivocation::report_equivocation_unsigned(
+			Origin::none(),
+			Box::new(equivocation_proof(5, 1))
+		));
+		assert_eq!(
+			last_event(),
+			MetaEvent::author_equivocation(Event::EquivocationReported(
+				author_pair(1).public(),
+				1,
+				5
+			))
+		);
+		assert_eq!(
+			AuthorEquivocation::reports(5, author_pair(1).public()),
+			Some((1, false))
+		);
+		// Reports are not slashed until they are confirmed
+		assert_eq!(offenders(), vec![]);
+	});
+}
+
+#[test]
+fn slashing_confirmed_reports_works() {
+	new_test_ext().execute_with(|| {
+		assert_ok!(AuthorEquivocation::report_equivocation_unsigned(
+			Origin::none(),
+			Box::new(equivocation_proof(5, 1))
+		));
+		assert_ok!(AuthorEquivocation::slash(
+			Origin::root(),
+			author_pair(1).public(),
+			5
+		));
+		assert_eq!(
+			last_event(),
+			MetaEvent::author_equivocation(Event::EquivocationSlashed(
+				author_pair(1).public(),
+				1,
+				5
+			))
+		);
+		assert_eq!(
+			AuthorEquivocation::reports(5, author_pair(1).public()),
+			Some((1, true))
+		);
+		assert_eq!(offenders(), vec![1]);
+
+		assert_noop!(
+			AuthorEquivocation::slash(Origin::root(), author_pair(1).public(), 5),
+			Error::<Test>::AlreadySlashed
+		);
+	});
+}
+
+#[test]
+fn only_slash_origin_can_slash_reported_equivocations() {
+	new_test_ext().execute_with(|| {
+		assert_ok!(AuthorEquivocation::report_equivocation_unsigned(
+			Origin::none(),
+			Box::new(equivocation_proof(5, 1))
+		));
+		assert_noop!(
+			AuthorEquivocation::slash(Origin::signed(2), author_pair(1).public(), 5),
+			DispatchError::BadOrigin
+		);
+		assert_noop!(
+			AuthorEquivocation::slash(Origin::root(), author_pair(1).public(), 6),
+			Error::<Test>::NotReported
+		);
+		assert_eq!(offenders(), vec![]);
+	});
+}
+
+#[test]
+fn only_recent_heights_can_be_reported() {
+	new_test_ext().execute_with(|| {
+		// The current block is 10 and the window 10 blocks
+		assert_noop!(
+			AuthorEquivocation::report_equivocation_unsigned(
+				Origin::none(),
+				Box::new(equivocation_proof(11, 1))
+			),
+			Error::<Test>::OutsideReportWindow
+		);
+		System::set_block_number(20);
+		assert_noop!(
+			AuthorEquivocation::report_equivocation_unsigned(
+				Origin::none(),
+				Box::new(equivocation_proof(10, 1))
+			),
+			Error::<Test>::OutsideReportWindow
+		);
+		assert_ok!(AuthorEquivocation::report_equivocation_unsigned(
+			Origin::none(),
+			Box::new(equivocation_proof(11, 1))
+		));
+	});
+}
+
+#[test]
+fn reports_are_pruned_when_they_leave_the_window() {
+	new_test_ext().execute_with(|| {
+		assert_ok!(AuthorEquivocation::report_equivocation_unsigned(
+			Origin::none(),
+			Box::new(equivocation_proof(5, 1))
+		));
+		AuthorEquivocation::on_initialize(14);
+		assert!(AuthorEquivocation::reports(5, author_pair(1).public()).is_some());
+		AuthorEquivocation::on_initialize(15);
+		assert!(AuthorEquivocation::reports(5, author_pair(1).public()).is_none());
+	});
+}
+
+#[test]
+fn large_proofs_are_rejected() {
+	new_test_ext().execute_with(|| {
+		let mut proof = equivocation_proof(5, 1);
+		proof.second_header = sealed_header_with_logs(
+			5,
+			H256::repeat_byte(2),
+			1,
+			vec![DigestItem::Other(vec![0; 1_000])],
+		);
+		assert_noop!(
+			AuthorEquivocation::report_equivocation_unsigned(Origin::none(), Box::new(proof)),
+			Error::<Test>::ProofTooLarge
+		);
+	});
+}
+
+#[test]
+fn signed_reports_are_rejected() {
+	new_test_ext().execute_with(|| {
+		assert_noop!(
+			AuthorEquivocation::report_equivocation_unsigned(
+				Origin::signed(2),
+				Box::new(equivocation_proof(5, 1))
+			),
+			DispatchError::BadOrigin
+		);
+	});
+}
+
+#[test]
+fn invalid_proof_is_rejected() {
+	new_test_ext().execute_with(|| {
+		let mut proof = equivocation_proof(5, 1);
+		proof.second_header = sealed_header(6, H256::repeat_byte(2), 1);
+		assert_noop!(
+			AuthorEquivocation::report_equivocation_unsigned(Origin::none(), Box::new(proof)),
+			Error::<Test>::InvalidEquivocationProof
+		);
+
+		let mut proof = equivocation_proof(5, 1);
+		proof.author = author_pair(2).public();
+		assert_noop!(
+			AuthorEquivocation::report_equivocation_unsigned(Origin::none(), Box::new(proof)),
+			Error::<Test>::InvalidEquivocationProof
+		);
+	});
+}
+
+#[test]
+fn unmapped_author_cannot_be_reported() {
+	new_test_ext().execute_with(|| {
+		assert_noop!(
+			AuthorEquivocation::report_equivocation_unsigned(
+				Origin::none(),
+				Box::new(equivocation_proof(5, 3))
+			),
+			Error::<Test>::UnknownAuthor
+		);
+	});
+}
+
+#[test]
+fn author_is_reported_once_per_height() {
+	new_test_ext().execute_with(|| {
+		assert_ok!(AuthorEquivocation::report_equivocation_unsigned(
+			Origin::none(),
+			Box::new(equivocation_proof(5, 1))
+		));
+		assert_noop!(
+			AuthorEquivocation::report_equivocation_unsigned(
+				Origin::none(),
+				Box::new(equivocation_proof(5, 1))
+			),
+			Error::<Test>::AlreadyReported
+		);
+		assert_ok!(AuthorEquivocation::report_equivocation_unsigned(
+			Origin::none(),
+			Box::new(equivocation_proof(6, 1))
+		));
+		assert!(AuthorEquivocation::reports(6, author_pair(1).public()).is_some());
+	});
+}
+
+#[test]
+fn validate_unsigned_checks_the_report() {
+	new_test_ext().execute_with(|| {
+		let call = crate::Call::report_equivocation_unsigned(Box::new(equivocation_proof(5, 1)));
+		let valid = AuthorEquivocation::validate_unsigned(TransactionSource::External, &call)
+			.expect("The report is valid");
+		assert_eq!(valid.priority, 7);
+
+		let mut proof = equivocation_proof(5, 1);
+		proof.second_header = proof.first_header.clone();
+		assert_eq!(
+			AuthorEquivocation::validate_unsigned(
+				TransactionSource::External,
+				&crate::Call::report_equivocation_unsigned(Box::new(proof))
+			),
+			InvalidTransaction::BadProof.into()
+		);
+
+		assert_ok!(AuthorEquivocation::report_equivocation_unsigned(
+			Origin::none(),
+			Box::new(equivocation_proof(5, 1))
+		));
+		assert_eq!(
+			AuthorEquivocation::validate_unsigned(TransactionSource::External, &call),
+			InvalidTransaction::Stale.into()
+		);
+	});
+}
+
+#[test]
+fn submitting_report_reaches_the_pool() {
+	let (pool, state) = TestTransactionPoolExt::new();
+	let mut ext = new_test_ext();
+	ext.register_extension(TransactionPoolExt::new(pool));
+	ext.execute_with(|| {
+		assert_eq!(
+			AuthorEquivocation::submit_unsigned_equivocation_report(equivocation_proof(5, 1)),
+			Some(())
+		);
+		let transactions = &state.read().transactions;
+		assert_eq!(transactions.len(), 1);
+		let extrinsic: crate::mock::UncheckedExtrinsic =
+			parity_scale_codec::Decode::decode(&mut &transactions[0][..]).unwrap();
+		assert_eq!(
+			extrinsic.call,
+			Call::AuthorEquivocation(crate::Call::report_equivocation_unsigned(Box::new(
+				equivocation_proof(5, 1)
+			)))
+		);
+	});
+}
@@ -22,16 +22,65 @@
 //!
 //! Using a randomness beacon supplied by the `Randomness` trait, this pallet takes the set of
 //! currently staked accounts from pallet stake, and filters them down to a pseudorandom subset.
-//! The current technique gives no preference to any particular author. In the future, we could
-//! disfavor authors who are authoring a disproportionate amount of the time in an attempt to
-//! "even the playing field".
+//! By default the filter gives no preference to any particular author. Governance can opt into
+//! disfavoring authors who are authoring a disproportionate amount of the time in an attempt to
+//! "even the playing field" by setting a `MonopolyPenalty`. Authors who have been awarded more
+//! than `MONOPOLY_FACTOR` times the average points in the current round then have their chance of
+//! being picked reduced by that percentage.
 
 #![cfg_attr(not(feature = "std"), no_std)]
 
 use frame_support::pallet;
+use frame_support::traits::Vec;
+use sp_runtime::Percent;
 
 pub use pallet::*;
 
+#[cfg(test)]
+mod tests;
+
+/// Authors awarded more than this many times the average points in a round are penalized.
+pub const MONOPOLY_FACTOR: u64 = 2;
+
+/// Weight of an author that is not penalized.
+const FULL_WEIGHT: u32 = 100;
+
+/// The relative chance of being picked of each author, given the points each was awarded in the
+/// current round. Authors above `MONOPOLY_FACTOR` times the average get `penalty` taken off.
+fn eligibility_weights(points: &[u32], penalty: Percent) -> Vec<u32> {
+	let count = points.len() as u64;
+	let total: u64 = points.iter().map(|pts| *pts as u64).sum();
+
+	points
+		.iter()
+		.map(|pts| {
+			if *pts as u64 * count > MONOPOLY_FACTOR * total {
+				FULL_WEIGHT - penalty.mul_ceil(FULL_WEIGHT)
+			} else {
+				FULL_WEIGHT
+			}
+		})
+		.collect()
+}
+
+/// Pick an index with probability proportional to its weight. Falls back to a uniform pick when
+/// all the weights are zero.
+fn weighted_index(weights: &[u32], random: u32) -> usize {
+	let total: u64 = weights.iter().map(|weight| *weight as u64).sum();
+	if total == 0 {
+		return random as usize % weights.len();
+	}
+
+	let mut target = random as u64 % total;
+	for (index, weight) in weights.iter().enumerate() {
+		if target < *weight as u64 {
+			return index;
+		}
+		target -= *weight as u64;
+	}
+	unreachable!("target is below the sum of the weights; qed")
+}
+
 #[pallet]
 pub mod pallet {
 
@@ -41,7 +90,7 @@ pub mod pallet {
 	use frame_support::traits::Vec;
 	use frame_system::pallet_prelude::*;
 	use sp_core::H256;
-	use sp_runtime::Percent;
+	use sp_runtime::{traits::Zero, Percent};
 
 	/// The Author Filter pallet
 	#[pallet::pallet]
@@ -73,6 +122,20 @@ pub mod pallet {
 			let num_eligible = EligibleRatio::<T>::get().mul_ceil(staked.len());
 			let mut eligible = Vec::with_capacity(num_eligible);
 
+			// Without a penalty every author is equally likely to be picked, which we do without
+			// computing any weights.
+			let penalty = MonopolyPenalty::<T>::get();
+			let mut weights = if penalty.is_zero() {
+				Vec::new()
+			} else {
+				let round = <parachain_staking::Pallet<T>>::round().current;
+				let points = staked
+					.iter()
+					.map(|author| <parachain_staking::Pallet<T>>::awarded_pts(round, author))
+					.collect::<Vec<_>>();
+				crate::eligibility_weights(&points, penalty)
+			};
+
 			// Grab the relay parent height as a temporary source of relay-based entropy
 			let validation_data = cumulus_pallet_parachain_system::Module::<T>::validation_data()
 				.expect("validation data was set in parachain system inherent");
@@ -97,14 +160,21 @@ pub mod pallet {
 				];
 				let randomness = T::RandomnessSource::random(&subject).0;
 				// Cast to u32 first so we get the same result on wasm and 64-bit platforms.
-				let index = (randomness.to_low_u64_be() as u32) as usize;
+				let random = randomness.to_low_u64_be() as u32;
+				let index = if weights.is_empty() {
+					random as usize % staked.len()
+				} else {
+					let index = crate::weighted_index(&weights, random);
+					weights.remove(index);
+					index
+				};
 
 				// Move the selected author from the original vector into the eligible vector
 				// TODO we could short-circuit this check by returning early when the claimed
 				// author is selected. For now I'll leave it like this because:
 				// 1. it is easier to understand what our core filtering logic is
 				// 2. we currently show the entire filtered set in the debug event
-				eligible.push(staked.remove(index % staked.len()));
+				eligible.push(staked.remove(index));
 
 				// Print some logs for debugging purposes.
 				log::trace!(target:"author-filter", "Filtering Authors");
@@ -158,6 +228,19 @@ pub mod pallet {
 
 			Ok(Default::default())
 		}
+
+		/// Update the monopoly penalty. Zero disables it. Intended to be called by governance.
+		#[pallet::weight(0)]
+		pub fn set_monopoly_penalty(
+			origin: OriginFor<T>,
+			new: Percent,
+		) -> DispatchResultWithPostInfo {
+			ensure_root(origin)?;
+			MonopolyPenalty::<T>::put(&new);
+			<Pallet<T>>::deposit_event(Event::MonopolyPenaltyUpdated(new));
+
+			Ok(Default::default())
+		}
 	}
 
 	/// The percentage of active staked authors that will be eligible at each height.
@@ -170,11 +253,18 @@ pub mod pallet {
 		Percent::from_percent(50)
 	}
 
+	/// How much less likely authors who have been awarded a disproportionate amount of points in
+	/// the current round are to be eligible. Zero (the default) disables the penalty.
+	#[pallet::storage]
+	pub type MonopolyPenalty<T: Config> = StorageValue<_, Percent, ValueQuery>;
+
 	#[pallet::event]
 	#[pallet::generate_deposit(fn deposit_event)]
 	pub enum Event<T: Config> {
 		/// The amount of eligible authors for the filter to select has been changed.
 		EligibleUpdated(Percent),
+		/// The penalty for authors with a disproportionate amount of points has been changed.
+		MonopolyPenaltyUpdated(Percent),
 		/// The staked authors have been filtered to these eligible authors in this block.
 		/// This is a debugging and development event and should be removed eventually.
 		/// Fields are: para block height, relay block height, eligible authors
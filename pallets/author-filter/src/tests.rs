@@ -0,0 +1,116 @@
+// Copyright 2019-2021 PureStake Inc.
+// This file is part of Moonbeam.
+
+// Moonbeam is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Moonbeam is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Moonbeam.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Unit testing of the monopoly weighting
+use crate::{eligibility_weights, weighted_index, FULL_WEIGHT};
+use sp_core::hashing::blake2_256;
+use sp_runtime::Percent;
+
+/// Points of ten authors where the first one authored far more than the others.
+const POINTS: [u32; 10] = [200, 20, 20, 20, 20, 20, 20, 20, 20, 20];
+
+/// How many times each author is picked over `trials` picks with deterministic randomness.
+fn pick_counts(weights: &[u32], trials: u32) -> Vec<u32> {
+	let mut counts = vec![0; weights.len()];
+	for trial in 0..trials {
+		let hash = blake2_256(&trial.to_le_bytes());
+		let random = u32::from_le_bytes([hash[0], hash[1], hash[2], hash[3]]);
+		counts[weighted_index(weights, random)] += 1;
+	}
+	counts
+}
+
+#[test]
+fn no_penalty_gives_full_weights() {
+	assert_eq!(
+		eligibility_weights(&POINTS, Percent::from_percent(0)),
+		vec![FULL_WEIGHT; 10]
+	);
+}
+
+#[test]
+fn only_authors_far_above_average_are_penalized() {
+	// The average is 38, so only the first author is above twice the average
+	let mut expected = vec![FULL_WEIGHT; 10];
+	expected[0] = 25;
+	assert_eq!(
+		eligibility_weights(&POINTS, Percent::from_percent(75)),
+		expected
+	);
+	// Twice the average is not enough
+	assert_eq!(
+		eligibility_weights(&[40, 10, 10], Percent::from_percent(75)),
+		vec![FULL_WEIGHT; 3]
+	);
+	// Nobody is penalized before any points are awarded
+	assert_eq!(
+		eligibility_weights(&[0, 0, 0], Percent::from_percent(75)),
+		vec![FULL_WEIGHT; 3]
+	);
+}
+
+#[test]
+fn weighted_index_walks_cumulative_weights() {
+	let weights = [10, 0, 30];
+	assert_eq!(weighted_index(&weights, 0), 0);
+	assert_eq!(weighted_index(&weights, 9), 0);
+	assert_eq!(weighted_index(&weights, 10), 2);
+	assert_eq!(weighted_index(&weights, 39), 2);
+	assert_eq!(weighted_index(&weights, 40), 0);
+}
+
+#[test]
+fn zero_weights_fall_back_to_uniform() {
+	assert_eq!(weighted_index(&[0, 0, 0], 4), 1);
+}
+
+#[test]
+fn distribution_is_uniform_without_penalty() {
+	let counts = pick_counts(
+		&eligibility_weights(&POINTS, Percent::from_percent(0)),
+		10_000,
+	);
+	for count in counts {
+		assert!(count > 900 && count < 1100, "count was {}", count);
+	}
+}
+
+#[test]
+fn distribution_disfavors_monopolist() {
+	// Weights are 50 for the monopolist and 100 for the other nine: it is expected to be picked
+	// 50 / 950 of the time, and each other author 100 / 950 of the time.
+	let counts = pick_counts(
+		&eligibility_weights(&POINTS, Percent::from_percent(50)),
+		10_000,
+	);
+	assert!(
+		counts[0] > 450 && counts[0] < 600,
+		"count was {}",
+		counts[0]
+	);
+	for count in &counts[1..] {
+		assert!(*count > 950 && *count < 1150, "count was {}", count);
+	}
+}
+
+#[test]
+fn full_penalty_excludes_monopolist() {
+	let counts = pick_counts(
+		&eligibility_weights(&POINTS, Percent::from_percent(100)),
+		10_000,
+	);
+	assert_eq!(counts[0], 0);
+}
@@ -15,14 +15,27 @@
 // along with Moonbeam.  If not, see <http://www.gnu.org/licenses/>.
 
 //! Pallet that allows block authors to include their identity in a block via an inherent.
-//! Currently the author does not _prove_ their identity, just states it. So it should not be used,
-//! for things like equivocation slashing that require authenticated authorship information.
+//!
+//! Authors are identified by an `AuthorId`, a session key kept in the node keystore. The
+//! inherent only _states_ which key authored the block. The proof comes from the block seal:
+//! after the block is built, the author signs its pre-hash with that key and appends the
+//! signature as a `Seal` digest. The seal is checked both by the node's import queue and by the
+//! `BlockExecutor` exposed here, which is what the relay chain validators run in
+//! `validate_block`.
+//!
+//! The author key is also reported in a `PreRuntime` digest, so it can be learned from the
+//! header alone. The inherent must agree with that digest, and it is the digest that the seal is
+//! checked against and that `find_author` reads.
+//!
+//! The account that gets credited with the block (and that has to be eligible to author it) is
+//! looked up from the author key through the `AccountLookup` trait. See the `author-mapping`
+//! pallet for an implementation.
 
 #![cfg_attr(not(feature = "std"), no_std)]
 
 use frame_support::{
 	decl_error, decl_module, decl_storage, ensure, log,
-	traits::FindAuthor,
+	traits::{ExecuteBlock, FindAuthor},
 	weights::{DispatchClass, Weight},
 };
 use frame_system::{ensure_none, Config as System};
@@ -30,8 +43,11 @@ use parity_scale_codec::{Decode, Encode};
 #[cfg(feature = "std")]
 use sp_inherents::ProvideInherentData;
 use sp_inherents::{InherentData, InherentIdentifier, IsFatalError, ProvideInherent};
-use sp_runtime::{ConsensusEngineId, DigestItem, RuntimeString};
-use sp_std::vec::Vec;
+use sp_runtime::{
+	traits::{Block as BlockT, Header as HeaderT},
+	ConsensusEngineId, DigestItem, KeyTypeId, RuntimeAppPublic, RuntimeDebug, RuntimeString,
+};
+use sp_std::{marker::PhantomData, vec::Vec};
 
 /// The given account ID is the author of the current block.
 pub trait EventHandler<Author> {
@@ -55,6 +71,38 @@ impl<T> CanAuthor<T> for () {
 	}
 }
 
+/// Lookup of the account that is credited with the blocks sealed by an author key.
+pub trait AccountLookup<AccountId> {
+	fn lookup_account(author: &AuthorId) -> Option<AccountId>;
+}
+
+/// Consequences for the account whose author key sealed two different blocks on the same parent.
+/// See `parachain-staking` for an implementation.
+pub trait OnEquivocation<AccountId> {
+	fn on_equivocation(offender: &AccountId);
+}
+
+impl<T> OnEquivocation<T> for () {
+	fn on_equivocation(_offender: &T) {}
+}
+
+mod app {
+	use sp_application_crypto::{app_crypto, ecdsa};
+	app_crypto!(ecdsa, crate::KEY_TYPE);
+}
+
+sp_application_crypto::with_pair! {
+	/// An author key pair.
+	pub type AuthorPair = app::Pair;
+}
+
+/// The public key that identifies a block author. Authors keep the key in their node's keystore
+/// and use it to seal their blocks.
+pub type AuthorId = app::Public;
+
+/// A seal made with an author key.
+pub type AuthorSignature = app::Signature;
+
 pub trait Config: System {
 	/// Other pallets that want to be informed about block authorship
 	type EventHandler: EventHandler<Self::AccountId>;
@@ -71,6 +119,9 @@ pub trait Config: System {
 	/// If the pallet that implements this trait depends on an inherent, that inherent **must**
 	/// be included before this one.
 	type FinalCanAuthor: CanAuthor<Self::AccountId>;
+
+	/// Finds the account behind the author key that sealed the block.
+	type AccountLookup: AccountLookup<Self::AccountId>;
 }
 
 decl_error! {
@@ -79,6 +130,10 @@ decl_error! {
 		AuthorAlreadySet,
 		/// The author in the inherent is not an eligible author.
 		CannotBeAuthor,
+		/// The author key in the inherent is not mapped to any account.
+		NoAccountId,
+		/// The author in the inherent is not the author reported in the pre-runtime digest.
+		AuthorDigestMismatch,
 	}
 }
 
@@ -103,30 +158,27 @@ decl_module! {
 			0,
 			DispatchClass::Mandatory
 		)]
-		fn set_author(origin, author: T::AccountId) {
+		fn set_author(origin, author: AuthorId) {
 			log::trace!(target:"author-inherent", "In the author inherent dispatchable");
 
 			ensure_none(origin)?;
 			ensure!(<Author<T>>::get().is_none(), Error::<T>::AuthorAlreadySet);
-			ensure!(T::FinalCanAuthor::can_author(&author), Error::<T>::CannotBeAuthor);
+			ensure!(
+				Self::reported_author().as_ref() == Some(&author),
+				Error::<T>::AuthorDigestMismatch
+			);
+			let account = T::AccountLookup::lookup_account(&author).ok_or(Error::<T>::NoAccountId)?;
+			ensure!(T::FinalCanAuthor::can_author(&account), Error::<T>::CannotBeAuthor);
 
 			// Update storage
 			log::trace!(
 				target:"author-inherent",
 				"Passed ensures. About to write claimed author to storage."
 			);
-			Author::<T>::put(&author);
-
-			// Add a digest item so Apps can detect the block author
-			// For now we use the Consensus digest item.
-			// Maybe this will change later.
-			frame_system::Pallet::<T>::deposit_log(DigestItem::<T::Hash>::Consensus(
-				ENGINE_ID,
-				author.encode(),
-			));
+			Author::<T>::put(&account);
 
 			// Notify any other pallets that are listening (eg rewards) about the author
-			T::EventHandler::note_author(author);
+			T::EventHandler::note_author(account);
 		}
 
 		fn on_finalize(_n: T::BlockNumber) {
@@ -143,20 +195,163 @@ decl_module! {
 	}
 }
 
+impl<T: Config> Module<T> {
+	/// The author key reported in the current block's pre-runtime digest.
+	fn reported_author() -> Option<AuthorId> {
+		author_from_digests(
+			frame_system::Pallet::<T>::digest()
+				.logs()
+				.iter()
+				.filter_map(|digest| digest.as_pre_runtime()),
+		)
+	}
+}
+
+impl<T: Config> sp_runtime::BoundToRuntimeAppPublic for Module<T> {
+	type Public = AuthorId;
+}
+
 impl<T: Config> FindAuthor<T::AccountId> for Module<T> {
-	fn find_author<'a, I>(_digests: I) -> Option<T::AccountId>
+	fn find_author<'a, I>(digests: I) -> Option<T::AccountId>
 	where
 		I: 'a + IntoIterator<Item = (ConsensusEngineId, &'a [u8])>,
 	{
-		// We don't use the digests at all.
-		// This will only return the correct author _after_ the authorship inherent is processed.
-		<Author<T>>::get()
+		// Unlike the `Author` storage item, the pre-runtime digest is available from the very
+		// start of the block, before the author inherent is processed.
+		author_from_digests(digests).and_then(|author| T::AccountLookup::lookup_account(&author))
 	}
 }
 
 // Can I express this as `*b"auth"` like we do for the inherent id?
 pub const ENGINE_ID: ConsensusEngineId = [b'a', b'u', b't', b'h'];
 
+/// The keystore key type of the author keys.
+pub const KEY_TYPE: KeyTypeId = KeyTypeId(*b"auth");
+
+/// Find the author key reported in the given pre-runtime digests, if there is one.
+pub fn author_from_digests<'a, I>(digests: I) -> Option<AuthorId>
+where
+	I: IntoIterator<Item = (ConsensusEngineId, &'a [u8])>,
+{
+	digests
+		.into_iter()
+		.find(|(id, _)| *id == ENGINE_ID)
+		.and_then(|(_, mut data)| AuthorId::decode(&mut data).ok())
+}
+
+/// Whether `signature` is a seal of `pre_hash` made with `author`'s key.
+pub fn verify_seal(author: &AuthorId, pre_hash: &[u8], signature: &[u8]) -> bool {
+	AuthorSignature::decode(&mut &signature[..])
+		.map(|signature| author.verify(&pre_hash, &signature))
+		.unwrap_or(false)
+}
+
+/// Proof that an author sealed two different blocks on the same parent.
+///
+/// Both headers are kept as they were sealed, with the seal as their last digest. Note that the
+/// headers do not commit to the relay parent they were built on, so such a pair can also be an
+/// honest author proposing again on a newer relay parent after its first block was not backed.
+#[derive(Clone, Encode, Decode, PartialEq, Eq, RuntimeDebug)]
+pub struct EquivocationProof<Header> {
+	/// The author key that sealed both headers.
+	pub author: AuthorId,
+	/// The first sealed header.
+	pub first_header: Header,
+	/// The second sealed header, on the same parent as the first one.
+	pub second_header: Header,
+}
+
+/// Check that `header` reports `author` in its pre-runtime digest and is sealed with its key.
+/// Returns the hash of the header without its seal.
+fn check_sealed_header<H: HeaderT>(mut header: H, author: &AuthorId) -> Option<H::Hash> {
+	let signature = match header.digest_mut().pop() {
+		Some(DigestItem::Seal(id, signature)) if id == ENGINE_ID => signature,
+		_ => return None,
+	};
+
+	let reported = author_from_digests(
+		header
+			.digest()
+			.logs()
+			.iter()
+			.filter_map(|digest| digest.as_pre_runtime()),
+	)?;
+
+	let pre_hash = header.hash();
+	if reported == *author && verify_seal(author, pre_hash.as_ref(), &signature) {
+		Some(pre_hash)
+	} else {
+		None
+	}
+}
+
+/// Whether `proof` shows its author sealing two different blocks on the same parent. Blocks at
+/// the same height on different parents are not conflicting: an author may build on each fork.
+pub fn check_equivocation_proof<H: HeaderT>(proof: EquivocationProof<H>) -> bool {
+	let EquivocationProof {
+		author,
+		first_header,
+		second_header,
+	} = proof;
+
+	if first_header.number() != second_header.number()
+		|| first_header.parent_hash() != second_header.parent_hash()
+	{
+		return false;
+	}
+
+	match (
+		check_sealed_header(first_header, &author),
+		check_sealed_header(second_header, &author),
+	) {
+		(Some(first_hash), Some(second_hash)) => first_hash != second_hash,
+		_ => false,
+	}
+}
+
+/// Block executor that checks and removes the author's seal before executing the block.
+///
+/// The seal is appended after the block is built, so it is not part of what the runtime
+/// produced and has to be stripped before the final header checks. This is meant to be passed
+/// to `register_validate_block!` in place of `Executive` so that relay chain validators reject
+/// blocks whose seal does not match the reported author.
+pub struct BlockExecutor<I>(PhantomData<I>);
+
+impl<Block, I> ExecuteBlock<Block> for BlockExecutor<I>
+where
+	Block: BlockT,
+	I: ExecuteBlock<Block>,
+{
+	fn execute_block(block: Block) {
+		let (mut header, extrinsics) = block.deconstruct();
+
+		let signature = match header.digest_mut().pop() {
+			Some(DigestItem::Seal(id, signature)) if id == ENGINE_ID => signature,
+			_ => panic!("Block must be sealed by its author"),
+		};
+
+		let author = author_from_digests(
+			header
+				.digest()
+				.logs()
+				.iter()
+				.filter_map(|digest| digest.as_pre_runtime()),
+		)
+		.expect("Block must report its author in a pre-runtime digest");
+
+		assert!(
+			verify_seal(&author, header.hash().as_ref(), &signature),
+			"Block seal must be signed by the reported author"
+		);
+
+		log::trace!(
+			target:"author-inherent",
+			"Seal verified. About to execute the unsealed block."
+		);
+		I::execute_block(Block::new(header, extrinsics));
+	}
+}
+
 pub const INHERENT_IDENTIFIER: InherentIdentifier = *b"author__";
 
 #[derive(Encode)]
@@ -186,7 +381,7 @@ impl InherentError {
 }
 
 /// The type of data that the inherent will contain.
-/// Just a byte array. It will be decoded to an actual account id later.
+/// Just a byte array. It will be decoded to an actual author id later.
 pub type InherentType = Vec<u8>;
 
 /// The thing that the outer node will use to actually inject the inherent data
@@ -230,12 +425,9 @@ impl<T: Config> ProvideInherent for Module<T> {
 			.get_data::<InherentType>(&INHERENT_IDENTIFIER)
 			.expect("Gets and decodes authorship inherent data")?;
 
-		//TODO we need to make the author _prove_ their identity, not just claim it.
-		// we should have them sign something here. Best idea so far: parent block hash.
-
-		// Decode the Vec<u8> into an account Id
+		// Decode the Vec<u8> into an author Id
 		let author =
-			T::AccountId::decode(&mut &author_raw[..]).expect("Decodes author raw inherent data");
+			AuthorId::decode(&mut &author_raw[..]).expect("Decodes author raw inherent data");
 
 		Some(Call::set_author(author))
 	}
@@ -247,8 +439,11 @@ impl<T: Config> ProvideInherent for Module<T> {
 				target:"author-inherent",
 				"In the author inherent's `check_inherent` impl"
 			);
+			let account = T::AccountLookup::lookup_account(&claimed_author).ok_or(
+				InherentError::Other(sp_runtime::RuntimeString::Borrowed("No AccountId")),
+			)?;
 			ensure!(
-				T::PreliminaryCanAuthor::can_author(&claimed_author),
+				T::PreliminaryCanAuthor::can_author(&account),
 				InherentError::Other(sp_runtime::RuntimeString::Borrowed("Cannot Be Author"))
 			);
 		}
@@ -262,15 +457,14 @@ mod tests {
 	use super::*;
 	use crate as author_inherent;
 
-	use frame_support::{
-		assert_noop, assert_ok, parameter_types,
-		traits::{OnFinalize, OnInitialize},
-	};
-	use sp_core::H256;
+	use frame_support::{assert_noop, assert_ok, parameter_types, traits::OnInitialize};
+	use sp_core::{Pair, H256};
 	use sp_io::TestExternalities;
 	use sp_runtime::{
+		generic,
 		testing::Header,
 		traits::{BlakeTwo256, IdentityLookup},
+		Digest,
 	};
 
 	pub fn new_test_ext() -> TestExternalities {
@@ -322,39 +516,281 @@ mod tests {
 		type SystemWeightInfo = ();
 		type SS58Prefix = ();
 	}
+	/// The author keys generated from seeds 1 and 2 belong to accounts 1 and 2 respectively.
+	/// Any other author key is unmapped.
+	pub struct SeedLookup;
+	impl AccountLookup<u64> for SeedLookup {
+		fn lookup_account(author: &AuthorId) -> Option<u64> {
+			(1..=2u8)
+				.find(|seed| author_pair(*seed).public() == *author)
+				.map(u64::from)
+		}
+	}
 	impl Config for Test {
 		type EventHandler = ();
 		type PreliminaryCanAuthor = ();
 		type FinalCanAuthor = ();
+		type AccountLookup = SeedLookup;
+	}
+
+	/// Inner executor that only checks that the seal has been removed.
+	pub struct ExpectUnsealed;
+	impl ExecuteBlock<Block> for ExpectUnsealed {
+		fn execute_block(block: Block) {
+			assert!(block
+				.header
+				.digest
+				.logs()
+				.iter()
+				.all(|digest| digest.as_seal().is_none()));
+		}
+	}
+
+	fn author_pair(seed: u8) -> AuthorPair {
+		AuthorPair::from_seed(&[seed; 32])
+	}
+
+	/// A header at height `n` that reports `author` in its pre-runtime digest and is sealed by
+	/// `sealer`. Different `extrinsics_root`s give different headers at the same height.
+	fn sealed_header(
+		n: u64,
+		extrinsics_root: H256,
+		author: &AuthorPair,
+		sealer: &AuthorPair,
+	) -> Header {
+		sealed_header_on(Default::default(), n, extrinsics_root, author, sealer)
+	}
+
+	/// Same as `sealed_header`, with `parent` as the parent hash.
+	fn sealed_header_on(
+		parent: H256,
+		n: u64,
+		extrinsics_root: H256,
+		author: &AuthorPair,
+		sealer: &AuthorPair,
+	) -> Header {
+		let mut header = generic::Header::<u64, BlakeTwo256>::new(
+			n,
+			extrinsics_root,
+			Default::default(),
+			parent,
+			Digest {
+				logs: vec![DigestItem::PreRuntime(ENGINE_ID, author.public().encode())],
+			},
+		);
+		let seal = sealer.sign(header.hash().as_ref());
+		header
+			.digest_mut()
+			.push(DigestItem::Seal(ENGINE_ID, seal.encode()));
+		header
+	}
+
+	/// A block that reports `author` in its pre-runtime digest and is sealed by `sealer`.
+	fn sealed_block(author: &AuthorPair, sealer: &AuthorPair) -> Block {
+		Block::new(sealed_header(1, Default::default(), author, sealer), vec![])
 	}
 
-	pub fn roll_to(n: u64) {
-		while System::block_number() < n {
-			System::on_finalize(System::block_number());
-			System::set_block_number(System::block_number() + 1);
-			System::on_initialize(System::block_number());
-			AuthorInherent::on_initialize(System::block_number());
+	fn equivocation_proof(first: Header, second: Header) -> EquivocationProof<Header> {
+		EquivocationProof {
+			author: author_pair(1).public(),
+			first_header: first,
+			second_header: second,
 		}
 	}
 
+	/// Start block `n`, reporting the author key generated from `seed` in its pre-runtime digest.
+	pub fn start_block(n: u64, seed: u8) {
+		System::initialize(
+			&n,
+			&Default::default(),
+			&Digest {
+				logs: vec![DigestItem::PreRuntime(
+					ENGINE_ID,
+					author_pair(seed).public().encode(),
+				)],
+			},
+			Default::default(),
+		);
+		AuthorInherent::on_initialize(n);
+	}
+
 	#[test]
 	fn set_author_works() {
 		new_test_ext().execute_with(|| {
-			assert_ok!(AuthorInherent::set_author(Origin::none(), 1));
-			roll_to(1);
-			assert_ok!(AuthorInherent::set_author(Origin::none(), 1));
-			roll_to(2);
+			start_block(1, 1);
+			assert_ok!(AuthorInherent::set_author(
+				Origin::none(),
+				author_pair(1).public()
+			));
+			assert_eq!(Author::<Test>::get(), Some(1));
+			start_block(2, 2);
+			assert_eq!(Author::<Test>::get(), None);
+			assert_ok!(AuthorInherent::set_author(
+				Origin::none(),
+				author_pair(2).public()
+			));
+			assert_eq!(Author::<Test>::get(), Some(2));
 		});
 	}
 
+	#[test]
+	fn author_must_match_digest() {
+		new_test_ext().execute_with(|| {
+			start_block(1, 1);
+			assert_noop!(
+				AuthorInherent::set_author(Origin::none(), author_pair(2).public()),
+				Error::<Test>::AuthorDigestMismatch
+			);
+		});
+	}
+
+	#[test]
+	fn find_author_reads_digest() {
+		new_test_ext().execute_with(|| {
+			let author = author_pair(2).public().encode();
+			assert_eq!(
+				AuthorInherent::find_author(vec![(ENGINE_ID, &author[..])]),
+				Some(2)
+			);
+			let unmapped = author_pair(3).public().encode();
+			assert_eq!(
+				AuthorInherent::find_author(vec![(ENGINE_ID, &unmapped[..])]),
+				None
+			);
+			assert_eq!(
+				AuthorInherent::find_author(vec![(*b"othr", &author[..])]),
+				None
+			);
+		});
+	}
+
+	#[test]
+	fn unmapped_author_fails() {
+		new_test_ext().execute_with(|| {
+			start_block(1, 3);
+			assert_noop!(
+				AuthorInherent::set_author(Origin::none(), author_pair(3).public()),
+				Error::<Test>::NoAccountId
+			);
+		});
+	}
+
+	#[test]
+	fn author_found_in_digests() {
+		let author = author_pair(1).public();
+		let encoded = author.encode();
+		let digests = vec![(*b"othr", &[9u8][..]), (ENGINE_ID, &encoded[..])];
+		assert_eq!(author_from_digests(digests), Some(author));
+	}
+
+	#[test]
+	fn seal_verification_works() {
+		let pair = author_pair(1);
+		let seal = pair.sign(b"pre hash").encode();
+		assert!(verify_seal(&pair.public(), b"pre hash", &seal));
+		assert!(!verify_seal(&pair.public(), b"other hash", &seal));
+		assert!(!verify_seal(&author_pair(2).public(), b"pre hash", &seal));
+		assert!(!verify_seal(&pair.public(), b"pre hash", &[1, 2, 3]));
+	}
+
+	#[test]
+	fn executor_accepts_valid_seal() {
+		let author = author_pair(1);
+		BlockExecutor::<ExpectUnsealed>::execute_block(sealed_block(&author, &author));
+	}
+
+	#[test]
+	#[should_panic(expected = "Block seal must be signed by the reported author")]
+	fn executor_rejects_seal_from_other_author() {
+		let block = sealed_block(&author_pair(1), &author_pair(2));
+		BlockExecutor::<ExpectUnsealed>::execute_block(block);
+	}
+
+	#[test]
+	#[should_panic(expected = "Block must be sealed by its author")]
+	fn executor_rejects_unsealed_block() {
+		let mut block = sealed_block(&author_pair(1), &author_pair(1));
+		block.header.digest_mut().pop();
+		BlockExecutor::<ExpectUnsealed>::execute_block(block);
+	}
+
 	#[test]
 	fn double_author_fails() {
 		new_test_ext().execute_with(|| {
-			assert_ok!(AuthorInherent::set_author(Origin::none(), 1));
+			start_block(1, 1);
+			assert_ok!(AuthorInherent::set_author(
+				Origin::none(),
+				author_pair(1).public()
+			));
 			assert_noop!(
-				AuthorInherent::set_author(Origin::none(), 1),
+				AuthorInherent::set_author(Origin::none(), author_pair(1).public()),
 				Error::<Test>::AuthorAlreadySet
 			);
 		});
 	}
+
+	#[test]
+	fn equivocation_proof_works() {
+		let author = author_pair(1);
+		assert!(check_equivocation_proof(equivocation_proof(
+			sealed_header(5, H256::repeat_byte(1), &author, &author),
+			sealed_header(5, H256::repeat_byte(2), &author, &author),
+		)));
+	}
+
+	#[test]
+	fn equivocation_proof_needs_different_blocks() {
+		let author = author_pair(1);
+		let header = sealed_header(5, H256::repeat_byte(1), &author, &author);
+		assert!(!check_equivocation_proof(equivocation_proof(
+			header.clone(),
+			header
+		)));
+	}
+
+	#[test]
+	fn equivocation_proof_needs_same_height() {
+		let author = author_pair(1);
+		assert!(!check_equivocation_proof(equivocation_proof(
+			sealed_header(5, H256::repeat_byte(1), &author, &author),
+			sealed_header(6, H256::repeat_byte(2), &author, &author),
+		)));
+	}
+
+	#[test]
+	fn equivocation_proof_needs_same_parent() {
+		let author = author_pair(1);
+		assert!(!check_equivocation_proof(equivocation_proof(
+			sealed_header_on(
+				H256::repeat_byte(8),
+				5,
+				H256::repeat_byte(1),
+				&author,
+				&author
+			),
+			sealed_header_on(
+				H256::repeat_byte(9),
+				5,
+				H256::repeat_byte(2),
+				&author,
+				&author
+			),
+		)));
+	}
+
+	#[test]
+	fn equivocation_proof_needs_both_seals_from_author() {
+		let author = author_pair(1);
+		let other = author_pair(2);
+		// Sealed by someone else
+		assert!(!check_equivocation_proof(equivocation_proof(
+			sealed_header(5, H256::repeat_byte(1), &author, &author),
+			sealed_header(5, H256::repeat_byte(2), &author, &other),
+		)));
+		// Reporting someone else
+		assert!(!check_equivocation_proof(equivocation_proof(
+			sealed_header(5, H256::repeat_byte(1), &author, &author),
+			sealed_header(5, H256::repeat_byte(2), &other, &other),
+		)));
+	}
 }
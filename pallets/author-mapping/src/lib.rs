@@ -0,0 +1,235 @@
+// Copyright 2019-2021 PureStake Inc.
+// This file is part of Moonbeam.
+
+// Moonbeam is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Moonbeam is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Moonbeam.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Registry of block author keys.
+//!
+//! Blocks are sealed with an `AuthorId`, a session key that lives in the author's node keystore.
+//! This pallet maps each author key to the account that is credited with (and rewarded for) the
+//! blocks that key seals, which is the account that stakes in `parachain-staking`. Because stake
+//! is tied to the account and not to the key, operators can rotate their author keys without
+//! touching their stake.
+//!
+//! Registering a key reserves a deposit from the registering account. The deposit is returned
+//! when the association is cleared, and stays with the association when the key is rotated.
+
+#![cfg_attr(not(feature = "std"), no_std)]
+
+use frame_support::pallet;
+
+pub use pallet::*;
+
+#[cfg(test)]
+mod mock;
+#[cfg(test)]
+mod tests;
+
+#[pallet]
+pub mod pallet {
+	use author_inherent::{AccountLookup, AuthorId};
+	use frame_support::pallet_prelude::*;
+	use frame_support::traits::{Currency, ReservableCurrency};
+	use frame_system::pallet_prelude::*;
+	use parity_scale_codec::{Decode, Encode};
+	use sp_runtime::RuntimeDebug;
+	use sp_std::vec::Vec;
+
+	pub type BalanceOf<T> = <<T as Config>::DepositCurrency as Currency<
+		<T as frame_system::Config>::AccountId,
+	>>::Balance;
+
+	/// The account an author key is mapped to and the deposit that was reserved for it.
+	#[derive(Encode, Decode, PartialEq, Eq, RuntimeDebug)]
+	pub struct RegistrationInfo<AccountId, Balance> {
+		pub account: AccountId,
+		pub deposit: Balance,
+	}
+
+	/// The Author Mapping pallet
+	#[pallet::pallet]
+	pub struct Pallet<T>(PhantomData<T>);
+
+	/// Configuration trait of this pallet.
+	#[pallet::config]
+	pub trait Config: frame_system::Config {
+		/// The overarching event type
+		type Event: From<Event<Self>> + IsType<<Self as frame_system::Config>::Event>;
+		/// Currency in which the registration deposit is reserved
+		type DepositCurrency: ReservableCurrency<Self::AccountId>;
+		/// Deposit reserved for each registered author key
+		type DepositAmount: Get<BalanceOf<Self>>;
+	}
+
+	#[pallet::error]
+	pub enum Error<T> {
+		/// The author key is not registered
+		AssociationNotFound,
+		/// The author key is registered to another account
+		NotYourAssociation,
+		/// The account cannot afford the registration deposit
+		CannotAffordSecurityDeposit,
+		/// The author key is already registered
+		AlreadyAssociated,
+	}
+
+	#[pallet::event]
+	#[pallet::generate_deposit(pub(crate) fn deposit_event)]
+	pub enum Event<T: Config> {
+		/// Author key, Account it was registered to
+		AuthorRegistered(AuthorId, T::AccountId),
+		/// Old author key, New author key, Account they belong to
+		AuthorRotated(AuthorId, AuthorId, T::AccountId),
+		/// Author key, Account it was registered to
+		AuthorDeRegistered(AuthorId, T::AccountId),
+	}
+
+	// No hooks
+	#[pallet::hooks]
+	impl<T: Config> Hooks<BlockNumberFor<T>> for Pallet<T> {}
+
+	#[pallet::call]
+	impl<T: Config> Pallet<T> {
+		/// Register an author key to the caller's account, reserving the registration deposit.
+		#[pallet::weight(0)]
+		pub fn add_association(
+			origin: OriginFor<T>,
+			author_id: AuthorId,
+		) -> DispatchResultWithPostInfo {
+			let account = ensure_signed(origin)?;
+			ensure!(
+				!<Mapping<T>>::contains_key(&author_id),
+				Error::<T>::AlreadyAssociated
+			);
+			Self::enact_registration(&author_id, account)?;
+
+			Ok(Default::default())
+		}
+
+		/// Replace one of the caller's author keys with a new one. The deposit moves over to the
+		/// new key.
+		#[pallet::weight(0)]
+		pub fn update_association(
+			origin: OriginFor<T>,
+			old_author_id: AuthorId,
+			new_author_id: AuthorId,
+		) -> DispatchResultWithPostInfo {
+			let account = ensure_signed(origin)?;
+			let info = Self::owned_registration(&old_author_id, &account)?;
+			ensure!(
+				!<Mapping<T>>::contains_key(&new_author_id),
+				Error::<T>::AlreadyAssociated
+			);
+
+			<Mapping<T>>::remove(&old_author_id);
+			<Mapping<T>>::insert(&new_author_id, info);
+			Self::deposit_event(Event::AuthorRotated(old_author_id, new_author_id, account));
+
+			Ok(Default::default())
+		}
+
+		/// Deregister one of the caller's author keys and return its deposit.
+		#[pallet::weight(0)]
+		pub fn clear_association(
+			origin: OriginFor<T>,
+			author_id: AuthorId,
+		) -> DispatchResultWithPostInfo {
+			let account = ensure_signed(origin)?;
+			let info = Self::owned_registration(&author_id, &account)?;
+
+			<Mapping<T>>::remove(&author_id);
+			T::DepositCurrency::unreserve(&account, info.deposit);
+			Self::deposit_event(Event::AuthorDeRegistered(author_id, account));
+
+			Ok(Default::default())
+		}
+	}
+
+	impl<T: Config> Pallet<T> {
+		/// Reserve the deposit from `account` and map `author_id` to it.
+		fn enact_registration(author_id: &AuthorId, account: T::AccountId) -> DispatchResult {
+			let deposit = T::DepositAmount::get();
+			T::DepositCurrency::reserve(&account, deposit)
+				.map_err(|_| Error::<T>::CannotAffordSecurityDeposit)?;
+
+			<Mapping<T>>::insert(
+				author_id,
+				RegistrationInfo {
+					account: account.clone(),
+					deposit,
+				},
+			);
+			Self::deposit_event(Event::AuthorRegistered(author_id.clone(), account));
+
+			Ok(())
+		}
+
+		/// The registration of `author_id`, provided it belongs to `account`.
+		fn owned_registration(
+			author_id: &AuthorId,
+			account: &T::AccountId,
+		) -> Result<RegistrationInfo<T::AccountId, BalanceOf<T>>, DispatchError> {
+			let info = <Mapping<T>>::get(author_id).ok_or(Error::<T>::AssociationNotFound)?;
+			ensure!(&info.account == account, Error::<T>::NotYourAssociation);
+
+			Ok(info)
+		}
+
+		/// The account that the given author key is registered to.
+		pub fn account_id_of(author_id: &AuthorId) -> Option<T::AccountId> {
+			<Mapping<T>>::get(author_id).map(|info| info.account)
+		}
+	}
+
+	impl<T: Config> AccountLookup<T::AccountId> for Pallet<T> {
+		fn lookup_account(author: &AuthorId) -> Option<T::AccountId> {
+			Self::account_id_of(author)
+		}
+	}
+
+	/// The registered author keys, with the account each one is mapped to.
+	#[pallet::storage]
+	#[pallet::getter(fn mapping)]
+	pub type Mapping<T: Config> = StorageMap<
+		_,
+		Blake2_128Concat,
+		AuthorId,
+		RegistrationInfo<T::AccountId, BalanceOf<T>>,
+		OptionQuery,
+	>;
+
+	#[pallet::genesis_config]
+	pub struct GenesisConfig<T: Config> {
+		/// Author keys to register at genesis, with the accounts they are mapped to. The
+		/// accounts pay the usual deposit.
+		pub mappings: Vec<(AuthorId, T::AccountId)>,
+	}
+
+	#[cfg(feature = "std")]
+	impl<T: Config> Default for GenesisConfig<T> {
+		fn default() -> Self {
+			Self { mappings: vec![] }
+		}
+	}
+
+	#[pallet::genesis_build]
+	impl<T: Config> GenesisBuild<T> for GenesisConfig<T> {
+		fn build(&self) {
+			for (author_id, account) in &self.mappings {
+				<Pallet<T>>::enact_registration(author_id, account.clone())
+					.expect("Account can afford the deposit for its genesis author key");
+			}
+		}
+	}
+}
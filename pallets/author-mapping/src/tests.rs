@@ -0,0 +1,207 @@
+// Copyright 2019-2021 PureStake Inc.
+// This file is part of Moonbeam.
+
+// Moonbeam is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Moonbeam is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Moonbeam.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Unit testing
+use crate::mock::{
+	author_id, last_event, AuthorMapping, Balances, Event as MetaEvent, ExtBuilder, Origin, Test,
+};
+use crate::{Error, Event, RegistrationInfo};
+use author_inherent::AccountLookup;
+use frame_support::{assert_noop, assert_ok};
+
+#[test]
+fn genesis_registers_and_reserves() {
+	ExtBuilder::default()
+		.with_balances(vec![(1, 1000)])
+		.with_mappings(vec![(author_id(1), 1)])
+		.build()
+		.execute_with(|| {
+			assert_eq!(
+				AuthorMapping::mapping(author_id(1)),
+				Some(RegistrationInfo {
+					account: 1,
+					deposit: 100
+				})
+			);
+			assert_eq!(Balances::reserved_balance(&1), 100);
+			assert_eq!(Balances::free_balance(&1), 900);
+		});
+}
+
+#[test]
+fn registering_works() {
+	ExtBuilder::default()
+		.with_balances(vec![(1, 1000)])
+		.build()
+		.execute_with(|| {
+			assert_ok!(AuthorMapping::add_association(
+				Origin::signed(1),
+				author_id(1)
+			));
+			assert_eq!(
+				last_event(),
+				MetaEvent::author_mapping(Event::AuthorRegistered(author_id(1), 1))
+			);
+			assert_eq!(AuthorMapping::lookup_account(&author_id(1)), Some(1));
+			assert_eq!(Balances::reserved_balance(&1), 100);
+		});
+}
+
+#[test]
+fn accounts_can_register_several_keys() {
+	ExtBuilder::default()
+		.with_balances(vec![(1, 1000)])
+		.build()
+		.execute_with(|| {
+			assert_ok!(AuthorMapping::add_association(
+				Origin::signed(1),
+				author_id(1)
+			));
+			assert_ok!(AuthorMapping::add_association(
+				Origin::signed(1),
+				author_id(2)
+			));
+			assert_eq!(AuthorMapping::lookup_account(&author_id(1)), Some(1));
+			assert_eq!(AuthorMapping::lookup_account(&author_id(2)), Some(1));
+			assert_eq!(Balances::reserved_balance(&1), 200);
+		});
+}
+
+#[test]
+fn cannot_register_without_deposit() {
+	ExtBuilder::default()
+		.with_balances(vec![(1, 50)])
+		.build()
+		.execute_with(|| {
+			assert_noop!(
+				AuthorMapping::add_association(Origin::signed(1), author_id(1)),
+				Error::<Test>::CannotAffordSecurityDeposit
+			);
+			assert_eq!(AuthorMapping::lookup_account(&author_id(1)), None);
+		});
+}
+
+#[test]
+fn cannot_register_key_twice() {
+	ExtBuilder::default()
+		.with_balances(vec![(1, 1000), (2, 1000)])
+		.with_mappings(vec![(author_id(1), 1)])
+		.build()
+		.execute_with(|| {
+			assert_noop!(
+				AuthorMapping::add_association(Origin::signed(2), author_id(1)),
+				Error::<Test>::AlreadyAssociated
+			);
+			assert_noop!(
+				AuthorMapping::add_association(Origin::signed(1), author_id(1)),
+				Error::<Test>::AlreadyAssociated
+			);
+		});
+}
+
+#[test]
+fn rotating_keys_works() {
+	ExtBuilder::default()
+		.with_balances(vec![(1, 1000)])
+		.with_mappings(vec![(author_id(1), 1)])
+		.build()
+		.execute_with(|| {
+			assert_ok!(AuthorMapping::update_association(
+				Origin::signed(1),
+				author_id(1),
+				author_id(2)
+			));
+			assert_eq!(
+				last_event(),
+				MetaEvent::author_mapping(Event::AuthorRotated(author_id(1), author_id(2), 1))
+			);
+			assert_eq!(AuthorMapping::lookup_account(&author_id(1)), None);
+			assert_eq!(AuthorMapping::lookup_account(&author_id(2)), Some(1));
+			// The deposit moves over with the registration
+			assert_eq!(Balances::reserved_balance(&1), 100);
+		});
+}
+
+#[test]
+fn cannot_rotate_to_registered_key() {
+	ExtBuilder::default()
+		.with_balances(vec![(1, 1000), (2, 1000)])
+		.with_mappings(vec![(author_id(1), 1), (author_id(2), 2)])
+		.build()
+		.execute_with(|| {
+			assert_noop!(
+				AuthorMapping::update_association(Origin::signed(1), author_id(1), author_id(2)),
+				Error::<Test>::AlreadyAssociated
+			);
+		});
+}
+
+#[test]
+fn cannot_rotate_someone_elses_key() {
+	ExtBuilder::default()
+		.with_balances(vec![(1, 1000), (2, 1000)])
+		.with_mappings(vec![(author_id(1), 1)])
+		.build()
+		.execute_with(|| {
+			assert_noop!(
+				AuthorMapping::update_association(Origin::signed(2), author_id(1), author_id(2)),
+				Error::<Test>::NotYourAssociation
+			);
+			assert_noop!(
+				AuthorMapping::update_association(Origin::signed(2), author_id(3), author_id(2)),
+				Error::<Test>::AssociationNotFound
+			);
+		});
+}
+
+#[test]
+fn clearing_returns_deposit() {
+	ExtBuilder::default()
+		.with_balances(vec![(1, 1000)])
+		.with_mappings(vec![(author_id(1), 1)])
+		.build()
+		.execute_with(|| {
+			assert_ok!(AuthorMapping::clear_association(
+				Origin::signed(1),
+				author_id(1)
+			));
+			assert_eq!(
+				last_event(),
+				MetaEvent::author_mapping(Event::AuthorDeRegistered(author_id(1), 1))
+			);
+			assert_eq!(AuthorMapping::lookup_account(&author_id(1)), None);
+			assert_eq!(Balances::reserved_balance(&1), 0);
+			assert_eq!(Balances::free_balance(&1), 1000);
+		});
+}
+
+#[test]
+fn cannot_clear_someone_elses_key() {
+	ExtBuilder::default()
+		.with_balances(vec![(1, 1000), (2, 1000)])
+		.with_mappings(vec![(author_id(1), 1)])
+		.build()
+		.execute_with(|| {
+			assert_noop!(
+				AuthorMapping::clear_association(Origin::signed(2), author_id(1)),
+				Error::<Test>::NotYourAssociation
+			);
+			assert_noop!(
+				AuthorMapping::clear_association(Origin::signed(2), author_id(2)),
+				Error::<Test>::AssociationNotFound
+			);
+		});
+}
@@ -0,0 +1,353 @@
+// Copyright 2019-2021 PureStake Inc.
+// This file is part of Moonbeam.
+
+// Moonbeam is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Moonbeam is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Moonbeam.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Minimum gas price of EVM transactions following the fullness of blocks.
+//!
+//! As the base fee of EIP-1559, the minimum gas price goes up when blocks use more gas than a
+//! target share of the block gas limit, and down when they use less, by at most
+//! `1 / MaxChangeDenominator` per block. It never goes below a floor. Governance sets the floor
+//! and the target fullness.
+//!
+//! The price of a block follows the gas used by the previous block, so it is known before any
+//! transaction of the block is applied. Unlike EIP-1559 the whole gas price is paid, none of it
+//! is burnt: the minimum only decides which transactions are valid.
+//!
+//! The pallet also charges the fees of EVM transactions. The fee for the gas limit is withdrawn
+//! before the transaction, the unused gas is refunded after it, and the fee for the used gas is
+//! split between the treasury and the block author. Governance sets the share of the treasury.
+//! The author share goes to the treasury too when the block has no known author.
+//!
+//! Without a target fullness, as on chains started before this pallet, the minimum gas price
+//! stays where it is until governance sets one.
+
+#![cfg_attr(not(feature = "std"), no_std)]
+
+use frame_support::pallet;
+use sp_core::U256;
+
+pub use pallet::*;
+
+#[cfg(test)]
+mod mock;
+#[cfg(test)]
+mod tests;
+
+/// The gas used by the EVM transactions of the previous block.
+pub trait PreviousBlockGas {
+	/// The gas used by the previous block, 0 when it had no EVM transaction.
+	fn gas_used() -> U256;
+}
+
+#[pallet]
+pub mod pallet {
+	use crate::PreviousBlockGas;
+	use frame_support::{
+		pallet_prelude::*,
+		traits::{
+			Currency, ExistenceRequirement, FindAuthor, Imbalance, OnUnbalanced, WithdrawReasons,
+		},
+	};
+	use frame_system::pallet_prelude::*;
+	use pallet_evm::{AddressMapping, OnChargeEVMTransaction};
+	use sp_core::{H160, U256};
+	use sp_runtime::{traits::UniqueSaturatedInto, PerThing, Perbill};
+
+	pub type BalanceOf<T> =
+		<<T as Config>::Currency as Currency<<T as frame_system::Config>::AccountId>>::Balance;
+	pub type NegativeImbalanceOf<T> = <<T as Config>::Currency as Currency<
+		<T as frame_system::Config>::AccountId,
+	>>::NegativeImbalance;
+
+	/// The Fee Market pallet
+	#[pallet::pallet]
+	pub struct Pallet<T>(PhantomData<T>);
+
+	/// Configuration trait of this pallet.
+	#[pallet::config]
+	pub trait Config: frame_system::Config {
+		/// The overarching event type
+		type Event: From<Event<Self>> + IsType<<Self as frame_system::Config>::Event>;
+		/// The gas limit of a block
+		type BlockGasLimit: Get<U256>;
+		/// The gas used by the previous block
+		type PreviousBlockGas: PreviousBlockGas;
+		/// The origin that sets the floor and the target fullness
+		type GovernanceOrigin: EnsureOrigin<Self::Origin>;
+		/// The minimum gas price changes by at most its share `1 / MaxChangeDenominator` per block
+		#[pallet::constant]
+		type MaxChangeDenominator: Get<u32>;
+		/// The currency the fees are paid in
+		type Currency: Currency<Self::AccountId>;
+		/// Where the treasury share of the fees goes
+		type Treasury: OnUnbalanced<NegativeImbalanceOf<Self>>;
+		/// The author of the current block, receiving the rest of the fees
+		type FindAuthor: FindAuthor<Self::AccountId>;
+	}
+
+	#[pallet::error]
+	pub enum Error<T> {
+		/// A target fullness of zero would make the price go up with any transaction
+		ZeroTargetFullness,
+	}
+
+	#[pallet::event]
+	#[pallet::generate_deposit(pub(crate) fn deposit_event)]
+	pub enum Event<T: Config> {
+		/// The floor of the minimum gas price was set
+		GasPriceFloorSet(U256),
+		/// The target fullness of blocks was set
+		TargetFullnessSet(Perbill),
+		/// The treasury share of the fees was set
+		TreasuryFeeShareSet(Perbill),
+		/// Fee payer, Amount to the treasury, Block author, Amount to the block author
+		FeeDistributed(
+			T::AccountId,
+			BalanceOf<T>,
+			Option<T::AccountId>,
+			BalanceOf<T>,
+		),
+	}
+
+	#[pallet::hooks]
+	impl<T: Config> Hooks<BlockNumberFor<T>> for Pallet<T> {
+		fn on_initialize(_n: T::BlockNumber) -> Weight {
+			Self::adjust_min_gas_price(T::PreviousBlockGas::gas_used());
+			T::DbWeight::get().reads_writes(4, 1)
+		}
+	}
+
+	#[pallet::call]
+	impl<T: Config> Pallet<T> {
+		/// Set the floor of the minimum gas price, raising the minimum gas price to it if needed.
+		#[pallet::weight(T::DbWeight::get().reads_writes(1, 2))]
+		pub fn set_gas_price_floor(
+			origin: OriginFor<T>,
+			floor: U256,
+		) -> DispatchResultWithPostInfo {
+			T::GovernanceOrigin::ensure_origin(origin)?;
+			GasPriceFloor::<T>::put(floor);
+			MinGasPrice::<T>::mutate(|price| *price = (*price).max(floor));
+			Self::deposit_event(Event::GasPriceFloorSet(floor));
+			Ok(Default::default())
+		}
+
+		/// Set the share of the block gas limit that blocks are expected to use.
+		#[pallet::weight(T::DbWeight::get().writes(1))]
+		pub fn set_target_fullness(
+			origin: OriginFor<T>,
+			target_fullness: Perbill,
+		) -> DispatchResultWithPostInfo {
+			T::GovernanceOrigin::ensure_origin(origin)?;
+			ensure!(!target_fullness.is_zero(), Error::<T>::ZeroTargetFullness);
+			TargetFullness::<T>::put(target_fullness);
+			Self::deposit_event(Event::TargetFullnessSet(target_fullness));
+			Ok(Default::default())
+		}
+
+		/// Set the share of the fees going to the treasury, the rest going to the block author.
+		#[pallet::weight(T::DbWeight::get().writes(1))]
+		pub fn set_treasury_fee_share(
+			origin: OriginFor<T>,
+			treasury_fee_share: Perbill,
+		) -> DispatchResultWithPostInfo {
+			T::GovernanceOrigin::ensure_origin(origin)?;
+			TreasuryFeeShare::<T>::put(treasury_fee_share);
+			Self::deposit_event(Event::TreasuryFeeShareSet(treasury_fee_share));
+			Ok(Default::default())
+		}
+	}
+
+	impl<T: Config> Pallet<T> {
+		/// Move the minimum gas price towards the price at which blocks would use `gas_used`.
+		pub(crate) fn adjust_min_gas_price(gas_used: U256) {
+			let target = T::BlockGasLimit::get()
+				.saturating_mul(TargetFullness::<T>::get().deconstruct().into())
+				/ U256::from(Perbill::ACCURACY);
+			if target.is_zero() {
+				return;
+			}
+			let denominator = U256::from(T::MaxChangeDenominator::get().max(1));
+
+			let price = MinGasPrice::<T>::get();
+			let price = if gas_used > target {
+				// The price goes up by at least 1, so that a price of 1 can go up at all.
+				let change = price.saturating_mul(gas_used - target) / target / denominator;
+				price.saturating_add(change.max(U256::one()))
+			} else {
+				let change = price.saturating_mul(target - gas_used) / target / denominator;
+				price.saturating_sub(change)
+			};
+			MinGasPrice::<T>::put(price.max(GasPriceFloor::<T>::get()));
+		}
+
+		/// Withdraw `fee` from `who`, before it is known how much of it is used.
+		pub(crate) fn charge_fee(
+			who: &T::AccountId,
+			fee: BalanceOf<T>,
+		) -> Result<NegativeImbalanceOf<T>, DispatchError> {
+			T::Currency::withdraw(
+				who,
+				fee,
+				WithdrawReasons::FEE,
+				ExistenceRequirement::AllowDeath,
+			)
+		}
+
+		/// Refund `who` what it paid over `fee`, and split `fee` between the treasury and the
+		/// block author.
+		pub(crate) fn settle_fee(
+			who: &T::AccountId,
+			fee: BalanceOf<T>,
+			paid: NegativeImbalanceOf<T>,
+		) {
+			let (fee, refund) = paid.split(fee);
+			T::Currency::resolve_creating(who, refund);
+
+			let treasury_amount = TreasuryFeeShare::<T>::get() * fee.peek();
+			let (mut to_treasury, to_author) = fee.split(treasury_amount);
+			let author = Self::block_author();
+			let author_amount = match &author {
+				Some(author) => {
+					let author_amount = to_author.peek();
+					T::Currency::resolve_creating(author, to_author);
+					author_amount
+				}
+				None => {
+					to_treasury.subsume(to_author);
+					Default::default()
+				}
+			};
+			let treasury_amount = to_treasury.peek();
+			T::Treasury::on_unbalanced(to_treasury);
+
+			Self::deposit_event(Event::FeeDistributed(
+				who.clone(),
+				treasury_amount,
+				author,
+				author_amount,
+			));
+		}
+
+		/// The author of the current block, from the pre-runtime digests of its header.
+		fn block_author() -> Option<T::AccountId> {
+			let digest = frame_system::Pallet::<T>::digest();
+			let pre_runtime_digests = digest.logs.iter().filter_map(|d| d.as_pre_runtime());
+			T::FindAuthor::find_author(pre_runtime_digests)
+		}
+	}
+
+	/// Charges the fees of EVM transactions, in the currency of this pallet.
+	impl<T> OnChargeEVMTransaction<T> for Pallet<T>
+	where
+		T: Config + pallet_evm::Config,
+	{
+		type LiquidityInfo = Option<NegativeImbalanceOf<T>>;
+
+		fn withdraw_fee(
+			who: &H160,
+			fee: U256,
+		) -> Result<Self::LiquidityInfo, pallet_evm::Error<T>> {
+			if fee.is_zero() {
+				return Ok(None);
+			}
+			let account = <T as pallet_evm::Config>::AddressMapping::into_account_id(*who);
+			Self::charge_fee(&account, balance::<T>(fee))
+				.map(Some)
+				.map_err(|_| pallet_evm::Error::<T>::BalanceLow)
+		}
+
+		fn correct_and_deposit_fee(
+			who: &H160,
+			corrected_fee: U256,
+			already_withdrawn: Self::LiquidityInfo,
+		) -> Result<(), pallet_evm::Error<T>> {
+			if let Some(paid) = already_withdrawn {
+				let account = <T as pallet_evm::Config>::AddressMapping::into_account_id(*who);
+				Self::settle_fee(&account, balance::<T>(corrected_fee), paid);
+			}
+			Ok(())
+		}
+	}
+
+	/// The balance of an EVM amount, saturating at the largest balance.
+	fn balance<T: Config>(amount: U256) -> BalanceOf<T> {
+		amount
+			.min(U256::from(u128::MAX))
+			.low_u128()
+			.unique_saturated_into()
+	}
+
+	impl<T: Config> pallet_evm::FeeCalculator for Pallet<T> {
+		fn min_gas_price() -> U256 {
+			MinGasPrice::<T>::get()
+		}
+	}
+
+	/// The minimum gas price of the EVM transactions of the current block.
+	#[pallet::storage]
+	#[pallet::getter(fn min_gas_price)]
+	pub type MinGasPrice<T: Config> = StorageValue<_, U256, ValueQuery>;
+
+	/// The lowest the minimum gas price can go.
+	#[pallet::storage]
+	#[pallet::getter(fn gas_price_floor)]
+	pub type GasPriceFloor<T: Config> = StorageValue<_, U256, ValueQuery>;
+
+	/// The share of the block gas limit above which the minimum gas price goes up.
+	#[pallet::storage]
+	#[pallet::getter(fn target_fullness)]
+	pub type TargetFullness<T: Config> = StorageValue<_, Perbill, ValueQuery>;
+
+	/// The share of the fees going to the treasury, the rest going to the block author.
+	#[pallet::storage]
+	#[pallet::getter(fn treasury_fee_share)]
+	pub type TreasuryFeeShare<T: Config> = StorageValue<_, Perbill, ValueQuery>;
+
+	#[pallet::genesis_config]
+	pub struct GenesisConfig {
+		/// The floor of the minimum gas price, which is also its initial value
+		pub gas_price_floor: U256,
+		/// The share of the block gas limit blocks are expected to use
+		pub target_fullness: Perbill,
+		/// The share of the fees going to the treasury
+		pub treasury_fee_share: Perbill,
+	}
+
+	#[cfg(feature = "std")]
+	impl Default for GenesisConfig {
+		fn default() -> Self {
+			Self {
+				gas_price_floor: U256::one(),
+				target_fullness: Perbill::from_percent(50),
+				treasury_fee_share: Perbill::from_percent(20),
+			}
+		}
+	}
+
+	#[pallet::genesis_build]
+	impl<T: Config> GenesisBuild<T> for GenesisConfig {
+		fn build(&self) {
+			assert!(
+				!self.target_fullness.is_zero(),
+				"The target fullness must not be zero"
+			);
+			GasPriceFloor::<T>::put(self.gas_price_floor);
+			MinGasPrice::<T>::put(self.gas_price_floor);
+			TargetFullness::<T>::put(self.target_fullness);
+			TreasuryFeeShare::<T>::put(self.treasury_fee_share);
+		}
+	}
+}
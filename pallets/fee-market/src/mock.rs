@@ -0,0 +1,191 @@
+// Copyright 2019-2021 PureStake Inc.
+// This file is part of Moonbeam.
+
+// Moonbeam is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Moonbeam is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Moonbeam.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Test utilities
+use super::*;
+use crate as fee_market;
+use frame_support::{
+	construct_runtime, parameter_types,
+	traits::{Currency, FindAuthor, GenesisBuild, OnInitialize, OnUnbalanced},
+	ConsensusEngineId,
+};
+use frame_system::EnsureRoot;
+use sp_core::{H256, U256};
+use sp_io;
+use sp_runtime::{
+	testing::Header,
+	traits::{BlakeTwo256, IdentityLookup},
+	Perbill,
+};
+use std::cell::RefCell;
+
+pub type AccountId = u64;
+pub type BlockNumber = u64;
+pub type Balance = u128;
+
+type UncheckedExtrinsic = frame_system::mocking::MockUncheckedExtrinsic<Test>;
+type Block = frame_system::mocking::MockBlock<Test>;
+
+// Configure a mock runtime to test the pallet.
+construct_runtime!(
+	pub enum Test where
+		Block = Block,
+		NodeBlock = Block,
+		UncheckedExtrinsic = UncheckedExtrinsic,
+	{
+		System: frame_system::{Pallet, Call, Config, Storage, Event<T>},
+		Balances: pallet_balances::{Pallet, Call, Storage, Config<T>, Event<T>},
+		FeeMarket: fee_market::{Pallet, Call, Storage, Config, Event<T>},
+	}
+);
+
+parameter_types! {
+	pub const BlockHashCount: u64 = 250;
+	pub const SS58Prefix: u8 = 42;
+}
+impl frame_system::Config for Test {
+	type BaseCallFilter = ();
+	type DbWeight = ();
+	type Origin = Origin;
+	type Index = u64;
+	type BlockNumber = BlockNumber;
+	type Call = Call;
+	type Hash = H256;
+	type Hashing = BlakeTwo256;
+	type AccountId = AccountId;
+	type Lookup = IdentityLookup<Self::AccountId>;
+	type Header = Header;
+	type Event = Event;
+	type BlockHashCount = BlockHashCount;
+	type Version = ();
+	type PalletInfo = PalletInfo;
+	type AccountData = pallet_balances::AccountData<Balance>;
+	type OnNewAccount = ();
+	type OnKilledAccount = ();
+	type SystemWeightInfo = ();
+	type BlockWeights = ();
+	type BlockLength = ();
+	type SS58Prefix = SS58Prefix;
+}
+
+parameter_types! {
+	pub const ExistentialDeposit: Balance = 1;
+}
+impl pallet_balances::Config for Test {
+	type MaxLocks = ();
+	type Balance = Balance;
+	type Event = Event;
+	type DustRemoval = ();
+	type ExistentialDeposit = ExistentialDeposit;
+	type AccountStore = System;
+	type WeightInfo = ();
+}
+
+thread_local! {
+	static GAS_USED: RefCell<U256> = RefCell::new(U256::zero());
+	static AUTHOR: RefCell<Option<AccountId>> = RefCell::new(None);
+}
+
+/// The gas used by the previous block, set by the tests.
+pub(crate) struct MockPreviousBlockGas;
+
+impl PreviousBlockGas for MockPreviousBlockGas {
+	fn gas_used() -> U256 {
+		GAS_USED.with(|gas_used| *gas_used.borrow())
+	}
+}
+
+/// A block author set by the tests.
+pub(crate) struct MockFindAuthor;
+
+impl FindAuthor<AccountId> for MockFindAuthor {
+	fn find_author<'a, I>(_digests: I) -> Option<AccountId>
+	where
+		I: 'a + IntoIterator<Item = (ConsensusEngineId, &'a [u8])>,
+	{
+		AUTHOR.with(|author| *author.borrow())
+	}
+}
+
+pub(crate) fn set_author(author: Option<AccountId>) {
+	AUTHOR.with(|current| *current.borrow_mut() = author);
+}
+
+pub(crate) const TREASURY: AccountId = 100;
+
+/// Deposits the treasury share of the fees into `TREASURY`.
+pub(crate) struct ToTreasury;
+
+impl OnUnbalanced<NegativeImbalanceOf<Test>> for ToTreasury {
+	fn on_nonzero_unbalanced(amount: NegativeImbalanceOf<Test>) {
+		Balances::resolve_creating(&TREASURY, amount);
+	}
+}
+
+parameter_types! {
+	pub BlockGasLimit: U256 = U256::from(1_000_000);
+	pub const MaxChangeDenominator: u32 = 8;
+}
+impl Config for Test {
+	type Event = Event;
+	type BlockGasLimit = BlockGasLimit;
+	type PreviousBlockGas = MockPreviousBlockGas;
+	type GovernanceOrigin = EnsureRoot<AccountId>;
+	type MaxChangeDenominator = MaxChangeDenominator;
+	type Currency = Balances;
+	type Treasury = ToTreasury;
+	type FindAuthor = MockFindAuthor;
+}
+
+/// Externalities with a minimum gas price starting at `gas_price_floor`, blocks expected to be
+/// half full, a fifth of the fees going to the treasury and 1_000 for account 1.
+pub(crate) fn new_test_ext(gas_price_floor: u64) -> sp_io::TestExternalities {
+	let mut t = frame_system::GenesisConfig::default()
+		.build_storage::<Test>()
+		.expect("Frame system builds valid default genesis config");
+
+	pallet_balances::GenesisConfig::<Test> {
+		balances: vec![(1, 1_000)],
+	}
+	.assimilate_storage(&mut t)
+	.expect("Pallet balances storage can be assimilated");
+	GenesisBuild::<Test>::assimilate_storage(
+		&fee_market::GenesisConfig {
+			gas_price_floor: gas_price_floor.into(),
+			target_fullness: Perbill::from_percent(50),
+			treasury_fee_share: Perbill::from_percent(20),
+		},
+		&mut t,
+	)
+	.expect("Fee market builds valid genesis config");
+
+	set_author(None);
+	let mut ext = sp_io::TestExternalities::new(t);
+	ext.execute_with(|| System::set_block_number(1));
+	ext
+}
+
+/// Start the next block, the previous one having used `gas_used`.
+pub(crate) fn next_block(gas_used: u64) {
+	GAS_USED.with(|current| *current.borrow_mut() = gas_used.into());
+	let n = System::block_number() + 1;
+	System::set_block_number(n);
+	FeeMarket::on_initialize(n);
+}
+
+pub(crate) fn last_event() -> Event {
+	System::events().pop().expect("Event expected").event
+}
@@ -0,0 +1,203 @@
+// Copyright 2019-2021 PureStake Inc.
+// This file is part of Moonbeam.
+
+// Moonbeam is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Moonbeam is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Moonbeam.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Unit testing
+use crate::mock::{
+	last_event, new_test_ext, next_block, set_author, Balances, Event as MetaEvent, FeeMarket,
+	Origin, Test, TREASURY,
+};
+use crate::{Error, Event};
+use frame_support::{assert_noop, assert_ok};
+use pallet_evm::FeeCalculator;
+use sp_core::U256;
+use sp_runtime::{DispatchError, PerThing, Perbill};
+
+#[test]
+fn genesis_sets_the_floor_as_min_gas_price() {
+	new_test_ext(1_000).execute_with(|| {
+		assert_eq!(FeeMarket::min_gas_price(), U256::from(1_000));
+		assert_eq!(FeeMarket::gas_price_floor(), U256::from(1_000));
+		assert_eq!(FeeMarket::target_fullness(), Perbill::from_percent(50));
+		assert_eq!(
+			<FeeMarket as FeeCalculator>::min_gas_price(),
+			U256::from(1_000)
+		);
+	});
+}
+
+#[test]
+fn price_goes_up_with_full_blocks() {
+	new_test_ext(1_000).execute_with(|| {
+		// A full block is twice the target, so the price goes up by 1/8.
+		next_block(1_000_000);
+		assert_eq!(FeeMarket::min_gas_price(), U256::from(1_125));
+
+		// A block 3/4 full is half the target over it.
+		next_block(750_000);
+		assert_eq!(FeeMarket::min_gas_price(), U256::from(1_195));
+	});
+}
+
+#[test]
+fn price_stays_at_target() {
+	new_test_ext(1_000).execute_with(|| {
+		next_block(1_000_000);
+		next_block(500_000);
+		assert_eq!(FeeMarket::min_gas_price(), U256::from(1_125));
+	});
+}
+
+#[test]
+fn price_goes_down_to_the_floor() {
+	new_test_ext(1_000).execute_with(|| {
+		next_block(1_000_000);
+		next_block(1_000_000);
+		assert_eq!(FeeMarket::min_gas_price(), U256::from(1_265));
+
+		// An empty block lowers the price by 1/8.
+		next_block(0);
+		assert_eq!(FeeMarket::min_gas_price(), U256::from(1_107));
+
+		for _ in 0..10 {
+			next_block(0);
+		}
+		assert_eq!(FeeMarket::min_gas_price(), U256::from(1_000));
+	});
+}
+
+#[test]
+fn price_of_one_can_go_up() {
+	new_test_ext(1).execute_with(|| {
+		next_block(500_001);
+		assert_eq!(FeeMarket::min_gas_price(), U256::from(2));
+	});
+}
+
+#[test]
+fn governance_sets_the_floor() {
+	new_test_ext(1_000).execute_with(|| {
+		assert_noop!(
+			FeeMarket::set_gas_price_floor(Origin::signed(1), U256::from(2_000)),
+			DispatchError::BadOrigin
+		);
+
+		assert_ok!(FeeMarket::set_gas_price_floor(
+			Origin::root(),
+			U256::from(2_000)
+		));
+		assert_eq!(
+			last_event(),
+			MetaEvent::fee_market(Event::GasPriceFloorSet(U256::from(2_000)))
+		);
+		// The minimum gas price is raised to the floor at once.
+		assert_eq!(FeeMarket::min_gas_price(), U256::from(2_000));
+
+		// Lowering the floor lets the price go down over the next blocks.
+		assert_ok!(FeeMarket::set_gas_price_floor(
+			Origin::root(),
+			U256::from(10)
+		));
+		assert_eq!(FeeMarket::min_gas_price(), U256::from(2_000));
+		next_block(0);
+		assert_eq!(FeeMarket::min_gas_price(), U256::from(1_750));
+	});
+}
+
+#[test]
+fn governance_sets_the_target_fullness() {
+	new_test_ext(1_000).execute_with(|| {
+		assert_noop!(
+			FeeMarket::set_target_fullness(Origin::root(), Perbill::zero()),
+			Error::<Test>::ZeroTargetFullness
+		);
+		assert_ok!(FeeMarket::set_target_fullness(
+			Origin::root(),
+			Perbill::from_percent(25)
+		));
+		assert_eq!(
+			last_event(),
+			MetaEvent::fee_market(Event::TargetFullnessSet(Perbill::from_percent(25)))
+		);
+
+		// Half full is now twice the target.
+		next_block(500_000);
+		assert_eq!(FeeMarket::min_gas_price(), U256::from(1_125));
+	});
+}
+
+#[test]
+fn fees_are_refunded_and_split_between_treasury_and_author() {
+	new_test_ext(1).execute_with(|| {
+		set_author(Some(2));
+		let paid = FeeMarket::charge_fee(&1, 500).unwrap();
+		assert_eq!(Balances::free_balance(1), 500);
+
+		FeeMarket::settle_fee(&1, 300, paid);
+		assert_eq!(Balances::free_balance(1), 700);
+		assert_eq!(Balances::free_balance(TREASURY), 60);
+		assert_eq!(Balances::free_balance(2), 240);
+		assert_eq!(
+			last_event(),
+			MetaEvent::fee_market(Event::FeeDistributed(1, 60, Some(2), 240))
+		);
+	});
+}
+
+#[test]
+fn fees_go_to_the_treasury_without_author() {
+	new_test_ext(1).execute_with(|| {
+		let paid = FeeMarket::charge_fee(&1, 500).unwrap();
+		FeeMarket::settle_fee(&1, 500, paid);
+		assert_eq!(Balances::free_balance(1), 500);
+		assert_eq!(Balances::free_balance(TREASURY), 500);
+		assert_eq!(
+			last_event(),
+			MetaEvent::fee_market(Event::FeeDistributed(1, 500, None, 0))
+		);
+	});
+}
+
+#[test]
+fn fees_cannot_exceed_the_balance() {
+	new_test_ext(1).execute_with(|| {
+		assert!(FeeMarket::charge_fee(&1, 1_001).is_err());
+		assert_eq!(Balances::free_balance(1), 1_000);
+	});
+}
+
+#[test]
+fn governance_sets_the_treasury_fee_share() {
+	new_test_ext(1).execute_with(|| {
+		assert_noop!(
+			FeeMarket::set_treasury_fee_share(Origin::signed(1), Perbill::one()),
+			DispatchError::BadOrigin
+		);
+		assert_ok!(FeeMarket::set_treasury_fee_share(
+			Origin::root(),
+			Perbill::from_percent(50)
+		));
+		assert_eq!(
+			last_event(),
+			MetaEvent::fee_market(Event::TreasuryFeeShareSet(Perbill::from_percent(50)))
+		);
+
+		set_author(Some(2));
+		let paid = FeeMarket::charge_fee(&1, 100).unwrap();
+		FeeMarket::settle_fee(&1, 100, paid);
+		assert_eq!(Balances::free_balance(TREASURY), 50);
+		assert_eq!(Balances::free_balance(2), 50);
+	});
+}
@@ -25,7 +25,8 @@
 //!
 //! At the start of every round,
 //! * issuance is distributed to collators for `BondDuration` rounds ago
-//! in proportion to the points they received in that round (for authoring blocks)
+//! in proportion to the points they received in that round (for authoring blocks), after
+//! `ReserveFraction` of it goes to `OnReserve`, such as a treasury
 //! * queued collator exits are executed
 //! * a new set of collators is chosen from the candidates
 //!
@@ -41,6 +42,11 @@
 //! stored in the `ExitQueue` and processed `BondDuration` rounds later to unstake the collator
 //! and all of its nominators.
 //!
+//! Collators slashed for equivocating (sealing two different blocks on the same parent) lose
+//! `SlashFraction` of their own bond, which goes to `OnSlash`, and are taken offline until they
+//! call `go_online` again. Their nominators and the other deposits they reserved are not
+//! slashed.
+//!
 //! To join the set of nominators, an account must call `join_nominators` with
 //! stake >= `MinNominatorStk`. There are also runtime methods for nominating additional collators
 //! and revoking nominations.
@@ -63,7 +69,7 @@ pub mod pallet {
 	use super::{InflationInfo, Range};
 	use crate::set::OrderedSet;
 	use frame_support::pallet_prelude::*;
-	use frame_support::traits::{Currency, Get, Imbalance, ReservableCurrency};
+	use frame_support::traits::{Currency, Get, Imbalance, OnUnbalanced, ReservableCurrency};
 	use frame_system::pallet_prelude::*;
 	use parity_scale_codec::{Decode, Encode};
 	use sp_runtime::{
@@ -365,6 +371,9 @@ pub mod pallet {
 	type RewardPoint = u32;
 	pub type BalanceOf<T> =
 		<<T as Config>::Currency as Currency<<T as frame_system::Config>::AccountId>>::Balance;
+	pub type NegativeImbalanceOf<T> = <<T as Config>::Currency as Currency<
+		<T as frame_system::Config>::AccountId,
+	>>::NegativeImbalance;
 
 	/// Configuration trait of this pallet.
 	#[pallet::config]
@@ -395,6 +404,14 @@ pub mod pallet {
 		type MinNomination: Get<BalanceOf<Self>>;
 		/// Minimum stake for any registered on-chain account to become a nominator
 		type MinNominatorStk: Get<BalanceOf<Self>>;
+		/// Fraction of its own bond that a collator loses for equivocating
+		type SlashFraction: Get<Perbill>;
+		/// Where the slashed funds go
+		type OnSlash: OnUnbalanced<NegativeImbalanceOf<Self>>;
+		/// Fraction of the issuance of each round that goes to `OnReserve` instead of the stakers
+		type ReserveFraction: Get<Perbill>;
+		/// Where the reserved issuance goes
+		type OnReserve: OnUnbalanced<NegativeImbalanceOf<Self>>;
 	}
 
 	#[pallet::error]
@@ -461,6 +478,10 @@ pub mod pallet {
 		CollatorCommissionSet(Perbill, Perbill),
 		/// Set blocks per round [current_round, first_block, old, new]
 		BlocksPerRoundSet(RoundIndex, T::BlockNumber, u32, u32),
+		/// Collator Account, Amount Slashed, New Bond
+		CollatorSlashed(T::AccountId, BalanceOf<T>, BalanceOf<T>),
+		/// Amount of the round issuance that went to `OnReserve`
+		IssuanceReserved(BalanceOf<T>),
 	}
 
 	#[pallet::hooks]
@@ -1165,7 +1186,14 @@ pub mod pallet {
 				let round_to_payout = next - duration;
 				let total = <Points<T>>::get(round_to_payout);
 				let total_staked = <Staked<T>>::get(round_to_payout);
-				let issuance = Self::compute_issuance(total_staked);
+				let mut issuance = Self::compute_issuance(total_staked);
+				let reserved = T::ReserveFraction::get() * issuance;
+				if !total.is_zero() && !reserved.is_zero() {
+					issuance -= reserved;
+					let imbalance = T::Currency::issue(reserved);
+					Self::deposit_event(Event::IssuanceReserved(imbalance.peek()));
+					T::OnReserve::on_unbalanced(imbalance);
+				}
 				for (val, pts) in <AwardedPts<T>>::drain_prefix(round_to_payout) {
 					let pct_due = Perbill::from_rational(pts, total);
 					let mut amt_due = pct_due * issuance;
@@ -1289,4 +1317,38 @@ pub mod pallet {
 			Self::is_selected_candidate(account)
 		}
 	}
+
+	/// Slash `SlashFraction` of the collator's own bond and take it offline
+	impl<T: Config> author_inherent::OnEquivocation<T::AccountId> for Pallet<T> {
+		fn on_equivocation(offender: &T::AccountId) {
+			let mut state = match <CollatorState<T>>::get(offender) {
+				Some(state) => state,
+				None => return,
+			};
+			// The reserved balance also holds the deposits of other pallets, such as the author
+			// mapping one. At most the part of it that backs the bond is slashed, and the bond is
+			// only reduced by what was actually slashed, so that those deposits stay whole.
+			let bonded = T::Currency::reserved_balance(offender).min(state.bond);
+			let (slashed, _) =
+				T::Currency::slash_reserved(offender, T::SlashFraction::get() * bonded);
+			let amount = slashed.peek().min(state.bond);
+			T::OnSlash::on_unbalanced(slashed);
+			state.bond -= amount;
+			state.total -= amount;
+			<Total<T>>::mutate(|total| *total -= amount);
+			Self::deposit_event(Event::CollatorSlashed(offender.clone(), amount, state.bond));
+			if state.is_active() {
+				state.go_offline();
+				let mut candidates = <CandidatePool<T>>::get();
+				if candidates.remove(&Bond::from_owner(offender.clone())) {
+					<CandidatePool<T>>::put(candidates);
+				}
+				Self::deposit_event(Event::CollatorWentOffline(
+					<Round<T>>::get().current,
+					offender.clone(),
+				));
+			}
+			<CollatorState<T>>::insert(offender, state);
+		}
+	}
 }
@@ -19,7 +19,7 @@ use super::*;
 use crate as stake;
 use frame_support::{
 	construct_runtime, parameter_types,
-	traits::{GenesisBuild, OnFinalize, OnInitialize},
+	traits::{Currency, GenesisBuild, Get, OnFinalize, OnInitialize, OnUnbalanced},
 	weights::Weight,
 };
 use sp_core::H256;
@@ -29,6 +29,7 @@ use sp_runtime::{
 	traits::{BlakeTwo256, IdentityLookup},
 	Perbill,
 };
+use std::cell::RefCell;
 
 pub type AccountId = u64;
 pub type Balance = u128;
@@ -104,7 +105,33 @@ parameter_types! {
 	pub const MinCollatorStk: u128 = 10;
 	pub const MinNominatorStk: u128 = 5;
 	pub const MinNomination: u128 = 3;
+	pub const SlashFraction: Perbill = Perbill::from_percent(10);
 }
+
+thread_local! {
+	static RESERVE_FRACTION: RefCell<Perbill> = RefCell::new(Perbill::zero());
+}
+
+/// The share of the issuance that goes to `TREASURY`, set by the tests.
+pub(crate) struct ReserveFraction;
+
+impl Get<Perbill> for ReserveFraction {
+	fn get() -> Perbill {
+		RESERVE_FRACTION.with(|fraction| *fraction.borrow())
+	}
+}
+
+pub(crate) const TREASURY: AccountId = 999;
+
+/// Deposits slashes and reserved issuance into `TREASURY`.
+pub(crate) struct ToTreasury;
+
+impl OnUnbalanced<NegativeImbalanceOf<Test>> for ToTreasury {
+	fn on_nonzero_unbalanced(amount: NegativeImbalanceOf<Test>) {
+		Balances::resolve_creating(&TREASURY, amount);
+	}
+}
+
 impl Config for Test {
 	type Event = Event;
 	type Currency = Balances;
@@ -119,6 +146,10 @@ impl Config for Test {
 	type MinCollatorCandidateStk = MinCollatorStk;
 	type MinNominatorStk = MinNominatorStk;
 	type MinNomination = MinNomination;
+	type SlashFraction = SlashFraction;
+	type OnSlash = ToTreasury;
+	type ReserveFraction = ReserveFraction;
+	type OnReserve = ToTreasury;
 }
 
 pub(crate) struct ExtBuilder {
@@ -130,6 +161,8 @@ pub(crate) struct ExtBuilder {
 	nominators: Vec<(AccountId, AccountId, Balance)>,
 	// inflation config
 	inflation: InflationInfo<Balance>,
+	// share of the issuance that goes to the treasury
+	reserve_fraction: Perbill,
 }
 
 impl Default for ExtBuilder {
@@ -151,6 +184,7 @@ impl Default for ExtBuilder {
 					max: Perbill::from_percent(5),
 				},
 			},
+			reserve_fraction: Perbill::zero(),
 		}
 	}
 }
@@ -180,7 +214,14 @@ impl ExtBuilder {
 		self
 	}
 
+	pub(crate) fn with_reserve_fraction(mut self, reserve_fraction: Perbill) -> Self {
+		self.reserve_fraction = reserve_fraction;
+		self
+	}
+
 	pub(crate) fn build(self) -> sp_io::TestExternalities {
+		RESERVE_FRACTION.with(|fraction| *fraction.borrow_mut() = self.reserve_fraction);
+
 		let mut t = frame_system::GenesisConfig::default()
 			.build_storage::<Test>()
 			.expect("Frame system builds valid default genesis config");
@@ -17,11 +17,12 @@
 //! Unit testing
 use crate::mock::{
 	events, last_event, roll_to, set_author, Balances, Event as MetaEvent, ExtBuilder, Origin,
-	Stake, System, Test,
+	Stake, System, Test, TREASURY,
 };
 use crate::{CollatorStatus, Error, Event};
-use frame_support::{assert_noop, assert_ok};
-use sp_runtime::{traits::Zero, DispatchError};
+use author_inherent::OnEquivocation;
+use frame_support::{assert_noop, assert_ok, traits::ReservableCurrency};
+use sp_runtime::{traits::Zero, DispatchError, Perbill};
 
 #[test]
 fn geneses() {
@@ -1235,3 +1236,149 @@ fn round_transitions() {
 			assert_eq!(last_event(), MetaEvent::stake(Event::NewRound(8, 3, 1, 40)));
 		});
 }
+
+#[test]
+fn reserve_fraction_of_issuance_goes_to_treasury() {
+	ExtBuilder::default()
+		.with_balances(vec![
+			(1, 1000),
+			(2, 1000),
+			(3, 1000),
+			(4, 1000),
+			(5, 1000),
+			(6, 1000),
+			(7, 33),
+			(8, 33),
+			(9, 33),
+		])
+		.with_collators(vec![(1, 100), (2, 90), (3, 80), (4, 70), (5, 60), (6, 50)])
+		.with_reserve_fraction(Perbill::from_percent(20))
+		.build()
+		.execute_with(|| {
+			roll_to(8);
+			set_author(2, 1, 100);
+			roll_to(16);
+			// 20% of the 305 issued this round goes to the treasury
+			let events = events();
+			assert!(events.contains(&Event::IssuanceReserved(61)));
+			assert!(events.contains(&Event::Rewarded(1, 244)));
+			assert_eq!(Balances::free_balance(&TREASURY), 61);
+		});
+}
+
+#[test]
+fn nothing_is_reserved_for_rounds_without_points() {
+	ExtBuilder::default()
+		.with_balances(vec![(1, 1000), (2, 1000)])
+		.with_collators(vec![(1, 100), (2, 90)])
+		.with_reserve_fraction(Perbill::from_percent(20))
+		.build()
+		.execute_with(|| {
+			roll_to(16);
+			assert!(events()
+				.iter()
+				.all(|e| !matches!(e, Event::IssuanceReserved(_))));
+			assert_eq!(Balances::free_balance(&TREASURY), 0);
+		});
+}
+
+#[test]
+fn equivocation_slashes_collator_and_takes_it_offline() {
+	ExtBuilder::default()
+		.with_balances(vec![(1, 1000), (2, 300), (3, 100), (4, 100)])
+		.with_collators(vec![(1, 500), (2, 200)])
+		.with_nominators(vec![(3, 1, 100), (4, 1, 100)])
+		.build()
+		.execute_with(|| {
+			assert_eq!(Stake::total(), 900);
+			<Stake as OnEquivocation<_>>::on_equivocation(&1);
+			assert_eq!(
+				events(),
+				vec![
+					Event::CollatorSlashed(1, 50, 450),
+					Event::CollatorWentOffline(1, 1),
+				]
+			);
+			let info = Stake::collator_state(&1).unwrap();
+			assert_eq!(info.state, CollatorStatus::Idle);
+			assert_eq!(info.bond, 450);
+			assert_eq!(info.total, 650);
+			assert_eq!(Stake::total(), 850);
+			assert_eq!(Balances::reserved_balance(&1), 450);
+			assert_eq!(Balances::free_balance(&1), 500);
+			assert_eq!(Balances::free_balance(&TREASURY), 50);
+			// nominators are not slashed
+			assert_eq!(Balances::reserved_balance(&3), 100);
+			assert!(Stake::candidate_pool().0.iter().all(|bond| bond.owner != 1));
+			assert_ok!(Stake::go_online(Origin::signed(1)));
+		});
+}
+
+#[test]
+fn equivocation_only_slashes_the_bond() {
+	ExtBuilder::default()
+		.with_balances(vec![(1, 1000), (2, 300)])
+		.with_collators(vec![(1, 500), (2, 200)])
+		.build()
+		.execute_with(|| {
+			// A deposit reserved by another pallet, such as the author mapping one
+			assert_ok!(<Balances as ReservableCurrency<_>>::reserve(&1, 100));
+			<Stake as OnEquivocation<_>>::on_equivocation(&1);
+			let info = Stake::collator_state(&1).unwrap();
+			assert_eq!(info.bond, 450);
+			assert_eq!(Balances::reserved_balance(&1), info.bond + 100);
+		});
+}
+
+#[test]
+fn equivocation_slash_is_bounded_by_the_reserved_bond() {
+	ExtBuilder::default()
+		.with_balances(vec![(1, 1000), (2, 300)])
+		.with_collators(vec![(1, 500), (2, 200)])
+		.build()
+		.execute_with(|| {
+			// Part of the bond was already taken by someone else
+			let _ = <Balances as ReservableCurrency<_>>::slash_reserved(&1, 300);
+			<Stake as OnEquivocation<_>>::on_equivocation(&1);
+			assert_eq!(
+				events(),
+				vec![
+					Event::CollatorSlashed(1, 20, 480),
+					Event::CollatorWentOffline(1, 1),
+				]
+			);
+			assert_eq!(Balances::reserved_balance(&1), 180);
+		});
+}
+
+#[test]
+fn equivocation_slashes_leaving_collator() {
+	ExtBuilder::default()
+		.with_balances(vec![(1, 1000), (2, 300)])
+		.with_collators(vec![(1, 500), (2, 200)])
+		.build()
+		.execute_with(|| {
+			assert_ok!(Stake::leave_candidates(Origin::signed(2)));
+			<Stake as OnEquivocation<_>>::on_equivocation(&2);
+			assert_eq!(
+				last_event(),
+				MetaEvent::stake(Event::CollatorSlashed(2, 20, 180))
+			);
+			let info = Stake::collator_state(&2).unwrap();
+			assert_eq!(info.state, CollatorStatus::Leaving(3));
+			assert_eq!(Balances::reserved_balance(&2), 180);
+		});
+}
+
+#[test]
+fn equivocation_by_non_candidate_is_ignored() {
+	ExtBuilder::default()
+		.with_balances(vec![(1, 1000), (3, 100)])
+		.with_collators(vec![(1, 500)])
+		.build()
+		.execute_with(|| {
+			<Stake as OnEquivocation<_>>::on_equivocation(&3);
+			assert!(events().is_empty());
+			assert_eq!(Balances::free_balance(&3), 100);
+		});
+}
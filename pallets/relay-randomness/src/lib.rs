@@ -0,0 +1,407 @@
+// Copyright 2019-2021 PureStake Inc.
+// This file is part of Moonbeam.
+
+// Moonbeam is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Moonbeam is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Moonbeam.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Randomness taken from the BABE randomness of the relay chain.
+//!
+//! Block authors include an inherent with a proof of the BABE storage of the relay parent, which
+//! the pallet checks against the relay parent storage root of the validation data. The relay
+//! chain validators check that root when they validate the block, so the BABE values cannot be
+//! forged.
+//!
+//! Two kinds of randomness are offered:
+//! - The epoch randomness is the BABE randomness of the current relay chain epoch. It is known
+//!   from the start of the previous relay chain epoch, so it must only be used where being
+//!   predictable is fine.
+//! - Randomness can be requested for a future block, and fulfilled once that block is built. It
+//!   is derived from the VRF output of the author of the relay parent of the block, and nobody
+//!   knows it when it is requested. Relay blocks authored in secondary slots without VRF have no
+//!   VRF output, and the requests then get the randomness of the next block with one. Requests
+//!   reserve `RequestDeposit` from the requester, which gets it back when it fulfils the request.
+//!   Requests that are not fulfilled within `RequestExpiry` blocks of their block expire, and
+//!   anyone can then purge them, which also returns the deposit.
+//!
+//! How biasable it is:
+//! - The author of a relay block cannot choose its VRF output, but it can withhold the block, at
+//!   the cost of its reward.
+//! - The collator of a block picks its relay parent among a few recent relay blocks, and knows the
+//!   randomness of the block while building it. It can choose between those few values, or not
+//!   produce the block at all, at the cost of its reward.
+//! So it is fine for games and sampling where the gain of biasing it is small, but not for
+//! lotteries where a relay chain validator or a collator could win more than it risks.
+
+#![cfg_attr(not(feature = "std"), no_std)]
+
+use frame_support::pallet;
+use sp_core::H256;
+use sp_inherents::InherentIdentifier;
+use sp_std::vec::Vec;
+
+pub use pallet::*;
+
+#[cfg(test)]
+mod mock;
+#[cfg(test)]
+mod tests;
+
+/// The identifier of the inherent data holding the proof of the relay chain BABE storage.
+pub const INHERENT_IDENTIFIER: InherentIdentifier = *b"relayrnd";
+
+/// The relay chain block the current parachain block builds on.
+pub trait RelayParent {
+	/// The storage root of the relay parent, once the validation data of the current block is
+	/// known.
+	fn relay_parent_storage_root() -> Option<H256>;
+}
+
+/// The relay chain storage key of the BABE randomness of the current epoch.
+pub fn babe_randomness_key() -> Vec<u8> {
+	storage_key(b"Babe", b"Randomness")
+}
+
+/// The relay chain storage key of the VRF output of the author of the block.
+pub fn babe_author_vrf_randomness_key() -> Vec<u8> {
+	storage_key(b"Babe", b"AuthorVrfRandomness")
+}
+
+/// The relay chain storage keys the inherent proves.
+pub fn relay_storage_keys() -> Vec<Vec<u8>> {
+	sp_std::vec![babe_randomness_key(), babe_author_vrf_randomness_key()]
+}
+
+fn storage_key(pallet: &[u8], item: &[u8]) -> Vec<u8> {
+	[
+		sp_io::hashing::twox_128(pallet),
+		sp_io::hashing::twox_128(item),
+	]
+	.concat()
+}
+
+#[pallet]
+pub mod pallet {
+	use crate::{babe_author_vrf_randomness_key, babe_randomness_key, RelayParent};
+	use frame_support::pallet_prelude::*;
+	use frame_support::traits::{Currency, ReservableCurrency};
+	use frame_system::pallet_prelude::*;
+	use parity_scale_codec::{Decode, Encode};
+	use sp_core::H256;
+	use sp_inherents::{InherentData, InherentIdentifier, MakeFatalError, ProvideInherent};
+	use sp_runtime::{
+		traits::{BlakeTwo256, Saturating, Zero},
+		RuntimeDebug,
+	};
+	use sp_std::vec::Vec;
+	use sp_trie::{read_trie_value, Layout, StorageProof};
+
+	pub type BalanceOf<T> =
+		<<T as Config>::Currency as Currency<<T as frame_system::Config>::AccountId>>::Balance;
+
+	/// A request for the randomness of a future block.
+	#[derive(Encode, Decode, PartialEq, Eq, RuntimeDebug)]
+	pub struct Request<AccountId, BlockNumber, Balance> {
+		/// The only account that can fulfil the request
+		pub requester: AccountId,
+		/// The block whose randomness is requested
+		pub block: BlockNumber,
+		/// The deposit reserved from the requester
+		pub deposit: Balance,
+	}
+
+	/// The Relay Randomness pallet
+	#[pallet::pallet]
+	pub struct Pallet<T>(PhantomData<T>);
+
+	/// Configuration trait of this pallet.
+	#[pallet::config]
+	pub trait Config: frame_system::Config {
+		/// The overarching event type
+		type Event: From<Event<Self>> + IsType<<Self as frame_system::Config>::Event>;
+		/// The relay parent of the current block
+		type RelayParent: RelayParent;
+		/// How many blocks ahead randomness can be requested
+		#[pallet::constant]
+		type MaxRequestDelay: Get<Self::BlockNumber>;
+		/// Currency in which the request deposit is reserved
+		type Currency: ReservableCurrency<Self::AccountId>;
+		/// Deposit reserved for each pending request
+		#[pallet::constant]
+		type RequestDeposit: Get<BalanceOf<Self>>;
+		/// How many blocks after its block a request can still be fulfilled
+		#[pallet::constant]
+		type RequestExpiry: Get<Self::BlockNumber>;
+	}
+
+	#[pallet::error]
+	pub enum Error<T> {
+		/// Randomness can only be requested for a future block
+		BlockNotInFuture,
+		/// The block is further ahead than `MaxRequestDelay`
+		BlockTooFarAhead,
+		/// There is no request with this id
+		RequestNotFound,
+		/// The request was made by another account
+		NotYourRequest,
+		/// The requested block has not been built yet
+		RandomnessNotReady,
+		/// The requester cannot afford the request deposit
+		CannotAffordDeposit,
+		/// The request was not fulfilled in time
+		RequestExpired,
+		/// The request can still be fulfilled
+		RequestNotExpired,
+		/// The relay randomness inherent was already included in the block
+		InherentAlreadyIncluded,
+		/// The validation data of the block is not known yet
+		NoRelayParent,
+		/// The proof does not prove the BABE storage of the relay parent
+		InvalidRelayStateProof,
+	}
+
+	#[pallet::event]
+	#[pallet::generate_deposit(pub(crate) fn deposit_event)]
+	pub enum Event<T: Config> {
+		/// Request id, Requester, Block whose randomness is requested
+		RandomnessRequested(u64, T::AccountId, T::BlockNumber),
+		/// Request id, Randomness it was fulfilled with
+		RandomnessFulfilled(u64, H256),
+		/// Request id of an expired request that was purged
+		RequestPurged(u64),
+	}
+
+	#[pallet::hooks]
+	impl<T: Config> Hooks<BlockNumberFor<T>> for Pallet<T> {
+		fn on_initialize(_n: T::BlockNumber) -> Weight {
+			// The work of `on_finalize`
+			T::DbWeight::get().reads_writes(1, 1)
+		}
+
+		fn on_finalize(_n: T::BlockNumber) {
+			assert!(
+				<InherentIncluded<T>>::take(),
+				"The relay randomness inherent must be included in every block"
+			);
+		}
+	}
+
+	#[pallet::inherent]
+	impl<T: Config> ProvideInherent for Pallet<T> {
+		type Call = Call<T>;
+		type Error = MakeFatalError<()>;
+		const INHERENT_IDENTIFIER: InherentIdentifier = crate::INHERENT_IDENTIFIER;
+
+		fn create_inherent(data: &InherentData) -> Option<Self::Call> {
+			let proof: StorageProof = data.get_data(&Self::INHERENT_IDENTIFIER).ok().flatten()?;
+			Some(Call::set_relay_randomness(proof))
+		}
+	}
+
+	#[pallet::call]
+	impl<T: Config> Pallet<T> {
+		/// Inherent recording the BABE randomness of the relay parent, from `proof` of its
+		/// storage.
+		#[pallet::weight((
+			T::DbWeight::get().reads_writes(4, 3),
+			DispatchClass::Mandatory
+		))]
+		pub fn set_relay_randomness(
+			origin: OriginFor<T>,
+			proof: StorageProof,
+		) -> DispatchResultWithPostInfo {
+			ensure_none(origin)?;
+			ensure!(
+				!<InherentIncluded<T>>::get(),
+				Error::<T>::InherentAlreadyIncluded
+			);
+			let root =
+				T::RelayParent::relay_parent_storage_root().ok_or(Error::<T>::NoRelayParent)?;
+			let (epoch_randomness, author_vrf_randomness) = Self::read_babe_randomness(root, proof)
+				.ok_or(Error::<T>::InvalidRelayStateProof)?;
+
+			<InherentIncluded<T>>::put(true);
+			<EpochRandomness<T>>::put(epoch_randomness);
+
+			// The requests for this block wait for a relay parent with a VRF output.
+			let n = frame_system::Pallet::<T>::block_number();
+			if <RequestedBlocks<T>>::contains_key(n) {
+				<AwaitingRandomness<T>>::append(n);
+			}
+			if let Some(vrf_output) = author_vrf_randomness {
+				for block in <AwaitingRandomness<T>>::take() {
+					// Requests purged while waiting leave their block behind.
+					if <RequestedBlocks<T>>::contains_key(block) {
+						let randomness = H256(sp_io::hashing::blake2_256(
+							&(b"relay-randomness", vrf_output, block).encode(),
+						));
+						<BlockRandomness<T>>::insert(block, randomness);
+					}
+				}
+			}
+
+			Ok(Default::default())
+		}
+
+		/// Remove the expired request `id` and return its deposit to the requester.
+		#[pallet::weight(T::DbWeight::get().reads_writes(3, 4))]
+		pub fn purge_expired_request(origin: OriginFor<T>, id: u64) -> DispatchResultWithPostInfo {
+			ensure_signed(origin)?;
+			let request = <Requests<T>>::get(id).ok_or(Error::<T>::RequestNotFound)?;
+			ensure!(Self::is_expired(&request), Error::<T>::RequestNotExpired);
+
+			Self::remove_request(id, request);
+			Self::deposit_event(Event::RequestPurged(id));
+
+			Ok(Default::default())
+		}
+	}
+
+	impl<T: Config> Pallet<T> {
+		/// Request the randomness of the future `block` on behalf of `requester`, reserving the
+		/// request deposit, and return the id of the request.
+		pub fn request_randomness(
+			requester: T::AccountId,
+			block: T::BlockNumber,
+		) -> Result<u64, DispatchError> {
+			let now = frame_system::Pallet::<T>::block_number();
+			ensure!(block > now, Error::<T>::BlockNotInFuture);
+			ensure!(
+				block <= now.saturating_add(T::MaxRequestDelay::get()),
+				Error::<T>::BlockTooFarAhead
+			);
+
+			let deposit = T::RequestDeposit::get();
+			T::Currency::reserve(&requester, deposit)
+				.map_err(|_| Error::<T>::CannotAffordDeposit)?;
+
+			let id = <NextRequestId<T>>::get();
+			<NextRequestId<T>>::put(id.wrapping_add(1));
+			<RequestedBlocks<T>>::mutate(block, |count| *count = count.saturating_add(1));
+			<Requests<T>>::insert(
+				id,
+				Request {
+					requester: requester.clone(),
+					block,
+					deposit,
+				},
+			);
+			Self::deposit_event(Event::RandomnessRequested(id, requester, block));
+
+			Ok(id)
+		}
+
+		/// Fulfil the request `id` of `requester`, returning the randomness of its block and the
+		/// request deposit.
+		pub fn fulfil_randomness(requester: &T::AccountId, id: u64) -> Result<H256, DispatchError> {
+			let request = <Requests<T>>::get(id).ok_or(Error::<T>::RequestNotFound)?;
+			ensure!(&request.requester == requester, Error::<T>::NotYourRequest);
+			ensure!(!Self::is_expired(&request), Error::<T>::RequestExpired);
+			let randomness =
+				<BlockRandomness<T>>::get(request.block).ok_or(Error::<T>::RandomnessNotReady)?;
+
+			Self::remove_request(id, request);
+			Self::deposit_event(Event::RandomnessFulfilled(id, randomness));
+
+			Ok(randomness)
+		}
+
+		/// Read the BABE randomness of the current epoch and the VRF output of the block author
+		/// from `proof` of the relay chain storage with `root`.
+		fn read_babe_randomness(root: H256, proof: StorageProof) -> Option<(H256, Option<H256>)> {
+			let db = proof.into_memory_db::<BlakeTwo256>();
+			// Missing trie nodes, including the root, are errors, while absent keys are not.
+			let read =
+				|key: Vec<u8>| read_trie_value::<Layout<BlakeTwo256>, _>(&db, &root, &key).ok();
+			// Both are `ValueQuery` storage items of BABE, absent while they have their default
+			// value.
+			let epoch_randomness = match read(babe_randomness_key())? {
+				Some(encoded) => <[u8; 32]>::decode(&mut &encoded[..]).ok()?,
+				None => [0u8; 32],
+			};
+			let author_vrf_randomness = match read(babe_author_vrf_randomness_key())? {
+				Some(encoded) => Option::<[u8; 32]>::decode(&mut &encoded[..]).ok()?,
+				None => None,
+			};
+			Some((H256(epoch_randomness), author_vrf_randomness.map(H256)))
+		}
+
+		/// Whether `request` can no longer be fulfilled.
+		fn is_expired(request: &Request<T::AccountId, T::BlockNumber, BalanceOf<T>>) -> bool {
+			frame_system::Pallet::<T>::block_number()
+				> request.block.saturating_add(T::RequestExpiry::get())
+		}
+
+		/// Remove the request `id` and return its deposit. The randomness of a block is forgotten
+		/// once all its requests are removed.
+		fn remove_request(id: u64, request: Request<T::AccountId, T::BlockNumber, BalanceOf<T>>) {
+			<Requests<T>>::remove(id);
+			T::Currency::unreserve(&request.requester, request.deposit);
+			let remaining = <RequestedBlocks<T>>::get(request.block).saturating_sub(1);
+			if remaining.is_zero() {
+				<RequestedBlocks<T>>::remove(request.block);
+				<BlockRandomness<T>>::remove(request.block);
+			} else {
+				<RequestedBlocks<T>>::insert(request.block, remaining);
+			}
+		}
+
+		/// Whether the request `id` can be fulfilled.
+		pub fn is_ready(id: u64) -> bool {
+			<Requests<T>>::get(id)
+				.map(|request| {
+					!Self::is_expired(&request) && <BlockRandomness<T>>::contains_key(request.block)
+				})
+				.unwrap_or(false)
+		}
+	}
+
+	/// The BABE randomness of the current relay chain epoch.
+	#[pallet::storage]
+	#[pallet::getter(fn epoch_randomness)]
+	pub type EpochRandomness<T: Config> = StorageValue<_, H256, OptionQuery>;
+
+	/// Whether the relay randomness inherent was included in the current block.
+	#[pallet::storage]
+	pub type InherentIncluded<T: Config> = StorageValue<_, bool, ValueQuery>;
+
+	/// The blocks with pending requests whose relay parents had no VRF output, waiting for the
+	/// next one that has.
+	#[pallet::storage]
+	pub type AwaitingRandomness<T: Config> = StorageValue<_, Vec<T::BlockNumber>, ValueQuery>;
+
+	/// The randomness of the blocks with pending requests, kept until the requests are fulfilled
+	/// or purged.
+	#[pallet::storage]
+	pub type BlockRandomness<T: Config> =
+		StorageMap<_, Twox64Concat, T::BlockNumber, H256, OptionQuery>;
+
+	/// The number of pending requests for the randomness of each block.
+	#[pallet::storage]
+	pub type RequestedBlocks<T: Config> =
+		StorageMap<_, Twox64Concat, T::BlockNumber, u32, ValueQuery>;
+
+	/// The pending requests, by id.
+	#[pallet::storage]
+	#[pallet::getter(fn request)]
+	pub type Requests<T: Config> = StorageMap<
+		_,
+		Twox64Concat,
+		u64,
+		Request<T::AccountId, T::BlockNumber, BalanceOf<T>>,
+		OptionQuery,
+	>;
+
+	/// The id of the next request.
+	#[pallet::storage]
+	pub type NextRequestId<T: Config> = StorageValue<_, u64, ValueQuery>;
+}
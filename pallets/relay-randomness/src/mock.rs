@@ -0,0 +1,194 @@
+// Copyright 2019-2021 PureStake Inc.
+// This file is part of Moonbeam.
+
+// Moonbeam is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Moonbeam is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Moonbeam.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Test utilities
+use super::*;
+use crate as relay_randomness;
+use frame_support::{
+	construct_runtime, parameter_types,
+	traits::{OnFinalize, OnInitialize},
+};
+use parity_scale_codec::Encode;
+use sp_core::H256;
+use sp_io;
+use sp_runtime::{
+	testing::Header,
+	traits::{BlakeTwo256, IdentityLookup},
+};
+use sp_trie::StorageProof;
+use std::cell::RefCell;
+
+pub type AccountId = u64;
+pub type Balance = u128;
+pub type BlockNumber = u64;
+
+type UncheckedExtrinsic = frame_system::mocking::MockUncheckedExtrinsic<Test>;
+type Block = frame_system::mocking::MockBlock<Test>;
+
+// Configure a mock runtime to test the pallet.
+construct_runtime!(
+	pub enum Test where
+		Block = Block,
+		NodeBlock = Block,
+		UncheckedExtrinsic = UncheckedExtrinsic,
+	{
+		System: frame_system::{Pallet, Call, Config, Storage, Event<T>},
+		Balances: pallet_balances::{Pallet, Call, Storage, Config<T>, Event<T>},
+		RelayRandomness: relay_randomness::{Pallet, Call, Storage, Event<T>},
+	}
+);
+
+parameter_types! {
+	pub const BlockHashCount: u64 = 250;
+	pub const SS58Prefix: u8 = 42;
+}
+impl frame_system::Config for Test {
+	type BaseCallFilter = ();
+	type DbWeight = ();
+	type Origin = Origin;
+	type Index = u64;
+	type BlockNumber = BlockNumber;
+	type Call = Call;
+	type Hash = H256;
+	type Hashing = BlakeTwo256;
+	type AccountId = AccountId;
+	type Lookup = IdentityLookup<Self::AccountId>;
+	type Header = Header;
+	type Event = Event;
+	type BlockHashCount = BlockHashCount;
+	type Version = ();
+	type PalletInfo = PalletInfo;
+	type AccountData = pallet_balances::AccountData<Balance>;
+	type OnNewAccount = ();
+	type OnKilledAccount = ();
+	type SystemWeightInfo = ();
+	type BlockWeights = ();
+	type BlockLength = ();
+	type SS58Prefix = SS58Prefix;
+}
+
+parameter_types! {
+	pub const ExistentialDeposit: u128 = 1;
+}
+impl pallet_balances::Config for Test {
+	type MaxLocks = ();
+	type Balance = Balance;
+	type Event = Event;
+	type DustRemoval = ();
+	type ExistentialDeposit = ExistentialDeposit;
+	type AccountStore = System;
+	type WeightInfo = ();
+}
+
+thread_local! {
+	static RELAY_PARENT_STORAGE_ROOT: RefCell<Option<H256>> = RefCell::new(None);
+}
+
+/// A relay parent set by the tests.
+pub(crate) struct MockRelayParent;
+
+impl RelayParent for MockRelayParent {
+	fn relay_parent_storage_root() -> Option<H256> {
+		RELAY_PARENT_STORAGE_ROOT.with(|root| *root.borrow())
+	}
+}
+
+pub(crate) fn set_relay_parent_storage_root(root: Option<H256>) {
+	RELAY_PARENT_STORAGE_ROOT.with(|current| *current.borrow_mut() = root);
+}
+
+parameter_types! {
+	pub const MaxRequestDelay: BlockNumber = 5;
+	pub const RequestDeposit: Balance = 10;
+	pub const RequestExpiry: BlockNumber = 5;
+}
+impl Config for Test {
+	type Event = Event;
+	type RelayParent = MockRelayParent;
+	type MaxRequestDelay = MaxRequestDelay;
+	type Currency = Balances;
+	type RequestDeposit = RequestDeposit;
+	type RequestExpiry = RequestExpiry;
+}
+
+/// Accounts 1 and 2 can afford a few request deposits, account 3 cannot afford any.
+pub(crate) fn new_test_ext() -> sp_io::TestExternalities {
+	let mut t = frame_system::GenesisConfig::default()
+		.build_storage::<Test>()
+		.expect("Frame system builds valid default genesis config");
+
+	pallet_balances::GenesisConfig::<Test> {
+		balances: vec![(1, 100), (2, 100), (3, 5)],
+	}
+	.assimilate_storage(&mut t)
+	.expect("Pallet balances storage can be assimilated");
+
+	set_relay_parent_storage_root(None);
+	let mut ext = sp_io::TestExternalities::new(t);
+	ext.execute_with(|| System::set_block_number(1));
+	ext
+}
+
+/// The BABE epoch randomness of the relay parents of `finalize_block`.
+pub(crate) const EPOCH_RANDOMNESS: H256 = H256([0xee; 32]);
+
+/// The storage root and a proof of the BABE storage of a relay chain state with
+/// `epoch_randomness` and `author_vrf_randomness`.
+pub(crate) fn relay_state(
+	epoch_randomness: H256,
+	author_vrf_randomness: Option<H256>,
+) -> (H256, StorageProof) {
+	let mut backend = sp_state_machine::new_in_mem::<BlakeTwo256>();
+	backend.insert(vec![(
+		None,
+		vec![
+			(babe_randomness_key(), Some(epoch_randomness.0.encode())),
+			(
+				babe_author_vrf_randomness_key(),
+				Some(author_vrf_randomness.map(|vrf| vrf.0).encode()),
+			),
+		],
+	)]);
+	let root = *backend.root();
+	let proof = sp_state_machine::prove_read(backend, relay_storage_keys())
+		.expect("The keys are in the backend");
+	(root, proof)
+}
+
+/// Include the relay randomness inherent for a relay parent with `epoch_randomness` and
+/// `author_vrf_randomness`, finalize the current block and start the next one.
+pub(crate) fn build_block(epoch_randomness: H256, author_vrf_randomness: Option<H256>) {
+	let n = System::block_number();
+	let (root, proof) = relay_state(epoch_randomness, author_vrf_randomness);
+	set_relay_parent_storage_root(Some(root));
+	RelayRandomness::set_relay_randomness(Origin::none(), proof)
+		.expect("The proof matches the relay parent");
+	RelayRandomness::on_finalize(n);
+	set_relay_parent_storage_root(None);
+
+	System::set_block_number(n + 1);
+	RelayRandomness::on_initialize(n + 1);
+}
+
+/// Finalize the current block, built on a relay parent whose author has `author_vrf_randomness`,
+/// and start the next one.
+pub(crate) fn finalize_block(author_vrf_randomness: H256) {
+	build_block(EPOCH_RANDOMNESS, Some(author_vrf_randomness));
+}
+
+pub(crate) fn last_event() -> Event {
+	System::events().pop().expect("Event expected").event
+}
@@ -0,0 +1,285 @@
+// Copyright 2019-2021 PureStake Inc.
+// This file is part of Moonbeam.
+
+// Moonbeam is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Moonbeam is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Moonbeam.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Unit testing
+use crate::mock::{
+	build_block, finalize_block, last_event, new_test_ext, relay_state,
+	set_relay_parent_storage_root, Balances, Event as MetaEvent, Origin, RelayRandomness, System,
+	Test, EPOCH_RANDOMNESS,
+};
+use crate::{AwaitingRandomness, BlockRandomness, Error, Event, RequestedBlocks, Requests};
+use frame_support::{assert_noop, assert_ok, traits::OnFinalize};
+use sp_core::H256;
+use sp_runtime::DispatchError;
+
+#[test]
+fn epoch_randomness_is_the_babe_randomness_of_the_relay_parent() {
+	new_test_ext().execute_with(|| {
+		assert_eq!(RelayRandomness::epoch_randomness(), None);
+
+		finalize_block(H256::repeat_byte(1));
+		assert_eq!(RelayRandomness::epoch_randomness(), Some(EPOCH_RANDOMNESS));
+
+		// It changes with the relay chain epoch, whatever the VRF output.
+		build_block(H256::repeat_byte(2), None);
+		assert_eq!(
+			RelayRandomness::epoch_randomness(),
+			Some(H256::repeat_byte(2))
+		);
+	});
+}
+
+#[test]
+fn the_inherent_needs_the_relay_parent() {
+	new_test_ext().execute_with(|| {
+		let (_, proof) = relay_state(EPOCH_RANDOMNESS, Some(H256::repeat_byte(1)));
+		assert_noop!(
+			RelayRandomness::set_relay_randomness(Origin::none(), proof),
+			Error::<Test>::NoRelayParent
+		);
+	});
+}
+
+#[test]
+fn the_inherent_checks_the_proof_against_the_relay_parent() {
+	new_test_ext().execute_with(|| {
+		let (root, _) = relay_state(EPOCH_RANDOMNESS, Some(H256::repeat_byte(1)));
+		let (_, other_proof) = relay_state(EPOCH_RANDOMNESS, Some(H256::repeat_byte(2)));
+		set_relay_parent_storage_root(Some(root));
+		assert_noop!(
+			RelayRandomness::set_relay_randomness(Origin::none(), other_proof),
+			Error::<Test>::InvalidRelayStateProof
+		);
+	});
+}
+
+#[test]
+fn the_inherent_is_included_once() {
+	new_test_ext().execute_with(|| {
+		let (root, proof) = relay_state(EPOCH_RANDOMNESS, Some(H256::repeat_byte(1)));
+		set_relay_parent_storage_root(Some(root));
+		assert_noop!(
+			RelayRandomness::set_relay_randomness(Origin::signed(1), proof.clone()),
+			DispatchError::BadOrigin
+		);
+		assert_ok!(RelayRandomness::set_relay_randomness(
+			Origin::none(),
+			proof.clone()
+		));
+		assert_noop!(
+			RelayRandomness::set_relay_randomness(Origin::none(), proof),
+			Error::<Test>::InherentAlreadyIncluded
+		);
+	});
+}
+
+#[test]
+#[should_panic(expected = "The relay randomness inherent must be included in every block")]
+fn blocks_without_the_inherent_are_invalid() {
+	new_test_ext().execute_with(|| {
+		RelayRandomness::on_finalize(1);
+	});
+}
+
+#[test]
+fn requests_must_target_near_future_blocks() {
+	new_test_ext().execute_with(|| {
+		assert_noop!(
+			RelayRandomness::request_randomness(1, 1),
+			Error::<Test>::BlockNotInFuture
+		);
+		assert_noop!(
+			RelayRandomness::request_randomness(1, 7),
+			Error::<Test>::BlockTooFarAhead
+		);
+		assert_eq!(RelayRandomness::request_randomness(1, 6), Ok(0));
+		assert_eq!(
+			last_event(),
+			MetaEvent::relay_randomness(Event::RandomnessRequested(0, 1, 6))
+		);
+		assert_eq!(RelayRandomness::request_randomness(2, 6), Ok(1));
+	});
+}
+
+#[test]
+fn requests_are_fulfilled_once_their_block_is_built() {
+	new_test_ext().execute_with(|| {
+		assert_ok!(RelayRandomness::request_randomness(1, 3));
+		assert_ok!(RelayRandomness::request_randomness(2, 3));
+
+		finalize_block(H256::repeat_byte(1));
+		finalize_block(H256::repeat_byte(2));
+		assert!(!RelayRandomness::is_ready(0));
+		assert_noop!(
+			RelayRandomness::fulfil_randomness(&1, 0),
+			Error::<Test>::RandomnessNotReady
+		);
+
+		finalize_block(H256::repeat_byte(3));
+		assert!(RelayRandomness::is_ready(0));
+		assert_noop!(
+			RelayRandomness::fulfil_randomness(&2, 0),
+			Error::<Test>::NotYourRequest
+		);
+
+		let randomness = RelayRandomness::fulfil_randomness(&1, 0).expect("block 3 was built");
+		assert_eq!(
+			last_event(),
+			MetaEvent::relay_randomness(Event::RandomnessFulfilled(0, randomness))
+		);
+		assert_noop!(
+			RelayRandomness::fulfil_randomness(&1, 0),
+			Error::<Test>::RequestNotFound
+		);
+
+		// Both requests for the block get the same randomness, which is then forgotten.
+		assert_eq!(RelayRandomness::fulfil_randomness(&2, 1), Ok(randomness));
+		assert!(!BlockRandomness::<Test>::contains_key(3));
+		assert!(!RequestedBlocks::<Test>::contains_key(3));
+	});
+}
+
+#[test]
+fn unrequested_blocks_are_not_recorded() {
+	new_test_ext().execute_with(|| {
+		finalize_block(H256::repeat_byte(1));
+		assert!(!BlockRandomness::<Test>::contains_key(1));
+	});
+}
+
+#[test]
+fn randomness_depends_on_the_vrf_output_of_the_relay_block_author() {
+	let randomness_with = |author_vrf_randomness| {
+		new_test_ext().execute_with(|| {
+			assert_ok!(RelayRandomness::request_randomness(1, 2));
+			finalize_block(H256::repeat_byte(1));
+			finalize_block(author_vrf_randomness);
+			RelayRandomness::fulfil_randomness(&1, 0).expect("block 2 was built")
+		})
+	};
+	assert_ne!(
+		randomness_with(H256::repeat_byte(2)),
+		randomness_with(H256::repeat_byte(3))
+	);
+}
+
+#[test]
+fn requests_wait_for_a_relay_parent_with_a_vrf_output() {
+	new_test_ext().execute_with(|| {
+		assert_ok!(RelayRandomness::request_randomness(1, 2));
+		assert_ok!(RelayRandomness::request_randomness(1, 3));
+		finalize_block(H256::repeat_byte(1));
+
+		// The relay parents of blocks 2 and 3 were authored in secondary slots without VRF.
+		build_block(EPOCH_RANDOMNESS, None);
+		build_block(EPOCH_RANDOMNESS, None);
+		assert!(!RelayRandomness::is_ready(0));
+		assert!(!RelayRandomness::is_ready(1));
+		assert_eq!(AwaitingRandomness::<Test>::get(), vec![2, 3]);
+
+		finalize_block(H256::repeat_byte(4));
+		assert!(AwaitingRandomness::<Test>::get().is_empty());
+		let first = RelayRandomness::fulfil_randomness(&1, 0).expect("block 4 has a VRF output");
+		let second = RelayRandomness::fulfil_randomness(&1, 1).expect("block 4 has a VRF output");
+		assert_ne!(first, second);
+	});
+}
+
+#[test]
+fn purged_requests_do_not_wait_for_randomness() {
+	new_test_ext().execute_with(|| {
+		assert_ok!(RelayRandomness::request_randomness(1, 2));
+		finalize_block(H256::repeat_byte(1));
+		while System::block_number() < 8 {
+			build_block(EPOCH_RANDOMNESS, None);
+		}
+		assert_ok!(RelayRandomness::purge_expired_request(Origin::signed(2), 0));
+
+		finalize_block(H256::repeat_byte(8));
+		assert!(AwaitingRandomness::<Test>::get().is_empty());
+		assert!(!BlockRandomness::<Test>::contains_key(2));
+	});
+}
+
+#[test]
+fn requests_reserve_a_deposit_until_fulfilled() {
+	new_test_ext().execute_with(|| {
+		assert_ok!(RelayRandomness::request_randomness(1, 2));
+		assert_eq!(Balances::reserved_balance(&1), 10);
+		assert_noop!(
+			RelayRandomness::request_randomness(3, 2),
+			Error::<Test>::CannotAffordDeposit
+		);
+
+		finalize_block(H256::repeat_byte(1));
+		finalize_block(H256::repeat_byte(2));
+		assert_ok!(RelayRandomness::fulfil_randomness(&1, 0));
+		assert_eq!(Balances::reserved_balance(&1), 0);
+		assert_eq!(Balances::free_balance(&1), 100);
+	});
+}
+
+#[test]
+fn expired_requests_cannot_be_fulfilled() {
+	new_test_ext().execute_with(|| {
+		assert_ok!(RelayRandomness::request_randomness(1, 2));
+		// Block 2 is built, and the request can be fulfilled until block 7
+		while System::block_number() < 8 {
+			finalize_block(H256::repeat_byte(System::block_number() as u8));
+		}
+
+		assert!(!RelayRandomness::is_ready(0));
+		assert_noop!(
+			RelayRandomness::fulfil_randomness(&1, 0),
+			Error::<Test>::RequestExpired
+		);
+	});
+}
+
+#[test]
+fn anyone_can_purge_expired_requests() {
+	new_test_ext().execute_with(|| {
+		assert_ok!(RelayRandomness::request_randomness(1, 2));
+		assert_noop!(
+			RelayRandomness::purge_expired_request(Origin::none(), 0),
+			DispatchError::BadOrigin
+		);
+		assert_noop!(
+			RelayRandomness::purge_expired_request(Origin::signed(2), 0),
+			Error::<Test>::RequestNotExpired
+		);
+
+		while System::block_number() < 8 {
+			finalize_block(H256::repeat_byte(System::block_number() as u8));
+		}
+		assert!(BlockRandomness::<Test>::contains_key(2));
+		assert_ok!(RelayRandomness::purge_expired_request(Origin::signed(2), 0));
+		assert_eq!(
+			last_event(),
+			MetaEvent::relay_randomness(Event::RequestPurged(0))
+		);
+		// The deposit goes back to the requester and the randomness of the block is forgotten
+		assert_eq!(Balances::reserved_balance(&1), 0);
+		assert_eq!(Balances::free_balance(&1), 100);
+		assert!(!Requests::<Test>::contains_key(0));
+		assert!(!BlockRandomness::<Test>::contains_key(2));
+		assert!(!RequestedBlocks::<Test>::contains_key(2));
+		assert_noop!(
+			RelayRandomness::purge_expired_request(Origin::signed(2), 0),
+			Error::<Test>::RequestNotFound
+		);
+	});
+}
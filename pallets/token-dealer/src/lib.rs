@@ -14,157 +14,726 @@
 // You should have received a copy of the GNU General Public License
 // along with Moonbeam.  If not, see <http://www.gnu.org/licenses/>.
 
+//! Token dealer: transfers of the relay chain token to and from the relay chain and sibling
+//! parachains over XCM.
+//!
+//! The relay chain token is held locally in `Config::Currency`, which must be a currency of its
+//! own with the relay chain's decimals rather than the native currency of the parachain: amounts
+//! are exchanged with other chains as they are. It is backed by the balances held in this
+//! parachain's sovereign accounts on the relay chain and on its siblings. Sending tokens burns them
+//! here and asks the destination to withdraw as much from our sovereign account there and deposit
+//! it to the beneficiary.
+//! Incoming messages are executed by `XcmExecutor`, which the runtime configures to move the
+//! currency between local accounts and the sovereign accounts of other chains.
+//!
+//! Messages that fail are rolled back and the relay chain tokens they carried are refunded to the
+//! sending chain. A sibling parachain gets the tokens it withdrew from its sovereign account here
+//! back in its sovereign account on the relay chain. XCM v0 messages do not say who sent them on
+//! the relay chain, so the tokens of failed reserve deposits from the relay chain are parked in
+//! the pallet's holding account for governance to release. Deposits to beneficiaries that have no
+//! local account are parked there too. Their owners claim them by signing the claiming account
+//! with the key of the 32-byte account they were sent to.
+//!
+//! Outgoing transfers are subject to governance controls: they can be paused, transfers to
+//! parachains are only sent to allowlisted parachains, and the amount each account and all
+//! accounts together can transfer out per `TransferPeriod` can be limited. These keep a bad
+//! channel or a compromised account from draining our sovereign accounts on other chains.
+//!
+//! This pallet is also the runtime's XCM transport: it handles the downward and HRMP messages
+//! passed on by `cumulus_pallet_parachain_system`, and implements `SendXcm` by routing messages
+//! to the relay chain (UMP) or to sibling parachains (HRMP).
+
 #![cfg_attr(not(feature = "std"), no_std)]
 
-use frame_support::{
-	decl_event, decl_module, dispatch::DispatchResult,
-	traits::{Currency, ExistenceRequirement, WithdrawReason},
-};
-use frame_system::ensure_signed;
-
-use codec::{Decode, Encode};
-use cumulus_primitives::{
-	relay_chain::DownwardMessage,
-	xcmp::{XCMPMessageHandler, XCMPMessageSender},
-	DownwardMessageHandler, ParaId, UpwardMessageOrigin, UpwardMessageSender,
-};
-use cumulus_upward_message::BalancesMessage;
-use polkadot_parachain::primitives::AccountIdConversion;
-
-#[derive(Encode, Decode)]
-pub enum XCMPMessage<XAccountId, XBalance> {
-	/// Transfer tokens to the given account from the Parachain account.
-	TransferToken(XAccountId, XBalance),
+use frame_support::pallet;
+use sp_std::{convert::TryFrom, marker::PhantomData};
+use xcm::v0::{Error as XcmError, Junction, MultiAsset, MultiLocation, NetworkId};
+use xcm_executor::traits::{LocationConversion, MatchesFungible, TransactAsset};
+
+pub use pallet::*;
+
+#[cfg(test)]
+mod mock;
+#[cfg(test)]
+mod tests;
+
+/// Converts between local 20-byte accounts and `AccountKey20` locations on `Network`.
+pub struct AccountKey20Aliases<Network, AccountId>(PhantomData<(Network, AccountId)>);
+
+impl<Network, AccountId> LocationConversion<AccountId> for AccountKey20Aliases<Network, AccountId>
+where
+	Network: frame_support::traits::Get<NetworkId>,
+	AccountId: From<[u8; 20]> + Into<[u8; 20]>,
+{
+	fn from_location(location: &MultiLocation) -> Option<AccountId> {
+		match location {
+			MultiLocation::X1(Junction::AccountKey20 { key, network })
+				if *network == NetworkId::Any || *network == Network::get() =>
+			{
+				Some((*key).into())
+			}
+			_ => None,
+		}
+	}
+
+	fn try_into_location(who: AccountId) -> Result<MultiLocation, AccountId> {
+		Ok(Junction::AccountKey20 {
+			network: Network::get(),
+			key: who.into(),
+		}
+		.into())
+	}
 }
 
-type BalanceOf<T> =
-	<<T as Trait>::Currency as Currency<<T as frame_system::Trait>::AccountId>>::Balance;
+/// Converts the relay chain location to the account `Account` gives.
+///
+/// The relay chain's sovereign account should be derived, for instance from a `ModuleId`, so that
+/// no one holds its key. The default account is a poor choice with 20-byte accounts: it is the
+/// zero address, which the EVM and its tooling treat specially.
+pub struct ParentIsAccount<AccountId, Account>(PhantomData<(AccountId, Account)>);
 
-type RelayAccountId = sp_runtime::AccountId32;
+impl<AccountId, Account> LocationConversion<AccountId> for ParentIsAccount<AccountId, Account>
+where
+	AccountId: PartialEq,
+	Account: frame_support::traits::Get<AccountId>,
+{
+	fn from_location(location: &MultiLocation) -> Option<AccountId> {
+		match location {
+			MultiLocation::X1(Junction::Parent) => Some(Account::get()),
+			_ => None,
+		}
+	}
 
-/// Configuration trait of this pallet.
-pub trait Trait: frame_system::Trait {
-	/// Event type used by the runtime.
-	type Event: From<Event<Self>> + Into<<Self as frame_system::Trait>::Event>;
+	fn try_into_location(who: AccountId) -> Result<MultiLocation, AccountId> {
+		if who == Account::get() {
+			Ok(Junction::Parent.into())
+		} else {
+			Err(who)
+		}
+	}
+}
 
-	/// The sender of upward messages.
-	type UpwardMessageSender: UpwardMessageSender<Self::UpwardMessage>;
+/// Maps the 32-byte accounts of the relay chain and other Substrate chains to local accounts.
+pub trait AccountId32Conversion<AccountId> {
+	/// The local account `id` maps to, if any.
+	fn from_account_id32(id: [u8; 32]) -> Option<AccountId>;
+	/// The 32-byte account that maps to `who`.
+	fn into_account_id32(who: AccountId) -> [u8; 32];
+}
 
-	/// The upward message type used by the Parachain runtime.
-	type UpwardMessage: codec::Codec + BalancesMessage<RelayAccountId, BalanceOf<Self>>;
+/// Maps 32-byte accounts to local 20-byte accounts by zero padding.
+///
+/// The local account `key` is addressed from other chains as the 32-byte account made of `key`
+/// followed by twelve zero bytes. Any other 32-byte account has no local account: those are
+/// public keys (or hashes of them) and an account derived from them would be controlled by no
+/// one.
+pub struct ZeroPaddedAccountKey20;
 
-	/// Currency of the runtime.
-	type Currency: Currency<Self::AccountId>;
+impl<AccountId> AccountId32Conversion<AccountId> for ZeroPaddedAccountKey20
+where
+	AccountId: From<[u8; 20]> + Into<[u8; 20]>,
+{
+	fn from_account_id32(id: [u8; 32]) -> Option<AccountId> {
+		if id[20..].iter().any(|byte| *byte != 0) {
+			return None;
+		}
+		let mut key = [0u8; 20];
+		key.copy_from_slice(&id[..20]);
+		Some(key.into())
+	}
 
-	/// The sender of XCMP messages.
-	type XCMPMessageSender: XCMPMessageSender<XCMPMessage<Self::AccountId, BalanceOf<Self>>>;
+	fn into_account_id32(who: AccountId) -> [u8; 32] {
+		let mut id = [0u8; 32];
+		id[..20].copy_from_slice(&who.into());
+		id
+	}
 }
 
-decl_event! {
-	pub enum Event<T> where
-		AccountId = <T as frame_system::Trait>::AccountId,
-		Balance = BalanceOf<T>
-	{
-		/// Transferred tokens to the account on the relay chain.
-		TransferredTokensToRelayChain(RelayAccountId, Balance),
-		/// Transferred tokens to the account on request from the relay chain.
-		TransferredTokensFromRelayChain(AccountId, Balance),
-		/// Transferred tokens to the account from the given parachain account.
-		TransferredTokensViaXCMP(ParaId, AccountId, Balance, DispatchResult),
+/// Converts between local accounts and `AccountId32` locations on `Network`, mapping the
+/// accounts with `Conversion`.
+pub struct MappedAccountId32<Network, AccountId, Conversion>(
+	PhantomData<(Network, AccountId, Conversion)>,
+);
+
+impl<Network, AccountId, Conversion> LocationConversion<AccountId>
+	for MappedAccountId32<Network, AccountId, Conversion>
+where
+	Network: frame_support::traits::Get<NetworkId>,
+	Conversion: AccountId32Conversion<AccountId>,
+{
+	fn from_location(location: &MultiLocation) -> Option<AccountId> {
+		match location {
+			MultiLocation::X1(Junction::AccountId32 { id, network })
+				if *network == NetworkId::Any || *network == Network::get() =>
+			{
+				Conversion::from_account_id32(*id)
+			}
+			_ => None,
+		}
+	}
+
+	fn try_into_location(who: AccountId) -> Result<MultiLocation, AccountId> {
+		Ok(Junction::AccountId32 {
+			network: Network::get(),
+			id: Conversion::into_account_id32(who),
+		}
+		.into())
 	}
 }
 
-decl_module! {
-	pub struct Module<T: Trait> for enum Call where origin: T::Origin, system = frame_system {
-		/// Transfer `amount` of tokens on the relay chain from the Parachain account to
-		/// the given `dest` account.
-		#[weight = 10]
-		fn transfer_tokens_to_relay_chain(origin, dest: RelayAccountId, amount: BalanceOf<T>) {
-			let who = ensure_signed(origin)?;
+/// Asset transactor that parks the deposits `Transactor` fails to make.
+///
+/// Fungible assets matched by `Matcher` that cannot be deposited, typically because the
+/// beneficiary has no local account, are credited to the token dealer's holding account and
+/// recorded as claimable by the beneficiary.
+pub struct ParkFailedDeposits<Transactor, Matcher, T>(PhantomData<(Transactor, Matcher, T)>);
 
-			let _ = T::Currency::withdraw(
-				&who,
-				amount,
-				WithdrawReason::Transfer.into(),
-				ExistenceRequirement::AllowDeath,
-			)?;
+impl<Transactor, Matcher, T> TransactAsset for ParkFailedDeposits<Transactor, Matcher, T>
+where
+	Transactor: TransactAsset,
+	Matcher: MatchesFungible<BalanceOf<T>>,
+	T: Config,
+{
+	fn deposit_asset(what: &MultiAsset, who: &MultiLocation) -> Result<(), XcmError> {
+		Transactor::deposit_asset(what, who).or_else(|error| {
+			let amount = Matcher::matches_fungible(what).ok_or(error)?;
+			Pallet::<T>::park(who.clone(), amount);
+			Ok(())
+		})
+	}
+
+	fn withdraw_asset(what: &MultiAsset, who: &MultiLocation) -> Result<MultiAsset, XcmError> {
+		Transactor::withdraw_asset(what, who)
+	}
+}
+
+#[pallet]
+pub mod pallet {
+	use super::*;
+	use cumulus_primitives_core::{
+		DownwardMessageHandler, HrmpMessageHandler, HrmpMessageSender, InboundDownwardMessage,
+		InboundHrmpMessage, OutboundHrmpMessage, ParaId, UpwardMessageSender,
+	};
+	use frame_support::{
+		pallet_prelude::*,
+		storage::with_transaction,
+		traits::{Currency, ExistenceRequirement, Imbalance, WithdrawReasons},
+		transactional,
+	};
+	use frame_system::pallet_prelude::*;
+	use parity_scale_codec::{Decode, Encode};
+	use sp_runtime::{
+		traits::{AccountIdConversion, CheckedDiv, Saturating, Verify, Zero},
+		AccountId32, ModuleId, MultiSignature, SaturatedConversion, TransactionOutcome,
+	};
+	use sp_std::{prelude::*, vec};
+	use xcm::{
+		v0::{ExecuteXcm, Order, SendXcm, Xcm},
+		VersionedXcm,
+	};
+
+	/// What the owner of a 32-byte account signs, followed by the genesis hash and the claiming
+	/// account, to claim the deposits parked for it.
+	pub const CLAIM_PREFIX: &[u8] = b"token-dealer/claim";
+
+	pub type BalanceOf<T> =
+		<<T as Config>::Currency as Currency<<T as frame_system::Config>::AccountId>>::Balance;
+
+	/// The Token Dealer pallet
+	#[pallet::pallet]
+	pub struct Pallet<T>(PhantomData<T>);
+
+	/// Configuration trait of this pallet.
+	#[pallet::config]
+	pub trait Config: frame_system::Config {
+		/// The overarching event type
+		type Event: From<Event<Self>> + IsType<<Self as frame_system::Config>::Event>;
+		/// The currency that is transferred
+		type Currency: Currency<Self::AccountId>;
+		/// Sends messages to the relay chain
+		type UpwardMessageSender: UpwardMessageSender;
+		/// Sends messages to sibling parachains
+		type HrmpMessageSender: HrmpMessageSender;
+		/// Executes the messages received from the relay chain and sibling parachains
+		type XcmExecutor: ExecuteXcm;
+		/// Converts between local accounts and their locations on other chains
+		type AccountIdConverter: LocationConversion<Self::AccountId>;
+		/// The network id of the relay chain, used for relay chain beneficiaries
+		type RelayNetwork: Get<NetworkId>;
+		/// The id of the account holding the deposits that could not be made
+		#[pallet::constant]
+		type ModuleId: Get<ModuleId>;
+		/// The origin that manages the transfer controls and releases parked deposits
+		type GovernanceOrigin: EnsureOrigin<Self::Origin>;
+		/// The length of the periods the transfer limits apply to
+		#[pallet::constant]
+		type TransferPeriod: Get<Self::BlockNumber>;
+	}
+
+	#[pallet::error]
+	pub enum Error<T> {
+		/// The beneficiary cannot be expressed as an XCM location
+		BadLocation,
+		/// The message could not be handed over to the relay chain or the sibling parachain
+		SendFailure,
+		/// The signature does not prove that the caller owns this beneficiary
+		NotBeneficiary,
+		/// Nothing is held for this beneficiary
+		NothingToClaim,
+		/// Outgoing transfers are paused
+		TransfersPaused,
+		/// Transfers to this parachain are not allowed
+		ParachainNotAllowed,
+		/// The account has reached its transfer limit for the current period
+		AccountLimitExceeded,
+		/// All accounts together have reached the transfer limit for the current period
+		GlobalLimitExceeded,
+	}
 
-			let msg = <T as Trait>::UpwardMessage::transfer(dest.clone(), amount.clone());
-			<T as Trait>::UpwardMessageSender::send_upward_message(&msg, UpwardMessageOrigin::Signed)
-				.expect("Should not fail; qed");
+	#[pallet::event]
+	#[pallet::generate_deposit(pub(crate) fn deposit_event)]
+	pub enum Event<T: Config> {
+		/// Account, Relay chain beneficiary, Amount
+		TransferredToRelayChain(T::AccountId, [u8; 32], BalanceOf<T>),
+		/// Account, Parachain, Beneficiary, Amount
+		TransferredToParachain(T::AccountId, ParaId, T::AccountId, BalanceOf<T>),
+		/// A message from this location was executed
+		Success(MultiLocation),
+		/// A message from this location failed to execute
+		Fail(MultiLocation, XcmError),
+		/// A message from this location is of an unsupported XCM version
+		BadVersion(MultiLocation),
+		/// A message from this location could not be decoded
+		BadFormat(MultiLocation),
+		/// A deposit to this beneficiary failed and was parked in the holding account
+		Parked(MultiLocation, BalanceOf<T>),
+		/// The relay chain tokens a failed message from this location carried were sent back
+		Refunded(MultiLocation, BalanceOf<T>),
+		/// Beneficiary, Account, Amount
+		Claimed(MultiLocation, T::AccountId, BalanceOf<T>),
+		/// Transfers to this parachain are now allowed
+		ParachainAllowed(ParaId),
+		/// Transfers to this parachain are no longer allowed
+		ParachainDisallowed(ParaId),
+		/// Outgoing transfers have been paused
+		TransfersPaused,
+		/// Outgoing transfers have been resumed
+		TransfersResumed,
+		/// Per account limit, Global limit
+		TransferLimitsUpdated(Option<BalanceOf<T>>, Option<BalanceOf<T>>),
+	}
+
+	/// The amounts parked in the holding account, by beneficiary.
+	#[pallet::storage]
+	#[pallet::getter(fn claimable)]
+	pub type Claimable<T: Config> =
+		StorageMap<_, Blake2_128Concat, MultiLocation, BalanceOf<T>, ValueQuery>;
+
+	/// The parachains transfers can be sent to.
+	#[pallet::storage]
+	#[pallet::getter(fn is_allowed)]
+	pub type AllowedParachains<T: Config> = StorageMap<_, Twox64Concat, ParaId, bool, ValueQuery>;
+
+	/// Whether outgoing transfers are paused.
+	#[pallet::storage]
+	#[pallet::getter(fn paused)]
+	pub type Paused<T: Config> = StorageValue<_, bool, ValueQuery>;
+
+	/// The most an account can transfer out per period. Unlimited if unset.
+	#[pallet::storage]
+	#[pallet::getter(fn account_limit)]
+	pub type AccountLimit<T: Config> = StorageValue<_, BalanceOf<T>, OptionQuery>;
+
+	/// The most all accounts together can transfer out per period. Unlimited if unset.
+	#[pallet::storage]
+	#[pallet::getter(fn global_limit)]
+	pub type GlobalLimit<T: Config> = StorageValue<_, BalanceOf<T>, OptionQuery>;
+
+	/// The period of the last transfer of each account and how much it transferred out in it.
+	#[pallet::storage]
+	pub type AccountTransferred<T: Config> =
+		StorageMap<_, Blake2_128Concat, T::AccountId, (T::BlockNumber, BalanceOf<T>), ValueQuery>;
+
+	/// The period of the last transfer and how much was transferred out in it.
+	#[pallet::storage]
+	pub type GlobalTransferred<T: Config> =
+		StorageValue<_, (T::BlockNumber, BalanceOf<T>), ValueQuery>;
+
+	// No hooks
+	#[pallet::hooks]
+	impl<T: Config> Hooks<BlockNumberFor<T>> for Pallet<T> {}
+
+	#[pallet::call]
+	impl<T: Config> Pallet<T> {
+		/// Transfer `amount` to the `dest` account on the relay chain. The tokens are paid out of
+		/// this parachain's sovereign account there.
+		#[pallet::weight(10)]
+		#[transactional]
+		pub fn transfer_to_relay_chain(
+			origin: OriginFor<T>,
+			dest: [u8; 32],
+			amount: BalanceOf<T>,
+		) -> DispatchResultWithPostInfo {
+			let who = ensure_signed(origin)?;
+			let beneficiary = Junction::AccountId32 {
+				network: T::RelayNetwork::get(),
+				id: dest,
+			}
+			.into();
 
-			Self::deposit_event(Event::<T>::TransferredTokensToRelayChain(dest, amount));
+			Self::ensure_can_transfer(&who, amount)?;
+			Self::withdraw(&who, amount)?;
+			// The relay chain token is the relay chain's own native asset.
+			Self::send_xcm(
+				Junction::Parent.into(),
+				Self::transfer_message(MultiLocation::Null, amount, beneficiary),
+			)
+			.map_err(|_| Error::<T>::SendFailure)?;
+
+			Self::deposit_event(Event::TransferredToRelayChain(who, dest, amount));
+			Ok(Default::default())
 		}
 
-		/// Transfer `amount` of tokens to another parachain.
-		#[weight = 10]
-		fn transfer_tokens_to_parachain_chain(
-			origin,
-			para_id: u32,
+		/// Transfer `amount` to the `dest` account on the sibling parachain `para_id`. The tokens
+		/// are paid out of this parachain's sovereign account there.
+		#[pallet::weight(10)]
+		#[transactional]
+		pub fn transfer_to_parachain(
+			origin: OriginFor<T>,
+			para_id: ParaId,
 			dest: T::AccountId,
 			amount: BalanceOf<T>,
-		) {
-			//TODO we don't make sure that the parachain has some tokens on the other parachain.
+		) -> DispatchResultWithPostInfo {
 			let who = ensure_signed(origin)?;
+			let beneficiary = T::AccountIdConverter::try_into_location(dest.clone())
+				.map_err(|_| Error::<T>::BadLocation)?;
+
+			ensure!(
+				AllowedParachains::<T>::get(para_id),
+				Error::<T>::ParachainNotAllowed
+			);
+			Self::ensure_can_transfer(&who, amount)?;
+			Self::withdraw(&who, amount)?;
+			Self::send_xcm(
+				MultiLocation::X2(Junction::Parent, Junction::Parachain { id: para_id.into() }),
+				Self::transfer_message(Junction::Parent.into(), amount, beneficiary),
+			)
+			.map_err(|_| Error::<T>::SendFailure)?;
+
+			Self::deposit_event(Event::TransferredToParachain(who, para_id, dest, amount));
+			Ok(Default::default())
+		}
+
+		/// Claim what is parked for `beneficiary`, a 32-byte account, with its `signature` of
+		/// `claim_payload` for the caller.
+		#[pallet::weight(10)]
+		pub fn claim(
+			origin: OriginFor<T>,
+			beneficiary: MultiLocation,
+			signature: MultiSignature,
+		) -> DispatchResultWithPostInfo {
+			let who = ensure_signed(origin)?;
+			let id = match beneficiary {
+				MultiLocation::X1(Junction::AccountId32 { id, .. }) => id,
+				_ => return Err(Error::<T>::NotBeneficiary.into()),
+			};
+			ensure!(
+				signature.verify(&Self::claim_payload(&who)[..], &AccountId32::from(id)),
+				Error::<T>::NotBeneficiary
+			);
+			Self::pay_out(beneficiary, who)?;
+			Ok(Default::default())
+		}
+
+		/// Pay what is parked for `beneficiary` to `dest`. Used by governance for beneficiaries
+		/// that have no local account.
+		#[pallet::weight(10)]
+		pub fn release(
+			origin: OriginFor<T>,
+			beneficiary: MultiLocation,
+			dest: T::AccountId,
+		) -> DispatchResultWithPostInfo {
+			T::GovernanceOrigin::ensure_origin(origin)?;
+			Self::pay_out(beneficiary, dest)?;
+			Ok(Default::default())
+		}
+
+		/// Allow transfers to `para_id`.
+		#[pallet::weight(10)]
+		pub fn allow_parachain(
+			origin: OriginFor<T>,
+			para_id: ParaId,
+		) -> DispatchResultWithPostInfo {
+			T::GovernanceOrigin::ensure_origin(origin)?;
+			AllowedParachains::<T>::insert(para_id, true);
+			Self::deposit_event(Event::ParachainAllowed(para_id));
+			Ok(Default::default())
+		}
+
+		/// Stop allowing transfers to `para_id`.
+		#[pallet::weight(10)]
+		pub fn disallow_parachain(
+			origin: OriginFor<T>,
+			para_id: ParaId,
+		) -> DispatchResultWithPostInfo {
+			T::GovernanceOrigin::ensure_origin(origin)?;
+			AllowedParachains::<T>::remove(para_id);
+			Self::deposit_event(Event::ParachainDisallowed(para_id));
+			Ok(Default::default())
+		}
+
+		/// Pause all outgoing transfers.
+		#[pallet::weight(10)]
+		pub fn pause(origin: OriginFor<T>) -> DispatchResultWithPostInfo {
+			T::GovernanceOrigin::ensure_origin(origin)?;
+			Paused::<T>::put(true);
+			Self::deposit_event(Event::TransfersPaused);
+			Ok(Default::default())
+		}
+
+		/// Resume outgoing transfers.
+		#[pallet::weight(10)]
+		pub fn resume(origin: OriginFor<T>) -> DispatchResultWithPostInfo {
+			T::GovernanceOrigin::ensure_origin(origin)?;
+			Paused::<T>::put(false);
+			Self::deposit_event(Event::TransfersResumed);
+			Ok(Default::default())
+		}
+
+		/// Set the most an account and all accounts together can transfer out per period. `None`
+		/// removes the limit.
+		#[pallet::weight(10)]
+		pub fn set_transfer_limits(
+			origin: OriginFor<T>,
+			per_account: Option<BalanceOf<T>>,
+			global: Option<BalanceOf<T>>,
+		) -> DispatchResultWithPostInfo {
+			T::GovernanceOrigin::ensure_origin(origin)?;
+			AccountLimit::<T>::set(per_account);
+			GlobalLimit::<T>::set(global);
+			Self::deposit_event(Event::TransferLimitsUpdated(per_account, global));
+			Ok(Default::default())
+		}
+	}
+
+	impl<T: Config> Pallet<T> {
+		/// The account holding the deposits that could not be made.
+		pub fn holding_account() -> T::AccountId {
+			T::ModuleId::get().into_account()
+		}
+
+		/// Credit `amount` to the holding account on behalf of `beneficiary`. Amounts too small to
+		/// create the holding account are lost, like any other dust.
+		pub(crate) fn park(beneficiary: MultiLocation, amount: BalanceOf<T>) {
+			let parked = T::Currency::deposit_creating(&Self::holding_account(), amount).peek();
+			if parked.is_zero() {
+				return;
+			}
+			Claimable::<T>::mutate(&beneficiary, |held| *held = held.saturating_add(parked));
+			Self::deposit_event(Event::Parked(beneficiary, parked));
+		}
+
+		/// What the owner of a 32-byte account signs to have the deposits parked for it paid to
+		/// `who`.
+		pub fn claim_payload(who: &T::AccountId) -> Vec<u8> {
+			let genesis_hash = frame_system::Pallet::<T>::block_hash(T::BlockNumber::zero());
+			(CLAIM_PREFIX, genesis_hash, who).encode()
+		}
 
-			let _ = T::Currency::withdraw(
-				&who,
+		fn pay_out(beneficiary: MultiLocation, dest: T::AccountId) -> DispatchResult {
+			let amount = Claimable::<T>::get(&beneficiary);
+			ensure!(!amount.is_zero(), Error::<T>::NothingToClaim);
+			T::Currency::transfer(
+				&Self::holding_account(),
+				&dest,
 				amount,
-				WithdrawReason::Transfer.into(),
 				ExistenceRequirement::AllowDeath,
 			)?;
-
-			T::XCMPMessageSender::send_xcmp_message(
-				para_id.into(),
-				&XCMPMessage::TransferToken(dest, amount),
-			).expect("Should not fail; qed");
+			Claimable::<T>::remove(&beneficiary);
+			Self::deposit_event(Event::Claimed(beneficiary, dest, amount));
+			Ok(())
 		}
 
-		fn deposit_event() = default;
-	}
-}
+		/// Check that `who` may transfer `amount` out now, and count it against the limits.
+		fn ensure_can_transfer(who: &T::AccountId, amount: BalanceOf<T>) -> DispatchResult {
+			ensure!(!Paused::<T>::get(), Error::<T>::TransfersPaused);
 
-/// This is a hack to convert from one generic type to another where we are sure that both are the
-/// same type/use the same encoding.
-fn convert_hack<O: Decode>(input: &impl Encode) -> O {
-	input.using_encoded(|e| Decode::decode(&mut &e[..]).expect("Must be compatible; qed"))
-}
+			let period = frame_system::Pallet::<T>::block_number()
+				.checked_div(&T::TransferPeriod::get())
+				.unwrap_or_else(Zero::zero);
+			// What was transferred in an earlier period no longer counts.
+			let in_period = |(last, transferred): (T::BlockNumber, BalanceOf<T>)| {
+				if last == period {
+					transferred
+				} else {
+					Zero::zero()
+				}
+			};
+
+			let account_total = in_period(AccountTransferred::<T>::get(who)).saturating_add(amount);
+			if let Some(limit) = AccountLimit::<T>::get() {
+				ensure!(account_total <= limit, Error::<T>::AccountLimitExceeded);
+			}
+			let global_total = in_period(GlobalTransferred::<T>::get()).saturating_add(amount);
+			if let Some(limit) = GlobalLimit::<T>::get() {
+				ensure!(global_total <= limit, Error::<T>::GlobalLimitExceeded);
+			}
 
-impl<T: Trait> DownwardMessageHandler for Module<T> {
-	fn handle_downward_message(msg: &DownwardMessage) {
-		match msg {
-			DownwardMessage::TransferInto(dest, amount, _) => {
-				let dest = convert_hack(&dest);
-				let amount: BalanceOf<T> = convert_hack(amount);
+			AccountTransferred::<T>::insert(who, (period, account_total));
+			GlobalTransferred::<T>::put((period, global_total));
+			Ok(())
+		}
 
-				let _ = T::Currency::deposit_creating(&dest, amount.clone());
+		fn withdraw(who: &T::AccountId, amount: BalanceOf<T>) -> DispatchResult {
+			T::Currency::withdraw(
+				who,
+				amount,
+				WithdrawReasons::TRANSFER,
+				ExistenceRequirement::AllowDeath,
+			)
+			.map(|_| ())
+		}
 
-				Self::deposit_event(Event::<T>::TransferredTokensFromRelayChain(dest, amount));
+		/// The message asking the destination to pay `amount` of the `asset` it holds for us to
+		/// `beneficiary`.
+		pub(crate) fn transfer_message(
+			asset: MultiLocation,
+			amount: BalanceOf<T>,
+			beneficiary: MultiLocation,
+		) -> Xcm {
+			Xcm::WithdrawAsset {
+				assets: vec![MultiAsset::ConcreteFungible {
+					id: asset,
+					amount: amount.saturated_into(),
+				}],
+				effects: vec![Order::DepositAsset {
+					assets: vec![MultiAsset::All],
+					dest: beneficiary,
+				}],
 			}
-			_ => {}
+		}
+
+		/// Decode and execute a message received from `origin`. The effects of messages that fail
+		/// are reverted and the relay chain tokens they carried are refunded, see `refund`.
+		fn execute(origin: MultiLocation, data: &[u8]) {
+			let event = match VersionedXcm::decode(&mut &data[..]).map(Xcm::try_from) {
+				Ok(Ok(xcm)) => {
+					let result = with_transaction(|| {
+						match T::XcmExecutor::execute_xcm(origin.clone(), xcm.clone()) {
+							Ok(()) => TransactionOutcome::Commit(Ok(())),
+							Err(e) => TransactionOutcome::Rollback(Err(e)),
+						}
+					});
+					match result {
+						Ok(()) => Event::Success(origin),
+						Err(e) => {
+							Self::refund(&origin, &xcm);
+							Event::Fail(origin, e)
+						}
+					}
+				}
+				Ok(Err(..)) => Event::BadVersion(origin),
+				Err(..) => Event::BadFormat(origin),
+			};
+			Self::deposit_event(event);
+		}
+
+		/// Refund the relay chain tokens carried by `xcm`, a message from `origin` that failed.
+		///
+		/// A sibling parachain's withdrawal from its sovereign account here is paid to its
+		/// sovereign account on the relay chain out of ours. A reserve deposit from the relay chain
+		/// was already paid to our sovereign account there by an unknown sender, so it is parked
+		/// for the relay chain location. Tokens that cannot be refunded, for instance because the
+		/// sibling does not hold as much here, stay where they are.
+		fn refund(origin: &MultiLocation, xcm: &Xcm) {
+			match (origin, xcm) {
+				(
+					MultiLocation::X2(Junction::Parent, Junction::Parachain { id }),
+					Xcm::WithdrawAsset { assets, .. },
+				) => {
+					let amount = Self::relay_tokens(assets);
+					let sovereign = match T::AccountIdConverter::from_location(origin) {
+						Some(sovereign) if !amount.is_zero() => sovereign,
+						_ => return,
+					};
+					let refunded = with_transaction(|| {
+						let sent = Self::withdraw(&sovereign, amount).is_ok()
+							&& Self::send_xcm(
+								Junction::Parent.into(),
+								Self::transfer_message(
+									MultiLocation::Null,
+									amount,
+									Junction::Parachain { id: *id }.into(),
+								),
+							)
+							.is_ok();
+						if sent {
+							TransactionOutcome::Commit(true)
+						} else {
+							TransactionOutcome::Rollback(false)
+						}
+					});
+					if refunded {
+						Self::deposit_event(Event::Refunded(origin.clone(), amount));
+					}
+				}
+				(MultiLocation::X1(Junction::Parent), Xcm::ReserveAssetDeposit { assets, .. }) => {
+					let amount = Self::relay_tokens(assets);
+					if !amount.is_zero() {
+						Self::park(origin.clone(), amount);
+					}
+				}
+				_ => {}
+			}
+		}
+
+		/// The amount of relay chain tokens among `assets`.
+		fn relay_tokens(assets: &[MultiAsset]) -> BalanceOf<T> {
+			assets
+				.iter()
+				.filter_map(|asset| match asset {
+					MultiAsset::ConcreteFungible {
+						id: MultiLocation::X1(Junction::Parent),
+						amount,
+					} => Some(*amount),
+					_ => None,
+				})
+				.fold(0u128, |total, amount| total.saturating_add(amount))
+				.saturated_into()
+		}
+	}
+
+	impl<T: Config> DownwardMessageHandler for Pallet<T> {
+		fn handle_downward_message(msg: InboundDownwardMessage) {
+			Self::execute(Junction::Parent.into(), &msg.msg);
+		}
+	}
+
+	impl<T: Config> HrmpMessageHandler for Pallet<T> {
+		fn handle_hrmp_message(sender: ParaId, msg: InboundHrmpMessage) {
+			let origin =
+				MultiLocation::X2(Junction::Parent, Junction::Parachain { id: sender.into() });
+			Self::execute(origin, &msg.data);
 		}
 	}
-}
 
-impl<T: Trait> XCMPMessageHandler<XCMPMessage<T::AccountId, BalanceOf<T>>> for Module<T> {
-	fn handle_xcmp_message(src: ParaId, msg: &XCMPMessage<T::AccountId, BalanceOf<T>>) {
-		match msg {
-			XCMPMessage::TransferToken(dest, amount) => {
-				let para_account = src.clone().into_account();
-
-				let res = T::Currency::transfer(
-					&para_account,
-					dest,
-					amount.clone(),
-					ExistenceRequirement::AllowDeath,
-				);
-
-				Self::deposit_event(Event::<T>::TransferredTokensViaXCMP(
-					src,
-					dest.clone(),
-					amount.clone(),
-					res,
-				));
+	/// Routes messages to the relay chain and to sibling parachains.
+	impl<T: Config> SendXcm for Pallet<T> {
+		fn send_xcm(dest: MultiLocation, msg: Xcm) -> Result<(), XcmError> {
+			let data = VersionedXcm::from(msg).encode();
+			match dest {
+				MultiLocation::X1(Junction::Parent) => {
+					T::UpwardMessageSender::send_upward_message(data)
+						.map_err(|_| XcmError::Undefined)
+				}
+				MultiLocation::X2(Junction::Parent, Junction::Parachain { id }) => {
+					T::HrmpMessageSender::send_hrmp_message(OutboundHrmpMessage {
+						recipient: id.into(),
+						data,
+					})
+					.map_err(|_| XcmError::Undefined)
+				}
+				_ => Err(XcmError::UnhandledXcmMessage),
 			}
 		}
 	}
@@ -0,0 +1,273 @@
+// Copyright 2019-2021 PureStake Inc.
+// This file is part of Moonbeam.
+
+// Moonbeam is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Moonbeam is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Moonbeam.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Test utilities, including a small XCM simulator.
+//!
+//! Every parachain of the simulated network runs this mock runtime in its own externalities.
+//! Messages sent upward are kept for inspection, and HRMP messages are delivered on demand to
+//! the recipient parachain.
+use super::*;
+use crate as token_dealer;
+use cumulus_primitives_core::{
+	DownwardMessageHandler, HrmpMessageHandler, HrmpMessageSender, InboundDownwardMessage,
+	InboundHrmpMessage, OutboundHrmpMessage, ParaId, UpwardMessage, UpwardMessageSender,
+};
+use frame_support::{construct_runtime, parameter_types};
+use parity_scale_codec::{Decode, Encode};
+use polkadot_parachain::primitives::{AccountIdConversion, Sibling};
+use sp_core::{H160, H256};
+use sp_runtime::{
+	testing::Header,
+	traits::{BlakeTwo256, IdentityLookup},
+	ModuleId,
+};
+use std::cell::{Cell, RefCell};
+use xcm::{
+	v0::{Junction, MultiLocation, NetworkId, Xcm},
+	VersionedXcm,
+};
+use xcm_builder::{
+	CurrencyAdapter, IsConcrete, LocationInverter, NativeAsset, SiblingParachainConvertsVia,
+	SovereignSignedViaLocation,
+};
+
+pub type AccountId = H160;
+pub type Balance = u128;
+pub type BlockNumber = u64;
+
+pub const ALICE: AccountId = H160::repeat_byte(0xAA);
+pub const BOB: AccountId = H160::repeat_byte(0xBB);
+
+type UncheckedExtrinsic = frame_system::mocking::MockUncheckedExtrinsic<Test>;
+type Block = frame_system::mocking::MockBlock<Test>;
+
+// Configure a mock runtime to test the pallet.
+construct_runtime!(
+	pub enum Test where
+		Block = Block,
+		NodeBlock = Block,
+		UncheckedExtrinsic = UncheckedExtrinsic,
+	{
+		System: frame_system::{Pallet, Call, Config, Storage, Event<T>},
+		Balances: pallet_balances::{Pallet, Call, Storage, Config<T>, Event<T>},
+		TokenDealer: token_dealer::{Pallet, Call, Storage, Event<T>},
+	}
+);
+
+parameter_types! {
+	pub const BlockHashCount: u64 = 250;
+	pub const SS58Prefix: u8 = 42;
+}
+impl frame_system::Config for Test {
+	type BaseCallFilter = ();
+	type DbWeight = ();
+	type Origin = Origin;
+	type Index = u64;
+	type BlockNumber = BlockNumber;
+	type Call = Call;
+	type Hash = H256;
+	type Hashing = BlakeTwo256;
+	type AccountId = AccountId;
+	type Lookup = IdentityLookup<Self::AccountId>;
+	type Header = Header;
+	type Event = Event;
+	type BlockHashCount = BlockHashCount;
+	type Version = ();
+	type PalletInfo = PalletInfo;
+	type AccountData = pallet_balances::AccountData<Balance>;
+	type OnNewAccount = ();
+	type OnKilledAccount = ();
+	type SystemWeightInfo = ();
+	type BlockWeights = ();
+	type BlockLength = ();
+	type SS58Prefix = SS58Prefix;
+}
+parameter_types! {
+	pub const ExistentialDeposit: u128 = 1;
+}
+impl pallet_balances::Config for Test {
+	type MaxLocks = ();
+	type Balance = Balance;
+	type Event = Event;
+	type DustRemoval = ();
+	type ExistentialDeposit = ExistentialDeposit;
+	type AccountStore = System;
+	type WeightInfo = ();
+}
+
+thread_local! {
+	/// The parachain whose externalities are currently being executed.
+	static CURRENT_PARA: Cell<u32> = Cell::new(0);
+	/// Messages sent to the relay chain, with the parachain that sent them.
+	static UPWARD_MESSAGES: RefCell<Vec<(ParaId, UpwardMessage)>> = RefCell::new(vec![]);
+	/// Messages sent to sibling parachains and not delivered yet, with their sender.
+	static HRMP_MESSAGES: RefCell<Vec<(ParaId, OutboundHrmpMessage)>> = RefCell::new(vec![]);
+}
+
+fn current_para() -> ParaId {
+	CURRENT_PARA.with(|para| para.get()).into()
+}
+
+pub struct MockUpwardMessageSender;
+impl UpwardMessageSender for MockUpwardMessageSender {
+	fn send_upward_message(msg: UpwardMessage) -> Result<(), ()> {
+		UPWARD_MESSAGES.with(|messages| messages.borrow_mut().push((current_para(), msg)));
+		Ok(())
+	}
+}
+
+pub struct MockHrmpMessageSender;
+impl HrmpMessageSender for MockHrmpMessageSender {
+	fn send_hrmp_message(msg: OutboundHrmpMessage) -> Result<(), OutboundHrmpMessage> {
+		HRMP_MESSAGES.with(|messages| messages.borrow_mut().push((current_para(), msg)));
+		Ok(())
+	}
+}
+
+parameter_types! {
+	pub const RelayNetwork: NetworkId = NetworkId::Polkadot;
+	pub RelayLocation: MultiLocation = MultiLocation::X1(Junction::Parent);
+	pub Ancestry: MultiLocation = Junction::Parachain { id: current_para().into() }.into();
+	pub const TokenDealerModuleId: ModuleId = ModuleId(*b"tkdealer");
+	pub const TransferPeriod: BlockNumber = 10;
+	pub RelayChainAccount: AccountId = ModuleId(*b"py/relay").into_account();
+}
+
+/// The relay chain's sovereign account is derived from a `ModuleId`, the sibling parachains' are
+/// derived from their ids, and local accounts are `AccountKey20` junctions or zero padded
+/// `AccountId32` junctions.
+pub type LocationToAccountId = (
+	ParentIsAccount<AccountId, RelayChainAccount>,
+	SiblingParachainConvertsVia<Sibling, AccountId>,
+	AccountKey20Aliases<RelayNetwork, AccountId>,
+	MappedAccountId32<RelayNetwork, AccountId, ZeroPaddedAccountKey20>,
+);
+
+pub struct XcmConfig;
+impl xcm_executor::Config for XcmConfig {
+	type Call = Call;
+	type XcmSender = TokenDealer;
+	type AssetTransactor = ParkFailedDeposits<
+		CurrencyAdapter<Balances, IsConcrete<RelayLocation>, LocationToAccountId, AccountId>,
+		IsConcrete<RelayLocation>,
+		Test,
+	>;
+	type OriginConverter = SovereignSignedViaLocation<LocationToAccountId, Origin>;
+	type IsReserve = NativeAsset;
+	type IsTeleporter = ();
+	type LocationInverter = LocationInverter<Ancestry>;
+}
+
+impl Config for Test {
+	type Event = Event;
+	type Currency = Balances;
+	type UpwardMessageSender = MockUpwardMessageSender;
+	type HrmpMessageSender = MockHrmpMessageSender;
+	type XcmExecutor = xcm_executor::XcmExecutor<XcmConfig>;
+	type AccountIdConverter = LocationToAccountId;
+	type RelayNetwork = RelayNetwork;
+	type ModuleId = TokenDealerModuleId;
+	type GovernanceOrigin = frame_system::EnsureRoot<AccountId>;
+	type TransferPeriod = TransferPeriod;
+}
+
+/// The account of a sibling parachain on the other parachains.
+pub(crate) fn sibling_account(id: u32) -> AccountId {
+	Sibling::from(id).into_account()
+}
+
+/// A network of parachains running the mock runtime.
+pub(crate) struct MockNet {
+	paras: Vec<(u32, sp_io::TestExternalities)>,
+}
+
+impl MockNet {
+	/// A network of parachains with the given ids and endowed accounts.
+	pub(crate) fn new(paras: Vec<(u32, Vec<(AccountId, Balance)>)>) -> Self {
+		let paras = paras
+			.into_iter()
+			.map(|(id, balances)| {
+				let mut t = frame_system::GenesisConfig::default()
+					.build_storage::<Test>()
+					.expect("Frame system builds valid default genesis config");
+
+				pallet_balances::GenesisConfig::<Test> { balances }
+					.assimilate_storage(&mut t)
+					.expect("Pallet balances storage can be assimilated");
+
+				let mut ext = sp_io::TestExternalities::new(t);
+				ext.execute_with(|| System::set_block_number(1));
+				(id, ext)
+			})
+			.collect();
+		MockNet { paras }
+	}
+
+	/// Execute `f` on the parachain with the given id.
+	pub(crate) fn execute_on<R>(&mut self, id: u32, f: impl FnOnce() -> R) -> R {
+		let (_, ext) = self
+			.paras
+			.iter_mut()
+			.find(|(para, _)| *para == id)
+			.expect("Parachain is part of the network");
+		CURRENT_PARA.with(|para| para.set(id));
+		ext.execute_with(f)
+	}
+
+	/// Send a message from the relay chain to the parachain with the given id.
+	pub(crate) fn send_downward(&mut self, id: u32, xcm: Xcm) {
+		self.execute_on(id, || {
+			TokenDealer::handle_downward_message(InboundDownwardMessage {
+				sent_at: 1,
+				msg: VersionedXcm::from(xcm).encode(),
+			})
+		});
+	}
+
+	/// Deliver all the HRMP messages sent so far.
+	pub(crate) fn process_hrmp_messages(&mut self) {
+		let messages = HRMP_MESSAGES.with(|messages| messages.replace(vec![]));
+		for (sender, message) in messages {
+			self.execute_on(message.recipient.into(), || {
+				TokenDealer::handle_hrmp_message(
+					sender,
+					InboundHrmpMessage {
+						sent_at: 1,
+						data: message.data,
+					},
+				)
+			});
+		}
+	}
+}
+
+/// The messages sent to the relay chain so far, with the parachain that sent them.
+pub(crate) fn upward_messages() -> Vec<(ParaId, Xcm)> {
+	UPWARD_MESSAGES.with(|messages| {
+		messages
+			.borrow()
+			.iter()
+			.map(|(para, data)| {
+				let xcm = VersionedXcm::decode(&mut &data[..]).expect("Valid versioned XCM");
+				(*para, Xcm::try_from(xcm).expect("XCM v0"))
+			})
+			.collect()
+	})
+}
+
+pub(crate) fn last_event() -> Event {
+	System::events().pop().expect("Event expected").event
+}
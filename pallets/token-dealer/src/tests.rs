@@ -0,0 +1,711 @@
+// Copyright 2019-2021 PureStake Inc.
+// This file is part of Moonbeam.
+
+// Moonbeam is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Moonbeam is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Moonbeam.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Unit testing
+use crate::mock::{
+	last_event, sibling_account, upward_messages, Balances, Event as MetaEvent, MockNet, Origin,
+	RelayChainAccount, RelayNetwork, System, Test, TokenDealer, ALICE, BOB,
+};
+use crate::{
+	AccountId32Conversion, AccountKey20Aliases, Claimable, Error, Event, MappedAccountId32,
+	ParentIsAccount, ZeroPaddedAccountKey20,
+};
+use cumulus_primitives_core::{HrmpMessageHandler, InboundHrmpMessage};
+use frame_support::{assert_noop, assert_ok, traits::Get};
+use parity_scale_codec::Encode;
+use sp_core::{sr25519, Pair};
+use sp_runtime::{DispatchError, MultiSignature};
+use xcm::v0::{Junction, MultiAsset, MultiLocation, NetworkId, Order, Xcm};
+use xcm::VersionedXcm;
+use xcm_executor::traits::LocationConversion;
+
+const RELAY_ACCOUNT: [u8; 32] = [0x11; 32];
+
+fn sibling(id: u32) -> MultiLocation {
+	MultiLocation::X2(Junction::Parent, Junction::Parachain { id })
+}
+
+fn account_key(who: [u8; 20]) -> MultiLocation {
+	Junction::AccountKey20 {
+		network: NetworkId::Polkadot,
+		key: who,
+	}
+	.into()
+}
+
+fn account_id32(id: [u8; 32]) -> MultiLocation {
+	Junction::AccountId32 {
+		network: NetworkId::Polkadot,
+		id,
+	}
+	.into()
+}
+
+fn padded(who: crate::mock::AccountId) -> [u8; 32] {
+	let mut id = [0u8; 32];
+	id[..20].copy_from_slice(who.as_bytes());
+	id
+}
+
+/// The message depositing `amount` of the relay chain token held in reserve to `beneficiary`.
+fn reserve_deposit(amount: u128, beneficiary: MultiLocation) -> Xcm {
+	Xcm::ReserveAssetDeposit {
+		assets: vec![MultiAsset::ConcreteFungible {
+			id: Junction::Parent.into(),
+			amount,
+		}],
+		effects: vec![Order::DepositAsset {
+			assets: vec![MultiAsset::All],
+			dest: beneficiary,
+		}],
+	}
+}
+
+/// The message asking for `amount` of `asset` to be paid to `beneficiary`.
+fn transfer(asset: MultiLocation, amount: u128, beneficiary: MultiLocation) -> Xcm {
+	Xcm::WithdrawAsset {
+		assets: vec![MultiAsset::ConcreteFungible { id: asset, amount }],
+		effects: vec![Order::DepositAsset {
+			assets: vec![MultiAsset::All],
+			dest: beneficiary,
+		}],
+	}
+}
+
+#[test]
+fn account_key_locations_convert_both_ways() {
+	type Converter = AccountKey20Aliases<RelayNetwork, crate::mock::AccountId>;
+	assert_eq!(
+		Converter::try_into_location(ALICE),
+		Ok(account_key(ALICE.into()))
+	);
+	assert_eq!(
+		Converter::from_location(&account_key(ALICE.into())),
+		Some(ALICE)
+	);
+	assert_eq!(
+		Converter::from_location(
+			&Junction::AccountKey20 {
+				network: NetworkId::Any,
+				key: ALICE.into(),
+			}
+			.into()
+		),
+		Some(ALICE)
+	);
+	assert_eq!(
+		Converter::from_location(
+			&Junction::AccountKey20 {
+				network: NetworkId::Kusama,
+				key: ALICE.into(),
+			}
+			.into()
+		),
+		None
+	);
+}
+
+#[test]
+fn zero_padded_account_ids_map_to_account_keys() {
+	assert_eq!(
+		<ZeroPaddedAccountKey20 as AccountId32Conversion<crate::mock::AccountId>>::from_account_id32(
+			padded(ALICE)
+		),
+		Some(ALICE)
+	);
+	assert_eq!(
+		<ZeroPaddedAccountKey20 as AccountId32Conversion<crate::mock::AccountId>>::into_account_id32(
+			ALICE
+		),
+		padded(ALICE)
+	);
+	assert_eq!(
+		<ZeroPaddedAccountKey20 as AccountId32Conversion<crate::mock::AccountId>>::from_account_id32(
+			RELAY_ACCOUNT
+		),
+		None
+	);
+}
+
+#[test]
+fn account_id32_locations_convert_both_ways() {
+	type Converter =
+		MappedAccountId32<RelayNetwork, crate::mock::AccountId, ZeroPaddedAccountKey20>;
+	assert_eq!(
+		Converter::try_into_location(ALICE),
+		Ok(account_id32(padded(ALICE)))
+	);
+	assert_eq!(
+		Converter::from_location(&account_id32(padded(ALICE))),
+		Some(ALICE)
+	);
+	assert_eq!(Converter::from_location(&account_id32(RELAY_ACCOUNT)), None);
+	assert_eq!(
+		Converter::from_location(
+			&Junction::AccountId32 {
+				network: NetworkId::Kusama,
+				id: padded(ALICE),
+			}
+			.into()
+		),
+		None
+	);
+}
+
+#[test]
+fn parent_converts_to_the_relay_chain_account() {
+	type Converter = ParentIsAccount<crate::mock::AccountId, RelayChainAccount>;
+	let relay_chain_account = RelayChainAccount::get();
+	assert_ne!(relay_chain_account, Default::default());
+	assert_eq!(
+		Converter::from_location(&Junction::Parent.into()),
+		Some(relay_chain_account)
+	);
+	assert_eq!(
+		Converter::try_into_location(relay_chain_account),
+		Ok(Junction::Parent.into())
+	);
+	assert_eq!(Converter::from_location(&sibling(2)), None);
+	assert_eq!(Converter::try_into_location(ALICE), Err(ALICE));
+}
+
+#[test]
+fn transfer_to_relay_chain_works() {
+	let mut net = MockNet::new(vec![(1, vec![(ALICE, 1000)])]);
+	net.execute_on(1, || {
+		assert_ok!(TokenDealer::transfer_to_relay_chain(
+			Origin::signed(ALICE),
+			RELAY_ACCOUNT,
+			100
+		));
+		assert_eq!(Balances::free_balance(&ALICE), 900);
+		assert_eq!(
+			last_event(),
+			MetaEvent::token_dealer(Event::TransferredToRelayChain(ALICE, RELAY_ACCOUNT, 100))
+		);
+	});
+	assert_eq!(
+		upward_messages(),
+		vec![(
+			1.into(),
+			transfer(
+				MultiLocation::Null,
+				100,
+				Junction::AccountId32 {
+					network: NetworkId::Polkadot,
+					id: RELAY_ACCOUNT,
+				}
+				.into()
+			)
+		)]
+	);
+}
+
+#[test]
+fn cannot_transfer_more_than_balance() {
+	let mut net = MockNet::new(vec![(1, vec![(ALICE, 1000)])]);
+	net.execute_on(1, || {
+		assert_noop!(
+			TokenDealer::transfer_to_relay_chain(Origin::signed(ALICE), RELAY_ACCOUNT, 2000),
+			pallet_balances::Error::<Test>::InsufficientBalance
+		);
+	});
+	assert!(upward_messages().is_empty());
+}
+
+#[test]
+fn transfer_to_parachain_pays_from_sovereign_account() {
+	let mut net = MockNet::new(vec![
+		(1, vec![(ALICE, 1000)]),
+		(2, vec![(sibling_account(1), 500)]),
+	]);
+	net.execute_on(1, || {
+		assert_ok!(TokenDealer::allow_parachain(Origin::root(), 2.into()));
+		assert_ok!(TokenDealer::transfer_to_parachain(
+			Origin::signed(ALICE),
+			2.into(),
+			BOB,
+			100
+		));
+		assert_eq!(Balances::free_balance(&ALICE), 900);
+		assert_eq!(
+			last_event(),
+			MetaEvent::token_dealer(Event::TransferredToParachain(ALICE, 2.into(), BOB, 100))
+		);
+	});
+
+	net.process_hrmp_messages();
+	net.execute_on(2, || {
+		assert_eq!(
+			last_event(),
+			MetaEvent::token_dealer(Event::Success(sibling(1)))
+		);
+		assert_eq!(Balances::free_balance(&BOB), 100);
+		assert_eq!(Balances::free_balance(&sibling_account(1)), 400);
+	});
+}
+
+#[test]
+fn transfer_to_parachain_fails_without_sovereign_funds() {
+	let mut net = MockNet::new(vec![(1, vec![(ALICE, 1000)]), (2, vec![])]);
+	net.execute_on(1, || {
+		assert_ok!(TokenDealer::allow_parachain(Origin::root(), 2.into()));
+		assert_ok!(TokenDealer::transfer_to_parachain(
+			Origin::signed(ALICE),
+			2.into(),
+			BOB,
+			100
+		));
+	});
+
+	net.process_hrmp_messages();
+	net.execute_on(2, || {
+		assert!(matches!(
+			last_event(),
+			MetaEvent::token_dealer(Event::Fail(origin, _)) if origin == sibling(1)
+		));
+		assert_eq!(Balances::free_balance(&BOB), 0);
+	});
+}
+
+#[test]
+fn reserve_deposit_from_relay_chain_works() {
+	let mut net = MockNet::new(vec![(1, vec![])]);
+	net.send_downward(1, reserve_deposit(100, account_key(BOB.into())));
+	net.execute_on(1, || {
+		assert_eq!(
+			last_event(),
+			MetaEvent::token_dealer(Event::Success(Junction::Parent.into()))
+		);
+		assert_eq!(Balances::free_balance(&BOB), 100);
+	});
+}
+
+#[test]
+fn relay_chain_cannot_withdraw_from_other_accounts() {
+	let mut net = MockNet::new(vec![(1, vec![(ALICE, 1000)])]);
+	// The relay chain can only withdraw from its own sovereign account here, which is empty.
+	net.send_downward(
+		1,
+		transfer(Junction::Parent.into(), 100, account_key(BOB.into())),
+	);
+	net.execute_on(1, || {
+		assert!(matches!(
+			last_event(),
+			MetaEvent::token_dealer(Event::Fail(MultiLocation::X1(Junction::Parent), _))
+		));
+		assert_eq!(Balances::free_balance(&ALICE), 1000);
+		assert_eq!(Balances::free_balance(&BOB), 0);
+	});
+}
+
+#[test]
+fn malformed_message_is_reported() {
+	let mut net = MockNet::new(vec![(1, vec![])]);
+	net.execute_on(1, || {
+		use cumulus_primitives_core::{DownwardMessageHandler, InboundDownwardMessage};
+		TokenDealer::handle_downward_message(InboundDownwardMessage {
+			sent_at: 1,
+			msg: vec![0xff, 0xff],
+		});
+		assert_eq!(
+			last_event(),
+			MetaEvent::token_dealer(Event::BadFormat(Junction::Parent.into()))
+		);
+	});
+}
+
+#[test]
+fn reserve_deposit_to_padded_account_id32_works() {
+	let mut net = MockNet::new(vec![(1, vec![])]);
+	net.send_downward(1, reserve_deposit(100, account_id32(padded(BOB))));
+	net.execute_on(1, || {
+		assert_eq!(
+			last_event(),
+			MetaEvent::token_dealer(Event::Success(Junction::Parent.into()))
+		);
+		assert_eq!(Balances::free_balance(&BOB), 100);
+	});
+}
+
+#[test]
+fn deposit_to_unknown_beneficiary_is_parked() {
+	let mut net = MockNet::new(vec![(1, vec![])]);
+	net.send_downward(1, reserve_deposit(100, account_id32(RELAY_ACCOUNT)));
+	net.execute_on(1, || {
+		assert_eq!(
+			last_event(),
+			MetaEvent::token_dealer(Event::Success(Junction::Parent.into()))
+		);
+		assert!(System::events().iter().any(|record| record.event
+			== MetaEvent::token_dealer(Event::Parked(account_id32(RELAY_ACCOUNT), 100))));
+		assert_eq!(Claimable::<Test>::get(account_id32(RELAY_ACCOUNT)), 100);
+		assert_eq!(Balances::free_balance(&TokenDealer::holding_account()), 100);
+	});
+}
+
+#[test]
+fn governance_can_release_parked_deposits() {
+	let mut net = MockNet::new(vec![(1, vec![])]);
+	net.send_downward(1, reserve_deposit(100, account_id32(RELAY_ACCOUNT)));
+	net.execute_on(1, || {
+		assert_noop!(
+			TokenDealer::release(Origin::signed(ALICE), account_id32(RELAY_ACCOUNT), ALICE),
+			DispatchError::BadOrigin
+		);
+		assert_ok!(TokenDealer::release(
+			Origin::root(),
+			account_id32(RELAY_ACCOUNT),
+			ALICE
+		));
+		assert_eq!(
+			last_event(),
+			MetaEvent::token_dealer(Event::Claimed(account_id32(RELAY_ACCOUNT), ALICE, 100))
+		);
+		assert_eq!(Balances::free_balance(&ALICE), 100);
+		assert_eq!(Balances::free_balance(&TokenDealer::holding_account()), 0);
+		assert_eq!(Claimable::<Test>::get(account_id32(RELAY_ACCOUNT)), 0);
+		assert_noop!(
+			TokenDealer::release(Origin::root(), account_id32(RELAY_ACCOUNT), ALICE),
+			Error::<Test>::NothingToClaim
+		);
+	});
+}
+
+#[test]
+fn beneficiary_can_claim_parked_deposits() {
+	let mut net = MockNet::new(vec![(1, vec![])]);
+	let owner = sr25519::Pair::from_seed(&[7u8; 32]);
+	let beneficiary = account_id32(owner.public().0);
+	net.send_downward(1, reserve_deposit(100, beneficiary.clone()));
+	net.execute_on(1, || {
+		assert_eq!(Claimable::<Test>::get(&beneficiary), 100);
+		let signature: MultiSignature = owner.sign(&TokenDealer::claim_payload(&BOB)).into();
+		// The signature is for BOB only
+		assert_noop!(
+			TokenDealer::claim(
+				Origin::signed(ALICE),
+				beneficiary.clone(),
+				signature.clone()
+			),
+			Error::<Test>::NotBeneficiary
+		);
+		// Someone else's key cannot claim
+		let other = sr25519::Pair::from_seed(&[8u8; 32]);
+		assert_noop!(
+			TokenDealer::claim(
+				Origin::signed(BOB),
+				beneficiary.clone(),
+				other.sign(&TokenDealer::claim_payload(&BOB)).into()
+			),
+			Error::<Test>::NotBeneficiary
+		);
+		assert_ok!(TokenDealer::claim(
+			Origin::signed(BOB),
+			beneficiary.clone(),
+			signature.clone()
+		));
+		assert_eq!(
+			last_event(),
+			MetaEvent::token_dealer(Event::Claimed(beneficiary.clone(), BOB, 100))
+		);
+		assert_eq!(Balances::free_balance(&BOB), 100);
+		assert_noop!(
+			TokenDealer::claim(Origin::signed(BOB), beneficiary, signature),
+			Error::<Test>::NothingToClaim
+		);
+	});
+}
+
+#[test]
+fn only_32_byte_beneficiaries_can_claim() {
+	let mut net = MockNet::new(vec![(1, vec![])]);
+	let owner = sr25519::Pair::from_seed(&[7u8; 32]);
+	net.execute_on(1, || {
+		assert_noop!(
+			TokenDealer::claim(
+				Origin::signed(BOB),
+				sibling(2),
+				owner.sign(&TokenDealer::claim_payload(&BOB)).into()
+			),
+			Error::<Test>::NotBeneficiary
+		);
+	});
+}
+
+#[test]
+fn failed_messages_are_refunded_to_the_sending_parachain() {
+	let mut net = MockNet::new(vec![(2, vec![(sibling_account(1), 500)])]);
+	// The second asset is unknown, so the message fails after withdrawing the first one.
+	let xcm = Xcm::WithdrawAsset {
+		assets: vec![
+			MultiAsset::ConcreteFungible {
+				id: Junction::Parent.into(),
+				amount: 100,
+			},
+			MultiAsset::ConcreteFungible {
+				id: MultiLocation::Null,
+				amount: 100,
+			},
+		],
+		effects: vec![Order::DepositAsset {
+			assets: vec![MultiAsset::All],
+			dest: account_key(BOB.into()),
+		}],
+	};
+	net.execute_on(2, || {
+		TokenDealer::handle_hrmp_message(
+			1.into(),
+			InboundHrmpMessage {
+				sent_at: 1,
+				data: VersionedXcm::from(xcm).encode(),
+			},
+		);
+		assert!(matches!(
+			last_event(),
+			MetaEvent::token_dealer(Event::Fail(origin, _)) if origin == sibling(1)
+		));
+		assert!(System::events().iter().any(
+			|record| record.event == MetaEvent::token_dealer(Event::Refunded(sibling(1), 100))
+		));
+		assert_eq!(Balances::free_balance(&sibling_account(1)), 400);
+		assert_eq!(Balances::free_balance(&BOB), 0);
+	});
+	// Our sovereign account on the relay chain pays the sibling's
+	assert_eq!(
+		upward_messages(),
+		vec![(
+			2.into(),
+			transfer(
+				MultiLocation::Null,
+				100,
+				Junction::Parachain { id: 1 }.into()
+			)
+		)]
+	);
+}
+
+#[test]
+fn failed_reserve_deposits_from_relay_chain_are_parked() {
+	let mut net = MockNet::new(vec![(1, vec![])]);
+	// The second asset is not the relay chain's, so the message fails.
+	let xcm = Xcm::ReserveAssetDeposit {
+		assets: vec![
+			MultiAsset::ConcreteFungible {
+				id: Junction::Parent.into(),
+				amount: 100,
+			},
+			MultiAsset::ConcreteFungible {
+				id: MultiLocation::Null,
+				amount: 100,
+			},
+		],
+		effects: vec![Order::DepositAsset {
+			assets: vec![MultiAsset::All],
+			dest: account_key(BOB.into()),
+		}],
+	};
+	net.send_downward(1, xcm);
+	net.execute_on(1, || {
+		assert!(matches!(
+			last_event(),
+			MetaEvent::token_dealer(Event::Fail(MultiLocation::X1(Junction::Parent), _))
+		));
+		assert_eq!(Balances::free_balance(&BOB), 0);
+		assert_eq!(
+			Claimable::<Test>::get(MultiLocation::from(Junction::Parent)),
+			100
+		);
+		assert_eq!(Balances::free_balance(&TokenDealer::holding_account()), 100);
+	});
+	assert!(upward_messages().is_empty());
+}
+
+#[test]
+fn transfer_controls_require_governance() {
+	let mut net = MockNet::new(vec![(1, vec![])]);
+	net.execute_on(1, || {
+		assert_noop!(
+			TokenDealer::allow_parachain(Origin::signed(ALICE), 2.into()),
+			DispatchError::BadOrigin
+		);
+		assert_noop!(
+			TokenDealer::disallow_parachain(Origin::signed(ALICE), 2.into()),
+			DispatchError::BadOrigin
+		);
+		assert_noop!(
+			TokenDealer::pause(Origin::signed(ALICE)),
+			DispatchError::BadOrigin
+		);
+		assert_noop!(
+			TokenDealer::resume(Origin::signed(ALICE)),
+			DispatchError::BadOrigin
+		);
+		assert_noop!(
+			TokenDealer::set_transfer_limits(Origin::signed(ALICE), None, None),
+			DispatchError::BadOrigin
+		);
+	});
+}
+
+#[test]
+fn cannot_transfer_to_parachain_not_allowed() {
+	let mut net = MockNet::new(vec![(1, vec![(ALICE, 1000)])]);
+	net.execute_on(1, || {
+		assert_noop!(
+			TokenDealer::transfer_to_parachain(Origin::signed(ALICE), 2.into(), BOB, 100),
+			Error::<Test>::ParachainNotAllowed
+		);
+		assert_ok!(TokenDealer::allow_parachain(Origin::root(), 2.into()));
+		assert_eq!(
+			last_event(),
+			MetaEvent::token_dealer(Event::ParachainAllowed(2.into()))
+		);
+		assert_ok!(TokenDealer::transfer_to_parachain(
+			Origin::signed(ALICE),
+			2.into(),
+			BOB,
+			100
+		));
+		assert_ok!(TokenDealer::disallow_parachain(Origin::root(), 2.into()));
+		assert_eq!(
+			last_event(),
+			MetaEvent::token_dealer(Event::ParachainDisallowed(2.into()))
+		);
+		assert_noop!(
+			TokenDealer::transfer_to_parachain(Origin::signed(ALICE), 2.into(), BOB, 100),
+			Error::<Test>::ParachainNotAllowed
+		);
+	});
+}
+
+#[test]
+fn cannot_transfer_while_paused() {
+	let mut net = MockNet::new(vec![(1, vec![(ALICE, 1000)])]);
+	net.execute_on(1, || {
+		assert_ok!(TokenDealer::allow_parachain(Origin::root(), 2.into()));
+		assert_ok!(TokenDealer::pause(Origin::root()));
+		assert_eq!(
+			last_event(),
+			MetaEvent::token_dealer(Event::TransfersPaused)
+		);
+		assert_noop!(
+			TokenDealer::transfer_to_relay_chain(Origin::signed(ALICE), RELAY_ACCOUNT, 100),
+			Error::<Test>::TransfersPaused
+		);
+		assert_noop!(
+			TokenDealer::transfer_to_parachain(Origin::signed(ALICE), 2.into(), BOB, 100),
+			Error::<Test>::TransfersPaused
+		);
+		assert_ok!(TokenDealer::resume(Origin::root()));
+		assert_eq!(
+			last_event(),
+			MetaEvent::token_dealer(Event::TransfersResumed)
+		);
+		assert_ok!(TokenDealer::transfer_to_relay_chain(
+			Origin::signed(ALICE),
+			RELAY_ACCOUNT,
+			100
+		));
+	});
+}
+
+#[test]
+fn account_limit_applies_per_period() {
+	let mut net = MockNet::new(vec![(1, vec![(ALICE, 1000), (BOB, 1000)])]);
+	net.execute_on(1, || {
+		assert_ok!(TokenDealer::set_transfer_limits(
+			Origin::root(),
+			Some(100),
+			None
+		));
+		assert_eq!(
+			last_event(),
+			MetaEvent::token_dealer(Event::TransferLimitsUpdated(Some(100), None))
+		);
+		assert_ok!(TokenDealer::transfer_to_relay_chain(
+			Origin::signed(ALICE),
+			RELAY_ACCOUNT,
+			60
+		));
+		assert_noop!(
+			TokenDealer::transfer_to_relay_chain(Origin::signed(ALICE), RELAY_ACCOUNT, 50),
+			Error::<Test>::AccountLimitExceeded
+		);
+		// Other accounts have their own limit.
+		assert_ok!(TokenDealer::transfer_to_relay_chain(
+			Origin::signed(BOB),
+			RELAY_ACCOUNT,
+			100
+		));
+
+		System::set_block_number(10);
+		assert_ok!(TokenDealer::transfer_to_relay_chain(
+			Origin::signed(ALICE),
+			RELAY_ACCOUNT,
+			100
+		));
+	});
+}
+
+#[test]
+fn global_limit_applies_per_period() {
+	let mut net = MockNet::new(vec![(1, vec![(ALICE, 1000), (BOB, 1000)])]);
+	net.execute_on(1, || {
+		assert_ok!(TokenDealer::set_transfer_limits(
+			Origin::root(),
+			None,
+			Some(100)
+		));
+		assert_ok!(TokenDealer::transfer_to_relay_chain(
+			Origin::signed(ALICE),
+			RELAY_ACCOUNT,
+			60
+		));
+		assert_noop!(
+			TokenDealer::transfer_to_relay_chain(Origin::signed(BOB), RELAY_ACCOUNT, 50),
+			Error::<Test>::GlobalLimitExceeded
+		);
+
+		System::set_block_number(10);
+		assert_ok!(TokenDealer::transfer_to_relay_chain(
+			Origin::signed(BOB),
+			RELAY_ACCOUNT,
+			100
+		));
+	});
+}
+
+#[test]
+fn failed_transfers_do_not_count_against_limits() {
+	let mut net = MockNet::new(vec![(1, vec![(ALICE, 1000)])]);
+	net.execute_on(1, || {
+		assert_ok!(TokenDealer::set_transfer_limits(
+			Origin::root(),
+			Some(1500),
+			Some(1500)
+		));
+		assert_noop!(
+			TokenDealer::transfer_to_relay_chain(Origin::signed(ALICE), RELAY_ACCOUNT, 1200),
+			pallet_balances::Error::<Test>::InsufficientBalance
+		);
+		assert_ok!(TokenDealer::transfer_to_relay_chain(
+			Origin::signed(ALICE),
+			RELAY_ACCOUNT,
+			1000
+		));
+	});
+}
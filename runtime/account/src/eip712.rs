@@ -0,0 +1,445 @@
+// Copyright 2019-2021 PureStake Inc.
+// This file is part of Moonbeam.
+
+// Moonbeam is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Moonbeam is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Moonbeam.  If not, see <http://www.gnu.org/licenses/>.
+
+//! EIP-712 typed data of Substrate signing payloads.
+//!
+//! Wallets such as MetaMask sign transactions with `eth_signTypedData_v4`, in the `Moonbeam`
+//! domain whose `chainId` is the Ethereum chain id of the chain and whose `version` is its
+//! runtime spec version. The signing payload of a transaction is split into a `Transaction`
+//! struct: its `call`, a `Call` struct naming the pallet and function called along with their
+//! indices and the SCALE encoded arguments, and the SCALE encoded signed `extensions` (the era,
+//! nonce, tip and the data they check against).
+//!
+//! Payloads longer than 256 bytes are only ever signed through their blake2-256 hash, as for any
+//! other Substrate signature, so they are signed as a `HashedTransaction` struct holding that
+//! hash. The payloads of a chain always end with its genesis hash and the hash of the block its
+//! era starts at, so a 32 bytes message is always such a hash.
+//!
+//! The runtime exposes the typed data of a payload to wallets through the `Eip712Api` runtime API.
+
+use sha3::{Digest, Keccak256};
+use sp_std::vec::Vec;
+
+/// The name of the domain.
+const DOMAIN_NAME: &[u8] = b"Moonbeam";
+/// Keccak-256 of `EIP712Domain(string name,string version,uint256 chainId)`.
+const DOMAIN_TYPE_HASH: [u8; 32] =
+	hex_literal::hex!("c2f8787176b8ac6bf7215b4adcc1e069bf4ab82d9ab1df05a57a91d425935b6e");
+/// Keccak-256 of `Call(string pallet,string function,uint8 palletIndex,uint8 functionIndex,bytes
+/// arguments)`.
+const CALL_TYPE_HASH: [u8; 32] =
+	hex_literal::hex!("5851231ccaddec076c0c3421295de86208a1a9ee3bbc672b8976341ff4996291");
+/// Keccak-256 of `Transaction(Call call,bytes extensions)` followed by the `Call` type.
+const TRANSACTION_TYPE_HASH: [u8; 32] =
+	hex_literal::hex!("7a4bd83fa66ad301f74b0f747f9de0141028f6c6577fe9af38edb3cf3aeeefd4");
+/// Keccak-256 of `HashedTransaction(bytes32 payloadHash)`.
+const HASHED_TRANSACTION_TYPE_HASH: [u8; 32] =
+	hex_literal::hex!("f6a36c3ab485e6fd4520835bbef25aee6636a2862652d6c2fe80c1b5bc1208dd");
+
+/// The JSON `types` of the EIP712 domain.
+const DOMAIN_TYPES_JSON: &str = concat!(
+	r#""EIP712Domain":[{"name":"name","type":"string"},"#,
+	r#"{"name":"version","type":"string"},{"name":"chainId","type":"uint256"}]"#,
+);
+/// The JSON `types` of a `Transaction`.
+const TRANSACTION_TYPES_JSON: &str = concat!(
+	r#""Transaction":[{"name":"call","type":"Call"},{"name":"extensions","type":"bytes"}],"#,
+	r#""Call":[{"name":"pallet","type":"string"},{"name":"function","type":"string"},"#,
+	r#"{"name":"palletIndex","type":"uint8"},{"name":"functionIndex","type":"uint8"},"#,
+	r#"{"name":"arguments","type":"bytes"}]"#,
+);
+/// The JSON `types` of a `HashedTransaction`.
+const HASHED_TRANSACTION_TYPES_JSON: &str =
+	r#""HashedTransaction":[{"name":"payloadHash","type":"bytes32"}]"#;
+
+/// The chain EIP-712 signatures are made for.
+pub trait Eip712Chain {
+	/// The domain the transactions of the chain are signed in.
+	fn domain() -> Eip712Domain;
+
+	/// The call the signing payload `payload` starts with, or `None` if it does not start with a
+	/// call of the chain.
+	fn call_info(payload: &[u8]) -> Option<CallInfo>;
+}
+
+/// The call a signing payload starts with.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct CallInfo {
+	/// The name of the pallet called
+	pub pallet: &'static str,
+	/// The name of the function called
+	pub function: &'static str,
+	/// The length of the encoded call
+	pub len: usize,
+}
+
+/// The EIP-712 domain the transactions of a chain are signed in. Signatures are only valid on
+/// the chain they were made for, and until its next runtime upgrade.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Eip712Domain {
+	/// The Ethereum chain id of the chain
+	pub chain_id: u64,
+	/// The runtime spec version of the chain
+	pub spec_version: u32,
+}
+
+impl Eip712Domain {
+	/// The hash that is signed to sign `message` with `eth_signTypedData_v4`.
+	pub fn signing_hash(&self, message: &Eip712Message) -> [u8; 32] {
+		keccak_256(&[&b"\x19\x01"[..], &self.separator(), &message.struct_hash()])
+	}
+
+	/// The typed data, as JSON, to pass to `eth_signTypedData_v4` to sign `message`.
+	pub fn typed_data(&self, message: &Eip712Message) -> Vec<u8> {
+		let mut json = Vec::new();
+		json.extend_from_slice(br#"{"types":{"#);
+		json.extend_from_slice(DOMAIN_TYPES_JSON.as_bytes());
+		json.push(b',');
+		match message {
+			Eip712Message::Transaction { .. } => {
+				json.extend_from_slice(TRANSACTION_TYPES_JSON.as_bytes());
+				json.extend_from_slice(br#"},"primaryType":"Transaction","#);
+			}
+			Eip712Message::HashedTransaction(_) => {
+				json.extend_from_slice(HASHED_TRANSACTION_TYPES_JSON.as_bytes());
+				json.extend_from_slice(br#"},"primaryType":"HashedTransaction","#);
+			}
+		}
+		json.extend_from_slice(br#""domain":{"name":"Moonbeam","version":""#);
+		json.extend_from_slice(&decimal(self.spec_version.into()));
+		json.extend_from_slice(br#"","chainId":"#);
+		json.extend_from_slice(&decimal(self.chain_id));
+		json.extend_from_slice(br#"},"message":"#);
+		message.write_json(&mut json);
+		json.push(b'}');
+		json
+	}
+
+	/// The domain separator.
+	fn separator(&self) -> [u8; 32] {
+		keccak_256(&[
+			&DOMAIN_TYPE_HASH[..],
+			&keccak_256(&[DOMAIN_NAME]),
+			&keccak_256(&[&decimal(self.spec_version.into())]),
+			&word(self.chain_id),
+		])
+	}
+}
+
+/// The message signed for a signing payload.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Eip712Message<'a> {
+	/// A payload of at most 256 bytes, split into its call and signed extensions.
+	Transaction {
+		/// The call of the payload
+		call: Eip712Call<'a>,
+		/// The SCALE encoded signed extensions and the data they check against
+		extensions: &'a [u8],
+	},
+	/// The blake2-256 hash of a longer payload.
+	HashedTransaction(&'a [u8]),
+}
+
+/// The call of a `Transaction`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Eip712Call<'a> {
+	/// The name of the pallet called
+	pub pallet: &'a str,
+	/// The name of the function called
+	pub function: &'a str,
+	/// The index of the pallet in the runtime
+	pub pallet_index: u8,
+	/// The index of the function in the pallet
+	pub function_index: u8,
+	/// The SCALE encoded arguments of the function
+	pub arguments: &'a [u8],
+}
+
+impl<'a> Eip712Message<'a> {
+	/// The message signed for the signing payload, or its hash, `payload` of a transaction of
+	/// `Chain`. `None` if it is not the payload of a transaction of the chain.
+	pub fn of<Chain: Eip712Chain>(payload: &'a [u8]) -> Option<Self> {
+		if payload.len() == 32 {
+			return Some(Eip712Message::HashedTransaction(payload));
+		}
+		let info = Chain::call_info(payload)?;
+		if info.len < 2 || info.len > payload.len() {
+			return None;
+		}
+		Some(Eip712Message::Transaction {
+			call: Eip712Call {
+				pallet: info.pallet,
+				function: info.function,
+				pallet_index: payload[0],
+				function_index: payload[1],
+				arguments: &payload[2..info.len],
+			},
+			extensions: &payload[info.len..],
+		})
+	}
+
+	/// The EIP-712 `hashStruct` of the message.
+	fn struct_hash(&self) -> [u8; 32] {
+		match self {
+			Eip712Message::Transaction { call, extensions } => {
+				let call_hash = keccak_256(&[
+					&CALL_TYPE_HASH[..],
+					&keccak_256(&[call.pallet.as_bytes()]),
+					&keccak_256(&[call.function.as_bytes()]),
+					&word(call.pallet_index.into()),
+					&word(call.function_index.into()),
+					&keccak_256(&[call.arguments]),
+				]);
+				keccak_256(&[
+					&TRANSACTION_TYPE_HASH[..],
+					&call_hash,
+					&keccak_256(&[extensions]),
+				])
+			}
+			Eip712Message::HashedTransaction(hash) => {
+				keccak_256(&[&HASHED_TRANSACTION_TYPE_HASH[..], hash])
+			}
+		}
+	}
+
+	/// Appends the JSON of the message to `json`.
+	fn write_json(&self, json: &mut Vec<u8>) {
+		match self {
+			Eip712Message::Transaction { call, extensions } => {
+				json.extend_from_slice(br#"{"call":{"pallet":""#);
+				json.extend_from_slice(call.pallet.as_bytes());
+				json.extend_from_slice(br#"","function":""#);
+				json.extend_from_slice(call.function.as_bytes());
+				json.extend_from_slice(br#"","palletIndex":"#);
+				json.extend_from_slice(&decimal(call.pallet_index.into()));
+				json.extend_from_slice(br#","functionIndex":"#);
+				json.extend_from_slice(&decimal(call.function_index.into()));
+				json.extend_from_slice(br#","arguments":""#);
+				write_hex(json, call.arguments);
+				json.extend_from_slice(br#""},"extensions":""#);
+				write_hex(json, extensions);
+				json.extend_from_slice(br#""}"#);
+			}
+			Eip712Message::HashedTransaction(hash) => {
+				json.extend_from_slice(br#"{"payloadHash":""#);
+				write_hex(json, hash);
+				json.extend_from_slice(br#""}"#);
+			}
+		}
+	}
+}
+
+/// Keccak-256 of the concatenation of `data`.
+fn keccak_256(data: &[&[u8]]) -> [u8; 32] {
+	let mut hasher = Keccak256::new();
+	for data in data {
+		hasher.input(data);
+	}
+	let mut hash = [0u8; 32];
+	hash.copy_from_slice(hasher.result().as_slice());
+	hash
+}
+
+/// The 32 bytes big-endian ABI encoding of `n`.
+fn word(n: u64) -> [u8; 32] {
+	let mut word = [0u8; 32];
+	word[24..].copy_from_slice(&n.to_be_bytes());
+	word
+}
+
+/// The decimal representation of `n`.
+fn decimal(mut n: u64) -> Vec<u8> {
+	let mut digits = Vec::new();
+	loop {
+		digits.push(b'0' + (n % 10) as u8);
+		n /= 10;
+		if n == 0 {
+			break;
+		}
+	}
+	digits.reverse();
+	digits
+}
+
+/// Appends the `0x` prefixed hexadecimal representation of `bytes` to `json`.
+fn write_hex(json: &mut Vec<u8>, bytes: &[u8]) {
+	const DIGITS: &[u8; 16] = b"0123456789abcdef";
+	json.extend_from_slice(b"0x");
+	for byte in bytes {
+		json.push(DIGITS[(byte >> 4) as usize]);
+		json.push(DIGITS[(byte & 0xf) as usize]);
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	const DOMAIN: Eip712Domain = Eip712Domain {
+		chain_id: 1281,
+		spec_version: 32,
+	};
+
+	/// A chain whose only call is `System::remark` of a single byte.
+	struct TestChain;
+
+	impl Eip712Chain for TestChain {
+		fn domain() -> Eip712Domain {
+			DOMAIN
+		}
+
+		fn call_info(payload: &[u8]) -> Option<CallInfo> {
+			if payload.starts_with(&[0, 1, 4]) {
+				Some(CallInfo {
+					pallet: "System",
+					function: "remark",
+					len: 4,
+				})
+			} else {
+				None
+			}
+		}
+	}
+
+	/// A call of `TestChain` followed by 40 bytes of extensions.
+	fn payload() -> Vec<u8> {
+		let mut payload = vec![0, 1, 4, 0x2a];
+		payload.extend_from_slice(&[0xaa; 40]);
+		payload
+	}
+
+	#[test]
+	fn decimal_works() {
+		assert_eq!(decimal(0), b"0".to_vec());
+		assert_eq!(decimal(32), b"32".to_vec());
+		assert_eq!(decimal(u64::MAX), b"18446744073709551615".to_vec());
+	}
+
+	#[test]
+	fn payloads_are_split_into_their_call_and_extensions() {
+		let payload = payload();
+		assert_eq!(
+			Eip712Message::of::<TestChain>(&payload),
+			Some(Eip712Message::Transaction {
+				call: Eip712Call {
+					pallet: "System",
+					function: "remark",
+					pallet_index: 0,
+					function_index: 1,
+					arguments: &[4, 0x2a],
+				},
+				extensions: &[0xaa; 40],
+			})
+		);
+	}
+
+	#[test]
+	fn payload_hashes_are_signed_as_hashes() {
+		assert_eq!(
+			Eip712Message::of::<TestChain>(&[0x11; 32]),
+			Some(Eip712Message::HashedTransaction(&[0x11; 32]))
+		);
+	}
+
+	#[test]
+	fn payloads_without_a_call_of_the_chain_have_no_message() {
+		assert_eq!(Eip712Message::of::<TestChain>(&[1, 2, 3]), None);
+	}
+
+	#[test]
+	fn signing_hash_matches_eip712() {
+		let payload = payload();
+		let message = Eip712Message::of::<TestChain>(&payload).unwrap();
+		assert_eq!(
+			DOMAIN.signing_hash(&message),
+			hex_literal::hex!("acfd58140310c98159e038db4bf9556de7a62010eccd29f7c96da15c27905973")
+		);
+		assert_eq!(
+			DOMAIN.signing_hash(&Eip712Message::HashedTransaction(&[0x11; 32])),
+			hex_literal::hex!("b313a8d4f8a38443ad67ebf0887de094ce55114052f450ed9b06955c1763478a")
+		);
+	}
+
+	#[test]
+	fn signing_hash_depends_on_the_domain() {
+		let upgraded = Eip712Domain {
+			spec_version: 33,
+			..DOMAIN
+		};
+		let other_chain = Eip712Domain {
+			chain_id: 1280,
+			..DOMAIN
+		};
+		let message = Eip712Message::HashedTransaction(&[0x11; 32]);
+		assert_ne!(
+			DOMAIN.signing_hash(&message),
+			upgraded.signing_hash(&message)
+		);
+		assert_ne!(
+			DOMAIN.signing_hash(&message),
+			other_chain.signing_hash(&message)
+		);
+	}
+
+	#[test]
+	fn typed_data_is_json() {
+		let payload = payload();
+		let message = Eip712Message::of::<TestChain>(&payload).unwrap();
+		let extensions = "aa".repeat(40);
+		assert_eq!(
+			String::from_utf8(DOMAIN.typed_data(&message)).unwrap(),
+			[
+				r#"{"types":{"EIP712Domain":[{"name":"name","type":"string"},"#,
+				r#"{"name":"version","type":"string"},{"name":"chainId","type":"uint256"}],"#,
+				r#""Transaction":[{"name":"call","type":"Call"},"#,
+				r#"{"name":"extensions","type":"bytes"}],"#,
+				r#""Call":[{"name":"pallet","type":"string"},"#,
+				r#"{"name":"function","type":"string"},"#,
+				r#"{"name":"palletIndex","type":"uint8"},"#,
+				r#"{"name":"functionIndex","type":"uint8"},"#,
+				r#"{"name":"arguments","type":"bytes"}]},"#,
+				r#""primaryType":"Transaction","#,
+				r#""domain":{"name":"Moonbeam","version":"32","chainId":1281},"#,
+				r#""message":{"call":{"pallet":"System","function":"remark","#,
+				r#""palletIndex":0,"functionIndex":1,"arguments":"0x042a"},"#,
+				r#""extensions":"0x"#,
+				&extensions,
+				r#""}}"#,
+			]
+			.concat()
+		);
+	}
+
+	#[test]
+	fn typed_data_of_hashes_is_json() {
+		let hash = "11".repeat(32);
+		assert_eq!(
+			String::from_utf8(DOMAIN.typed_data(&Eip712Message::HashedTransaction(&[0x11; 32])))
+				.unwrap(),
+			[
+				r#"{"types":{"EIP712Domain":[{"name":"name","type":"string"},"#,
+				r#"{"name":"version","type":"string"},{"name":"chainId","type":"uint256"}],"#,
+				r#""HashedTransaction":[{"name":"payloadHash","type":"bytes32"}]},"#,
+				r#""primaryType":"HashedTransaction","#,
+				r#""domain":{"name":"Moonbeam","version":"32","chainId":1281},"#,
+				r#""message":{"payloadHash":"0x"#,
+				&hash,
+				r#""}}"#,
+			]
+			.concat()
+		);
+	}
+}
@@ -16,14 +16,19 @@
 
 //! The Ethereum Signature implementation.
 //!
-//! It includes the Verify and IdentifyAccount traits for the AccountId20
+//! It includes the Verify and IdentifyAccount traits for the AccountId20, and the EIP-712
+//! signature scheme that lets wallets sign Substrate transactions as typed data.
 
 #![cfg_attr(not(feature = "std"), no_std)]
 
 use parity_scale_codec::{Decode, Encode};
 use sha3::{Digest, Keccak256};
 use sp_core::{ecdsa, H160, H256};
+use sp_std::{convert::TryFrom, marker::PhantomData};
 
+mod eip712;
+
+pub use eip712::{CallInfo, Eip712Call, Eip712Chain, Eip712Domain, Eip712Message};
 #[cfg(feature = "std")]
 pub use serde::{de::DeserializeOwned, Deserialize, Serialize};
 
@@ -42,23 +47,91 @@ impl sp_runtime::traits::Verify for EthereumSignature {
 	fn verify<L: sp_runtime::traits::Lazy<[u8]>>(&self, mut msg: L, signer: &H160) -> bool {
 		let mut m = [0u8; 32];
 		m.copy_from_slice(Keccak256::digest(msg.get()).as_slice());
-		match sp_io::crypto::secp256k1_ecdsa_recover(self.0.as_ref(), &m) {
-			Ok(pubkey) => {
-				// TODO This conversion could use a comment. Why H256 first, then H160?
-				H160::from(H256::from_slice(Keccak256::digest(&pubkey).as_slice())) == *signer
-			}
-			Err(sp_io::EcdsaVerifyError::BadRS) => {
-				log::error!(target: "evm", "Error recovering: Incorrect value of R or S");
-				false
-			}
-			Err(sp_io::EcdsaVerifyError::BadV) => {
-				log::error!(target: "evm", "Error recovering: Incorrect value of V");
-				false
-			}
-			Err(sp_io::EcdsaVerifyError::BadSignature) => {
-				log::error!(target: "evm", "Error recovering: Invalid signature");
-				false
-			}
+		recover_signer(&self.0, &m).map_or(false, |recovered| recovered == *signer)
+	}
+}
+
+/// An ECDSA signature of the EIP-712 typed data of the payload (see `Eip712Message`), for the
+/// chain given by `Chain`. This lets wallets sign with `eth_signTypedData_v4`.
+#[cfg_attr(feature = "std", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "std", serde(bound = ""))]
+#[derive(Eq, PartialEq, Clone, Encode, Decode, sp_core::RuntimeDebug)]
+pub struct Eip712Signature<Chain>(
+	ecdsa::Signature,
+	#[codec(skip)]
+	#[cfg_attr(feature = "std", serde(skip))]
+	PhantomData<Chain>,
+);
+
+impl<Chain> From<ecdsa::Signature> for Eip712Signature<Chain> {
+	fn from(x: ecdsa::Signature) -> Self {
+		Eip712Signature(x, PhantomData)
+	}
+}
+
+impl<Chain: Eip712Chain> sp_runtime::traits::Verify for Eip712Signature<Chain> {
+	type Signer = EthereumSigner;
+	fn verify<L: sp_runtime::traits::Lazy<[u8]>>(&self, mut msg: L, signer: &H160) -> bool {
+		Eip712Message::of::<Chain>(msg.get()).map_or(false, |message| {
+			let m = Chain::domain().signing_hash(&message);
+			recover_signer(&self.0, &m).map_or(false, |recovered| recovered == *signer)
+		})
+	}
+}
+
+/// A signature of either of the signing schemes of Ethereum accounts.
+#[cfg_attr(feature = "std", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "std", serde(bound = ""))]
+#[derive(Eq, PartialEq, Clone, Encode, Decode, sp_core::RuntimeDebug)]
+pub enum MultiEthereumSignature<Chain> {
+	/// A signature of the Keccak-256 hash of the payload.
+	Ethereum(EthereumSignature),
+	/// A signature of the EIP-712 typed data of the payload.
+	Eip712(Eip712Signature<Chain>),
+}
+
+impl<Chain> From<EthereumSignature> for MultiEthereumSignature<Chain> {
+	fn from(x: EthereumSignature) -> Self {
+		MultiEthereumSignature::Ethereum(x)
+	}
+}
+
+impl<Chain> From<Eip712Signature<Chain>> for MultiEthereumSignature<Chain> {
+	fn from(x: Eip712Signature<Chain>) -> Self {
+		MultiEthereumSignature::Eip712(x)
+	}
+}
+
+impl<Chain: Eip712Chain> sp_runtime::traits::Verify for MultiEthereumSignature<Chain> {
+	type Signer = EthereumSigner;
+	fn verify<L: sp_runtime::traits::Lazy<[u8]>>(&self, msg: L, signer: &H160) -> bool {
+		match self {
+			MultiEthereumSignature::Ethereum(signature) => signature.verify(msg, signer),
+			MultiEthereumSignature::Eip712(signature) => signature.verify(msg, signer),
+		}
+	}
+}
+
+/// The address of the account that signed the 32 bytes `message` with `signature`.
+fn recover_signer(signature: &ecdsa::Signature, message: &[u8; 32]) -> Option<H160> {
+	match sp_io::crypto::secp256k1_ecdsa_recover(signature.as_ref(), message) {
+		Ok(pubkey) => {
+			// The address is the last 20 bytes of the Keccak-256 hash of the public key.
+			Some(H160::from(H256::from_slice(
+				Keccak256::digest(&pubkey).as_slice(),
+			)))
+		}
+		Err(sp_io::EcdsaVerifyError::BadRS) => {
+			log::error!(target: "evm", "Error recovering: Incorrect value of R or S");
+			None
+		}
+		Err(sp_io::EcdsaVerifyError::BadV) => {
+			log::error!(target: "evm", "Error recovering: Incorrect value of V");
+			None
+		}
+		Err(sp_io::EcdsaVerifyError::BadSignature) => {
+			log::error!(target: "evm", "Error recovering: Invalid signature");
+			None
 		}
 	}
 }
@@ -81,17 +154,79 @@ impl From<[u8; 20]> for EthereumSigner {
 	}
 }
 
-impl From<ecdsa::Public> for EthereumSigner {
-	fn from(x: ecdsa::Public) -> Self {
+impl From<H160> for EthereumSigner {
+	fn from(x: H160) -> Self {
+		EthereumSigner(x.0)
+	}
+}
+
+impl From<secp256k1::PublicKey> for EthereumSigner {
+	fn from(x: secp256k1::PublicKey) -> Self {
+		// The address is the last 20 bytes of the Keccak-256 hash of the uncompressed public key,
+		// without its 0x04 prefix.
+		let hash = Keccak256::digest(&x.serialize()[1..]);
 		let mut m = [0u8; 20];
-		m.copy_from_slice(&x.as_ref()[13..33]);
+		m.copy_from_slice(&hash[12..32]);
 		EthereumSigner(m)
 	}
 }
 
+/// The signer of the account of a compressed ECDSA public key, such as the keys of the keystore.
+/// Fails if the bytes are not a valid secp256k1 public key, which have no account.
+impl TryFrom<ecdsa::Public> for EthereumSigner {
+	type Error = secp256k1::Error;
+
+	fn try_from(x: ecdsa::Public) -> Result<Self, Self::Error> {
+		let mut compressed = [0u8; 33];
+		compressed.copy_from_slice(x.as_ref());
+		secp256k1::PublicKey::parse_compressed(&compressed).map(Into::into)
+	}
+}
+
 #[cfg(feature = "std")]
 impl std::fmt::Display for EthereumSigner {
 	fn fmt(&self, fmt: &mut std::fmt::Formatter) -> std::fmt::Result {
 		write!(fmt, "ethereum signature: {:?}", H160::from_slice(&self.0))
 	}
 }
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use sp_core::Pair;
+	use sp_runtime::traits::IdentifyAccount;
+
+	fn account_of_secret_key(secret_key: [u8; 32]) -> H160 {
+		EthereumSigner::try_from(ecdsa::Pair::from_seed(&secret_key).public())
+			.expect("The public key of a key pair is valid")
+			.into_account()
+	}
+
+	#[test]
+	fn derives_the_metamask_addresses_of_keys() {
+		assert_eq!(
+			account_of_secret_key(hex_literal::hex!(
+				"99b3c12287537e38c90a9219d4cb074a89a16e9cdb20bf85728ebd97c343e342"
+			)),
+			H160::from(hex_literal::hex!(
+				"6be02d1d3665660d22ff9624b7be0551ee1ac91b"
+			))
+		);
+		assert_eq!(
+			account_of_secret_key(hex_literal::hex!(
+				"5fb92d6e98884f76de468fa3f6278f8807c48bebc13595d45af5bdc4da702133"
+			)),
+			H160::from(hex_literal::hex!(
+				"f24ff3a9cf04c71dbc94d0b566f7a27b94566cac"
+			))
+		);
+	}
+
+	#[test]
+	fn invalid_public_keys_have_no_signer() {
+		assert!(EthereumSigner::try_from(ecdsa::Public::from_raw([0u8; 33])).is_err());
+		let mut off_curve = [0xffu8; 33];
+		off_curve[0] = 0x02;
+		assert!(EthereumSigner::try_from(ecdsa::Public::from_raw(off_curve)).is_err());
+	}
+}
@@ -0,0 +1,101 @@
+// Copyright 2019-2021 PureStake Inc.
+// This file is part of Moonbeam.
+
+// Moonbeam is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Moonbeam is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Moonbeam.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Benchmarks of the cryptographic operations of the precompiles in `src/crypto.rs`, printing the
+//! weights to set there. Run them with `cargo bench -p precompiles --bench crypto` on the
+//! reference hardware of the runtime weights, a few times, and keep the median of each weight.
+//!
+//! Each weight is the median time of an operation over a number of samples. The weight of a
+//! message byte is the largest of the extra time ed25519 and sr25519 take to verify a signature
+//! of a long message, per byte of that message.
+
+use sp_core::{ecdsa, ed25519, sr25519, Pair};
+use std::time::Instant;
+
+/// The number of samples each weight is the median of.
+const SAMPLES: usize = 51;
+/// The number of runs of the operation each sample averages.
+const RUNS: u32 = 20;
+/// The length of the message the weight of a message byte is measured with.
+const LONG_MESSAGE_LEN: usize = 65_536;
+/// The weight of a nanosecond.
+const WEIGHT_PER_NANOS: u128 = 1_000;
+
+/// The weight of `operation`.
+fn measure(mut operation: impl FnMut()) -> u128 {
+	// Warm up
+	for _ in 0..RUNS {
+		operation();
+	}
+	let mut samples: Vec<u128> = (0..SAMPLES)
+		.map(|_| {
+			let start = Instant::now();
+			for _ in 0..RUNS {
+				operation();
+			}
+			start.elapsed().as_nanos() / RUNS as u128
+		})
+		.collect();
+	samples.sort_unstable();
+	samples[SAMPLES / 2] * WEIGHT_PER_NANOS
+}
+
+/// The weights of the verification of a signature of an empty message and of a long message.
+fn measure_verify<P: Pair>(verify: fn(&P::Signature, &[u8], &P::Public) -> bool) -> (u128, u128) {
+	let pair = P::from_seed_slice(&[7u8; 32]).expect("the seed is 32 bytes");
+	let long_message = vec![0x2au8; LONG_MESSAGE_LEN];
+	let signature = pair.sign(&[]);
+	let long_signature = pair.sign(&long_message);
+
+	let weight = measure(|| assert!(verify(&signature, &[], &pair.public())));
+	let long_weight = measure(|| assert!(verify(&long_signature, &long_message, &pair.public())));
+	(weight, long_weight)
+}
+
+fn main() {
+	sp_io::TestExternalities::default().execute_with(|| {
+		let (ed25519, ed25519_long) =
+			measure_verify::<ed25519::Pair>(sp_io::crypto::ed25519_verify);
+		let (sr25519, sr25519_long) =
+			measure_verify::<sr25519::Pair>(sp_io::crypto::sr25519_verify);
+
+		// `sign` signs the blake2-256 hash of the message
+		let pair = ecdsa::Pair::from_seed(&[7u8; 32]);
+		let message_hash = sp_io::hashing::blake2_256(b"moonbeam");
+		let signature = pair.sign(b"moonbeam");
+		let recover = measure(|| {
+			sp_io::crypto::secp256k1_ecdsa_recover(signature.as_ref(), &message_hash)
+				.expect("the signature is valid");
+		});
+		let recover_compressed = measure(|| {
+			sp_io::crypto::secp256k1_ecdsa_recover_compressed(signature.as_ref(), &message_hash)
+				.expect("the signature is valid");
+		});
+
+		let message_byte = ed25519_long
+			.saturating_sub(ed25519)
+			.max(sr25519_long.saturating_sub(sr25519))
+			/ LONG_MESSAGE_LEN as u128;
+
+		println!("const ED25519_VERIFY_WEIGHT: Weight = {};", ed25519);
+		println!("const SR25519_VERIFY_WEIGHT: Weight = {};", sr25519);
+		println!(
+			"const ECDSA_RECOVER_WEIGHT: Weight = {};",
+			recover.max(recover_compressed)
+		);
+		println!("const MESSAGE_BYTE_WEIGHT: Weight = {};", message_byte);
+	});
+}
@@ -0,0 +1,54 @@
+// SPDX-License-Identifier: GPL-3.0-only
+pragma solidity >=0.8.0;
+
+/// Interface of the batch precompile at address
+/// 0x0000000000000000000000000000000000000808
+///
+/// Makes a batch of calls with the caller as `msg.sender`. The arrays list the subcalls by index:
+/// `value`, `call_data` and `gas_limit` may be shorter than `to`, in which case the missing values
+/// are 0, the missing call data is empty and subcalls without gas limit (or a gas limit of 0) are
+/// given all the gas left.
+///
+/// Each subcall runs as a transaction of its own, with a call depth starting at 0, so batches
+/// cannot be nested. On top of the gas it uses, each subcall pays the intrinsic gas of a
+/// transaction: 21000 gas, plus 16 gas per non-zero byte and 4 gas per zero byte of its call data.
+/// The nonce of the caller is only bumped once, for the transaction calling the precompile.
+///
+/// The logs of the subcalls and the events of this interface are deposited as `pallet_evm` `Log`
+/// events, and are not part of the Ethereum transaction receipts.
+///
+/// The precompile must be called with CALL, not DELEGATECALL or CALLCODE.
+interface Batch {
+    /// Make all the subcalls, whether or not some of them fail.
+    /// Selector: c8e8ae79
+    function batch_some(
+        address[] memory to,
+        uint256[] memory value,
+        bytes[] memory call_data,
+        uint64[] memory gas_limit
+    ) external;
+
+    /// Make the subcalls until one of them fails, keeping the ones that succeeded before it.
+    /// Selector: 355116e6
+    function batch_some_until_failure(
+        address[] memory to,
+        uint256[] memory value,
+        bytes[] memory call_data,
+        uint64[] memory gas_limit
+    ) external;
+
+    /// Make all the subcalls, reverting all of them if one fails.
+    /// Selector: d30c5f76
+    function batch_all(
+        address[] memory to,
+        uint256[] memory value,
+        bytes[] memory call_data,
+        uint64[] memory gas_limit
+    ) external;
+
+    /// Emitted when the subcall at `index` succeeded
+    event SubcallSucceeded(uint256 index);
+
+    /// Emitted when the subcall at `index` failed
+    event SubcallFailed(uint256 index);
+}
@@ -0,0 +1,39 @@
+// SPDX-License-Identifier: GPL-3.0-only
+pragma solidity >=0.8.0;
+
+/// Interface of the collective precompiles. The council is at address
+/// 0x0000000000000000000000000000000000000803
+interface Collective {
+    /// Whether the address is a member of the collective
+    /// Selector: b0c90f90
+    function is_member(address account) external view returns (bool);
+
+    /// The number of proposals made so far
+    /// Selector: 9c5f8184
+    function proposal_count() external view returns (uint256);
+
+    /// Execute the SCALE encoded call as a single member of the collective
+    /// Selector: 09c5eabe
+    function execute(bytes memory proposal) external;
+
+    /// Propose the SCALE encoded call, executing it right away if the threshold is 1
+    /// Selector: 5f90ebaf
+    function propose(uint256 threshold, bytes memory proposal) external;
+
+    /// Vote on the proposal with this hash and index
+    /// Selector: 2c729fd1
+    function vote(
+        bytes32 proposal_hash,
+        uint256 proposal_index,
+        bool approve
+    ) external;
+
+    /// Close the vote on the proposal, executing it if it was approved
+    /// Selector: 077bab06
+    function close(
+        bytes32 proposal_hash,
+        uint256 proposal_index,
+        uint256 proposal_weight_bound,
+        uint256 length_bound
+    ) external;
+}
@@ -0,0 +1,46 @@
+// SPDX-License-Identifier: GPL-3.0-only
+pragma solidity >=0.8.0;
+
+/// Interface of the ed25519 verification precompile at address
+/// 0x0000000000000000000000000000000000000804
+interface Ed25519 {
+    /// Whether `signature` (64 bytes) is the ed25519 signature of `message` by `public_key`
+    /// Selector: 5bf48e3a
+    function verify(
+        bytes32 public_key,
+        bytes memory message,
+        bytes memory signature
+    ) external view returns (bool);
+}
+
+/// Interface of the sr25519 verification precompile at address
+/// 0x0000000000000000000000000000000000000805
+interface Sr25519 {
+    /// Whether `signature` (64 bytes) is the sr25519 signature of `message` by `public_key`
+    /// Selector: 5bf48e3a
+    function verify(
+        bytes32 public_key,
+        bytes memory message,
+        bytes memory signature
+    ) external view returns (bool);
+}
+
+/// Interface of the secp256k1 public key recovery precompile at address
+/// 0x0000000000000000000000000000000000000806
+interface EcdsaRecover {
+    /// The 64 byte uncompressed public key that made `signature` (65 bytes: r, s and v as 0 or 1)
+    /// of the message hash. Reverts if the signature is invalid.
+    /// Selector: 19045a25
+    function recover(bytes32 message_hash, bytes memory signature)
+        external
+        view
+        returns (bytes memory);
+
+    /// The 33 byte compressed public key that made `signature` of the message hash, as used by
+    /// Substrate ECDSA accounts. Reverts if the signature is invalid.
+    /// Selector: 30e24164
+    function recover_compressed(bytes32 message_hash, bytes memory signature)
+        external
+        view
+        returns (bytes memory);
+}
@@ -0,0 +1,79 @@
+// SPDX-License-Identifier: GPL-3.0-only
+pragma solidity >=0.8.0;
+
+/// Interface of the democracy precompile at address
+/// 0x0000000000000000000000000000000000000802
+interface Democracy {
+    /// The number of public proposals made so far
+    /// Selector: 56fdf547
+    function public_prop_count() external view returns (uint256);
+
+    /// The deposit locked by the public proposal
+    /// Selector: a30305e9
+    function deposit_of(uint256 prop_index) external view returns (uint256);
+
+    /// The lowest referendum index that has not been baked yet
+    /// Selector: 0388f282
+    function lowest_unbaked() external view returns (uint256);
+
+    /// The details of an ongoing referendum. The threshold is 0 for a super majority approve,
+    /// 1 for a super majority against and 2 for a simple majority.
+    /// Selector: 8b93d11a
+    function ongoing_referendum_info(uint256 ref_index)
+        external
+        view
+        returns (
+            uint256 end,
+            bytes32 proposal_hash,
+            uint256 threshold,
+            uint256 delay
+        );
+
+    /// Whether a finished referendum was approved, and when it ended
+    /// Selector: b1fd383f
+    function finished_referendum_info(uint256 ref_index)
+        external
+        view
+        returns (bool approved, uint256 end);
+
+    /// Make a public proposal of the preimage with this hash, locking `value`
+    /// Selector: 7824e7d1
+    function propose(bytes32 proposal_hash, uint256 value) external;
+
+    /// Second the public proposal. The bound is at least the number of seconds it already has.
+    /// Selector: c7a76601
+    function second(uint256 prop_index, uint256 seconds_upper_bound) external;
+
+    /// Vote in a referendum with `vote_amount` and a conviction between 0 and 6
+    /// Selector: 3f3c21cc
+    function standard_vote(
+        uint256 ref_index,
+        bool aye,
+        uint256 vote_amount,
+        uint256 conviction
+    ) external;
+
+    /// Vote in a referendum with `aye` for and `nay` against, without conviction
+    /// Selector: bd584d94
+    function split_vote(
+        uint256 ref_index,
+        uint256 aye,
+        uint256 nay
+    ) external;
+
+    /// Delegate the voting power of `amount` with a conviction between 0 and 6
+    /// Selector: 0185921e
+    function delegate(
+        address representative,
+        uint256 conviction,
+        uint256 amount
+    ) external;
+
+    /// Stop delegating the voting power
+    /// Selector: 92ab89bb
+    function undelegate() external;
+
+    /// Remove the vote in a referendum
+    /// Selector: 2042f50b
+    function remove_vote(uint256 ref_index) external;
+}
@@ -0,0 +1,52 @@
+// SPDX-License-Identifier: GPL-3.0-only
+pragma solidity >=0.8.0;
+
+/// Interface of the native currency ERC-20 precompile at address
+/// 0x0000000000000000000000000000000000000801
+///
+/// The precompile must be called with CALL, not DELEGATECALL or CALLCODE.
+/// The precompile does not emit the `Transfer` and `Approval` events of ERC-20 tokens.
+interface IERC20 {
+    /// The name of the token
+    /// Selector: 06fdde03
+    function name() external view returns (string memory);
+
+    /// The symbol of the token
+    /// Selector: 95d89b41
+    function symbol() external view returns (string memory);
+
+    /// The number of decimals of the token amounts
+    /// Selector: 313ce567
+    function decimals() external view returns (uint8);
+
+    /// The total issuance of the token
+    /// Selector: 18160ddd
+    function totalSupply() external view returns (uint256);
+
+    /// The free balance of `owner`
+    /// Selector: 70a08231
+    function balanceOf(address owner) external view returns (uint256);
+
+    /// How much `spender` may still transfer on behalf of `owner`
+    /// Selector: dd62ed3e
+    function allowance(address owner, address spender)
+        external
+        view
+        returns (uint256);
+
+    /// Transfer `value` from the caller to `to`
+    /// Selector: a9059cbb
+    function transfer(address to, uint256 value) external returns (bool);
+
+    /// Allow `spender` to transfer up to `value` on behalf of the caller
+    /// Selector: 095ea7b3
+    function approve(address spender, uint256 value) external returns (bool);
+
+    /// Transfer `value` from `from` to `to` out of the caller's allowance
+    /// Selector: 23b872dd
+    function transferFrom(
+        address from,
+        address to,
+        uint256 value
+    ) external returns (bool);
+}
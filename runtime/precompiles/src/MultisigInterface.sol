@@ -0,0 +1,56 @@
+// SPDX-License-Identifier: GPL-3.0-only
+pragma solidity >=0.8.0;
+
+/// Interface of the multisig precompile at address
+/// 0x0000000000000000000000000000000000000809
+///
+/// Multisig accounts are approved by `threshold` of their signatories, the caller being one of
+/// them. The signatories can be given in any order, and `other_signatories` leaves out the caller.
+///
+/// An operation is identified by the block number and extrinsic index of its first approval, its
+/// timepoint. The first approval of an operation gives a block number of 0, as it has no timepoint
+/// yet.
+interface Multisig {
+    /// Approve an operation dispatching `call`, the SCALE encoded call, from the multisig
+    /// account. The call is dispatched by the approval reaching the threshold, with at most
+    /// `max_weight`. Only the calls the dispatch precompile accepts can be approved this way;
+    /// others must be approved with `approve_as_multi` and dispatched by a Substrate signatory.
+    /// Selector: ff3a5c35
+    function as_multi(
+        uint16 threshold,
+        address[] memory other_signatories,
+        uint32 timepoint_height,
+        uint32 timepoint_index,
+        bytes memory call,
+        uint64 max_weight
+    ) external;
+
+    /// Approve an operation by the blake2-256 hash of its call, without dispatching it.
+    /// Selector: e7c54377
+    function approve_as_multi(
+        uint16 threshold,
+        address[] memory other_signatories,
+        uint32 timepoint_height,
+        uint32 timepoint_index,
+        bytes32 call_hash,
+        uint64 max_weight
+    ) external;
+
+    /// Cancel an operation the caller approved first, returning its deposit.
+    /// Selector: fadf1450
+    function cancel_as_multi(
+        uint16 threshold,
+        address[] memory other_signatories,
+        uint32 timepoint_height,
+        uint32 timepoint_index,
+        bytes32 call_hash
+    ) external;
+
+    /// The multisig account of `signatories` and `threshold`: the first 20 bytes of
+    /// blake2_256("modlpy/utilisuba" ++ SCALE(sorted signatories) ++ SCALE(threshold)).
+    /// Selector: 7bee4775
+    function multisig_address(address[] memory signatories, uint16 threshold)
+        external
+        view
+        returns (address);
+}
@@ -0,0 +1,34 @@
+// SPDX-License-Identifier: GPL-3.0-only
+pragma solidity >=0.8.0;
+
+/// Interface of the randomness precompile at address
+/// 0x0000000000000000000000000000000000000807
+///
+/// The randomness is taken from the BABE randomness of the relay chain. Relay chain block authors
+/// and collators can bias it by withholding blocks, so it must not be used where they could gain
+/// more from biasing it than the reward of a block.
+///
+/// Requests are made and fulfilled on behalf of the caller, so it must be called with CALL.
+interface Randomness {
+    /// The BABE randomness of the current relay chain epoch. It is known from the start of the
+    /// previous epoch, so it is predictable.
+    /// Selector: 72de708e
+    function current_epoch_randomness() external view returns (bytes32);
+
+    /// Request the randomness of a future block, at most `MaxRequestDelay` blocks ahead.
+    /// Reserves `RequestDeposit` from the caller until the request is fulfilled or purged.
+    /// Returns the id of the request, which only the caller can fulfil.
+    /// Selector: 915da532
+    function request_randomness(uint256 block) external returns (uint256 request_id);
+
+    /// Fulfil a request of the caller whose block was built, returning its randomness and the
+    /// deposit. The randomness is derived from the VRF output of the author of the relay parent of
+    /// the block, or of the next block with one. Requests expire `RequestExpiry` blocks after
+    /// their block.
+    /// Selector: 88444e93
+    function fulfil_randomness(uint256 request_id) external returns (bytes32);
+
+    /// Whether the request can be fulfilled
+    /// Selector: 2bbfe85e
+    function is_ready(uint256 request_id) external view returns (bool);
+}
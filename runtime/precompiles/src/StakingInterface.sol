@@ -0,0 +1,83 @@
+// SPDX-License-Identifier: GPL-3.0-only
+pragma solidity >=0.8.0;
+
+/// Interface of the parachain staking precompile at address
+/// 0x0000000000000000000000000000000000000800
+interface ParachainStaking {
+    /// Whether the address is a nominator
+    /// Selector: 8e5080e7
+    function is_nominator(address nominator) external view returns (bool);
+
+    /// Whether the address is a collator candidate
+    /// Selector: 8545c833
+    function is_candidate(address candidate) external view returns (bool);
+
+    /// Whether the address is one of the collators selected for the current round
+    /// Selector: 8f6d27c7
+    function is_selected_candidate(address candidate)
+        external
+        view
+        returns (bool);
+
+    /// The number of nominations of the collator candidate
+    /// Selector: 0ad6a7be
+    function collator_nomination_count(address candidate)
+        external
+        view
+        returns (uint256);
+
+    /// The current round index, its first block and its length in blocks
+    /// Selector: f8aa8ddd
+    function round_info()
+        external
+        view
+        returns (
+            uint256 current,
+            uint256 first,
+            uint256 length
+        );
+
+    /// Join the set of collator candidates, bonding `amount`
+    /// Selector: ad76ed5a
+    function join_candidates(uint256 amount) external;
+
+    /// Leave the set of collator candidates
+    /// Selector: b7694219
+    function leave_candidates() external;
+
+    /// Temporarily stop being selected as a collator
+    /// Selector: 767e0450
+    function go_offline() external;
+
+    /// Be selected as a collator again after going offline
+    /// Selector: d2f73ceb
+    function go_online() external;
+
+    /// Increase the bond of the calling collator candidate
+    /// Selector: c57bd3a8
+    function candidate_bond_more(uint256 more) external;
+
+    /// Decrease the bond of the calling collator candidate
+    /// Selector: 289b6ba7
+    function candidate_bond_less(uint256 less) external;
+
+    /// Nominate the collator candidate with `amount`, joining the nominators if needed
+    /// Selector: 82f2c8df
+    function nominate(address collator, uint256 amount) external;
+
+    /// Revoke all the nominations of the caller
+    /// Selector: e8d68a37
+    function leave_nominators() external;
+
+    /// Revoke the nomination of the collator candidate
+    /// Selector: 4b65c34b
+    function revoke_nomination(address collator) external;
+
+    /// Increase the nomination of the collator candidate
+    /// Selector: 971d44c8
+    function nominator_bond_more(address candidate, uint256 more) external;
+
+    /// Decrease the nomination of the collator candidate
+    /// Selector: f6a52569
+    function nominator_bond_less(address candidate, uint256 less) external;
+}
@@ -0,0 +1,18 @@
+// SPDX-License-Identifier: GPL-3.0-only
+pragma solidity >=0.8.0;
+
+/// Interface of the treasury precompile at address
+/// 0x000000000000000000000000000000000000080a
+///
+/// The treasury collects part of the transaction fees and the slashed funds. The council approves
+/// spending them.
+interface Treasury {
+    /// The funds available for spending, which leave out the existential deposit of the treasury
+    /// account.
+    /// Selector: b69ef8a8
+    function balance() external view returns (uint256);
+
+    /// The account of the treasury.
+    /// Selector: 5dab2420
+    function account() external view returns (address);
+}
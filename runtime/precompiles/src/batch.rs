@@ -0,0 +1,245 @@
+// Copyright 2019-2021 PureStake Inc.
+// This file is part of Moonbeam.
+
+// Moonbeam is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Moonbeam is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Moonbeam.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Precompile making a list of calls on behalf of its caller, through the Solidity interface in
+//! `BatchInterface.sol`. The subcalls keep the caller as `msg.sender`, which lets an EOA group
+//! several calls in one transaction.
+//!
+//! The precompile interface of this `pallet_evm` version cannot make calls from within the
+//! running EVM, so each subcall runs in its own EVM execution through the `pallet_evm` runner,
+//! and pays the intrinsic gas of a transaction on top of what it uses. The nonce the runner bumps
+//! is restored, as the subcalls are part of the transaction of the caller. The EVM call depth
+//! starts over in each of these executions, so batches cannot be nested: a batch called while
+//! another one is executing fails, which keeps them from recursing without bound.
+//!
+//! For the same reason, the logs of the subcalls and the `SubcallSucceeded`/`SubcallFailed` logs
+//! of the precompile are deposited as `pallet_evm` `Log` events rather than returned to the EVM,
+//! so they are not part of the Ethereum transaction receipts.
+
+use evm::{backend::Log, Context, ExitError, ExitReason, ExitSucceed};
+use frame_support::storage::{unhashed, with_transaction, TransactionOutcome};
+use pallet_evm::{AddressMapping, Runner};
+use precompile_utils::{
+	error, selectors, Bytes, EvmDataReader, EvmDataWriter, EvmResult, Gasometer,
+};
+use sp_core::{H160, H256, U256};
+use sp_std::{marker::PhantomData, vec, vec::Vec};
+
+selectors! {
+	/// The functions of `BatchInterface.sol`.
+	enum Action {
+		BatchSome = 0xc8e8ae79,
+		BatchSomeUntilFailure = 0x355116e6,
+		BatchAll = 0xd30c5f76,
+	}
+}
+
+/// Keccak-256 of `SubcallSucceeded(uint256)`.
+const SUBCALL_SUCCEEDED_EVENT: [u8; 32] =
+	hex_literal::hex!("bf855484633929c3d6688eb3caf8eff910fb4bef030a8d7dbc9390d26759714d");
+/// Keccak-256 of `SubcallFailed(uint256)`.
+const SUBCALL_FAILED_EVENT: [u8; 32] =
+	hex_literal::hex!("dbc5d06f4f877f959b1ff12d2161cdd693fa8e442ee53f1790b2804b24881f05");
+
+/// The storage key set while a batch is executing. It is removed when the batch returns, so it
+/// never outlives the EVM execution of the batch.
+const BATCH_IN_PROGRESS_KEY: &[u8] = b":moonbeam:batch_in_progress";
+
+/// What to do with the rest of the batch when a subcall fails.
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Mode {
+	/// Carry on with the next subcall.
+	BatchSome,
+	/// Stop, keeping the subcalls that already succeeded.
+	BatchSomeUntilFailure,
+	/// Revert the whole batch.
+	BatchAll,
+}
+
+/// A subcall of a batch.
+struct Subcall {
+	to: H160,
+	value: U256,
+	call_data: Vec<u8>,
+	/// The gas given to the subcall, or 0 to give it all the gas left.
+	gas_limit: u64,
+}
+
+/// Makes a batch of calls with the caller as `msg.sender`.
+///
+/// It must be called with `CALL`: it would otherwise make the calls on behalf of the caller of
+/// the contract using `DELEGATECALL` or `CALLCODE`, which `MoonbeamPrecompiles` prevents.
+pub struct BatchPrecompile<Runtime>(PhantomData<Runtime>);
+
+impl<Runtime> pallet_evm::Precompile for BatchPrecompile<Runtime>
+where
+	Runtime: pallet_evm::Config,
+{
+	fn execute(
+		input: &[u8],
+		target_gas: Option<u64>,
+		context: &Context,
+	) -> Result<(ExitSucceed, Vec<u8>, u64), ExitError> {
+		let (mut input, action) = EvmDataReader::new_with_selector::<Action>(input)?;
+		let mode = match action {
+			Action::BatchSome => Mode::BatchSome,
+			Action::BatchSomeUntilFailure => Mode::BatchSomeUntilFailure,
+			Action::BatchAll => Mode::BatchAll,
+		};
+		let subcalls = Self::read_subcalls(&mut input)?;
+		let mut gasometer = Gasometer::new(target_gas);
+
+		if unhashed::exists(BATCH_IN_PROGRESS_KEY) {
+			return Err(error("batches cannot be nested"));
+		}
+		unhashed::put(BATCH_IN_PROGRESS_KEY, &true);
+		let result = with_transaction(|| {
+			let result = Self::batch(context, mode, subcalls, &mut gasometer);
+			if result.is_ok() {
+				TransactionOutcome::Commit(result)
+			} else {
+				TransactionOutcome::Rollback(result)
+			}
+		});
+		unhashed::kill(BATCH_IN_PROGRESS_KEY);
+		result?;
+
+		Ok((
+			ExitSucceed::Returned,
+			EvmDataWriter::new().build(),
+			gasometer.used_gas(),
+		))
+	}
+}
+
+impl<Runtime> BatchPrecompile<Runtime>
+where
+	Runtime: pallet_evm::Config,
+{
+	/// Read the subcalls from the four arrays of the input. Missing values and call data are
+	/// zero and empty, and subcalls without a gas limit are given all the gas left.
+	fn read_subcalls(input: &mut EvmDataReader) -> EvmResult<Vec<Subcall>> {
+		let to = input.read::<Vec<H160>>()?;
+		let value = input.read::<Vec<U256>>()?;
+		let call_data = input.read::<Vec<Bytes>>()?;
+		let gas_limit = input.read::<Vec<u64>>()?;
+
+		if value.len() > to.len() || call_data.len() > to.len() || gas_limit.len() > to.len() {
+			return Err(error("more values, call data or gas limits than addresses"));
+		}
+
+		let mut value = value.into_iter();
+		let mut call_data = call_data.into_iter();
+		let mut gas_limit = gas_limit.into_iter();
+		Ok(to
+			.into_iter()
+			.map(|to| Subcall {
+				to,
+				value: value.next().unwrap_or_default(),
+				call_data: call_data.next().map(|data| data.0).unwrap_or_default(),
+				gas_limit: gas_limit.next().unwrap_or_default(),
+			})
+			.collect())
+	}
+
+	/// Make the subcalls in order. Fails, so that the whole batch is reverted, when a subcall of
+	/// a `BatchAll` fails.
+	fn batch(
+		context: &Context,
+		mode: Mode,
+		subcalls: Vec<Subcall>,
+		gasometer: &mut Gasometer,
+	) -> EvmResult {
+		for (index, subcall) in subcalls.into_iter().enumerate() {
+			let gas_limit = match (subcall.gas_limit, gasometer.remaining_gas()) {
+				(0, Some(remaining_gas)) => remaining_gas,
+				(0, None) => return Err(error("subcalls need a gas limit")),
+				(gas_limit, Some(remaining_gas)) if gas_limit > remaining_gas => {
+					return Err(ExitError::OutOfGas)
+				}
+				(gas_limit, _) => gas_limit,
+			};
+
+			let (succeeded, used_gas) = Self::subcall(context.caller, subcall, gas_limit);
+			gasometer.record_cost(used_gas)?;
+
+			let event = if succeeded {
+				SUBCALL_SUCCEEDED_EVENT
+			} else {
+				SUBCALL_FAILED_EVENT
+			};
+			Self::log(context.address, event, index);
+
+			if !succeeded {
+				match mode {
+					Mode::BatchSome => (),
+					Mode::BatchSomeUntilFailure => break,
+					Mode::BatchAll => return Err(error("a subcall of the batch failed")),
+				}
+			}
+		}
+
+		Ok(())
+	}
+
+	/// Make `subcall` on behalf of `caller`, returning whether it succeeded and the gas it used.
+	/// The changes of a failed subcall are reverted.
+	fn subcall(caller: H160, subcall: Subcall, gas_limit: u64) -> (bool, u64) {
+		let account = Runtime::AddressMapping::into_account_id(caller);
+
+		with_transaction(|| {
+			let nonce = frame_system::Pallet::<Runtime>::account_nonce(&account);
+			let info = Runtime::Runner::call(
+				caller,
+				subcall.to,
+				subcall.call_data,
+				subcall.value,
+				gas_limit,
+				None,
+				None,
+				Runtime::config(),
+			);
+			// Only restore the nonce of an account the subcall left in place, without recreating
+			// an account it reaped.
+			frame_system::Account::<Runtime>::mutate_exists(&account, |info| {
+				if let Some(info) = info {
+					info.nonce = nonce;
+				}
+			});
+
+			match info {
+				Ok(info) if matches!(info.exit_reason, ExitReason::Succeed(_)) => {
+					TransactionOutcome::Commit((true, info.used_gas.low_u64()))
+				}
+				Ok(info) => TransactionOutcome::Rollback((false, info.used_gas.low_u64())),
+				// The subcall could not start, the caller cannot afford its value.
+				Err(_) => TransactionOutcome::Rollback((false, 0)),
+			}
+		})
+	}
+
+	/// Deposit the log of the outcome of the subcall at `index`.
+	fn log(address: H160, event: [u8; 32], index: usize) {
+		let log = Log {
+			address,
+			topics: vec![H256(event)],
+			data: EvmDataWriter::new().write(U256::from(index)).build(),
+		};
+		frame_system::Pallet::<Runtime>::deposit_event(
+			<Runtime as pallet_evm::Config>::Event::from(pallet_evm::Event::<Runtime>::Log(log)),
+		);
+	}
+}
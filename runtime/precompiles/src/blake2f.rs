@@ -0,0 +1,255 @@
+// Copyright 2019-2021 PureStake Inc.
+// This file is part of Moonbeam.
+
+// Moonbeam is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Moonbeam is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Moonbeam.  If not, see <http://www.gnu.org/licenses/>.
+
+//! The BLAKE2 compression function F precompile of EIP-152
+//! (https://eips.ethereum.org/EIPS/eip-152), at address 9 since Istanbul.
+
+use evm::{Context, ExitError, ExitSucceed};
+use pallet_evm::Precompile;
+use precompile_utils::{error, Gasometer};
+use sp_std::{convert::TryInto, vec::Vec};
+
+/// The input is the number of rounds (4 bytes), the state vector `h` (64 bytes), the message
+/// block `m` (128 bytes), the offset counters `t` (16 bytes) and the final block flag (1 byte).
+const INPUT_LENGTH: usize = 213;
+
+/// Gas cost of each round of the compression.
+const GAS_PER_ROUND: u64 = 1;
+
+const IV: [u64; 8] = [
+	0x6a09e667f3bcc908,
+	0xbb67ae8584caa73b,
+	0x3c6ef372fe94f82b,
+	0xa54ff53a5f1d36f1,
+	0x510e527fade682d1,
+	0x9b05688c2b3e6c1f,
+	0x1f83d9abfb41bd6b,
+	0x5be0cd19137e2179,
+];
+
+/// The message word permutation of each round, which repeats every ten rounds.
+const SIGMA: [[usize; 16]; 10] = [
+	[0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15],
+	[14, 10, 4, 8, 9, 15, 13, 6, 1, 12, 0, 2, 11, 7, 5, 3],
+	[11, 8, 12, 0, 5, 2, 15, 13, 10, 14, 3, 6, 7, 1, 9, 4],
+	[7, 9, 3, 1, 13, 12, 11, 14, 2, 6, 5, 10, 4, 0, 15, 8],
+	[9, 0, 5, 7, 2, 4, 10, 15, 14, 1, 11, 12, 6, 8, 3, 13],
+	[2, 12, 6, 10, 0, 11, 8, 3, 4, 13, 7, 5, 15, 14, 1, 9],
+	[12, 5, 1, 15, 14, 13, 4, 10, 0, 7, 6, 3, 9, 2, 8, 11],
+	[13, 11, 7, 14, 12, 1, 3, 9, 5, 0, 15, 4, 8, 6, 2, 10],
+	[6, 15, 14, 9, 11, 3, 0, 8, 12, 2, 13, 7, 1, 4, 10, 5],
+	[10, 2, 8, 4, 7, 6, 1, 5, 15, 11, 9, 14, 3, 12, 13, 0],
+];
+
+/// The BLAKE2b mixing function.
+fn g(v: &mut [u64; 16], a: usize, b: usize, c: usize, d: usize, x: u64, y: u64) {
+	v[a] = v[a].wrapping_add(v[b]).wrapping_add(x);
+	v[d] = (v[d] ^ v[a]).rotate_right(32);
+	v[c] = v[c].wrapping_add(v[d]);
+	v[b] = (v[b] ^ v[c]).rotate_right(24);
+	v[a] = v[a].wrapping_add(v[b]).wrapping_add(y);
+	v[d] = (v[d] ^ v[a]).rotate_right(16);
+	v[c] = v[c].wrapping_add(v[d]);
+	v[b] = (v[b] ^ v[c]).rotate_right(63);
+}
+
+/// The BLAKE2b compression function, with a configurable number of rounds.
+fn compress(h: &mut [u64; 8], m: &[u64; 16], t: [u64; 2], last: bool, rounds: u32) {
+	let mut v = [0u64; 16];
+	v[..8].copy_from_slice(h);
+	v[8..].copy_from_slice(&IV);
+	v[12] ^= t[0];
+	v[13] ^= t[1];
+	if last {
+		v[14] = !v[14];
+	}
+
+	for round in 0..rounds as usize {
+		let s = &SIGMA[round % 10];
+		g(&mut v, 0, 4, 8, 12, m[s[0]], m[s[1]]);
+		g(&mut v, 1, 5, 9, 13, m[s[2]], m[s[3]]);
+		g(&mut v, 2, 6, 10, 14, m[s[4]], m[s[5]]);
+		g(&mut v, 3, 7, 11, 15, m[s[6]], m[s[7]]);
+		g(&mut v, 0, 5, 10, 15, m[s[8]], m[s[9]]);
+		g(&mut v, 1, 6, 11, 12, m[s[10]], m[s[11]]);
+		g(&mut v, 2, 7, 8, 13, m[s[12]], m[s[13]]);
+		g(&mut v, 3, 4, 9, 14, m[s[14]], m[s[15]]);
+	}
+
+	for i in 0..8 {
+		h[i] ^= v[i] ^ v[i + 8];
+	}
+}
+
+/// Read little endian words from `bytes` into `words`.
+fn read_words(bytes: &[u8], words: &mut [u64]) {
+	for (word, chunk) in words.iter_mut().zip(bytes.chunks_exact(8)) {
+		*word = u64::from_le_bytes(chunk.try_into().expect("chunks are 8 bytes long; qed"));
+	}
+}
+
+/// The BLAKE2 F precompile. Costs one gas per round.
+pub struct Blake2F;
+
+impl Precompile for Blake2F {
+	fn execute(
+		input: &[u8],
+		target_gas: Option<u64>,
+		_context: &Context,
+	) -> Result<(ExitSucceed, Vec<u8>, u64), ExitError> {
+		if input.len() != INPUT_LENGTH {
+			return Err(error("input length for Blake2F must be exactly 213 bytes"));
+		}
+
+		let rounds = u32::from_be_bytes(input[..4].try_into().expect("input is 213 bytes; qed"));
+		let mut gasometer = Gasometer::new(target_gas);
+		gasometer.record_cost(u64::from(rounds) * GAS_PER_ROUND)?;
+
+		let mut h = [0u64; 8];
+		let mut m = [0u64; 16];
+		let mut t = [0u64; 2];
+		read_words(&input[4..68], &mut h);
+		read_words(&input[68..196], &mut m);
+		read_words(&input[196..212], &mut t);
+		let last = match input[212] {
+			0 => false,
+			1 => true,
+			_ => return Err(error("final block flag for Blake2F must be 0 or 1")),
+		};
+
+		compress(&mut h, &m, t, last, rounds);
+
+		let output = h
+			.iter()
+			.flat_map(|word| word.to_le_bytes().to_vec())
+			.collect();
+		Ok((ExitSucceed::Returned, output, gasometer.used_gas()))
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use hex_literal::hex;
+	use sp_core::{H160, U256};
+
+	/// The BLAKE2b-512 hash of "abc" in 12 rounds, test vector 4 of EIP-152.
+	const INPUT: [u8; 213] = hex!(
+		"0000000c
+		48c9bdf267e6096a3ba7ca8485ae67bb2bf894fe72f36e3cf1361d5f3af54fa5
+		d182e6ad7f520e511f6c3e2b8c68059b6bbd41fbabd9831f79217e1319cde05b
+		6162630000000000000000000000000000000000000000000000000000000000
+		0000000000000000000000000000000000000000000000000000000000000000
+		0000000000000000000000000000000000000000000000000000000000000000
+		0000000000000000000000000000000000000000000000000000000000000000
+		03000000000000000000000000000000
+		01"
+	);
+
+	fn execute(input: &[u8], target_gas: Option<u64>) -> Result<(Vec<u8>, u64), ExitError> {
+		let context = Context {
+			address: H160::from_low_u64_be(9),
+			caller: H160::zero(),
+			apparent_value: U256::zero(),
+		};
+		Blake2F::execute(input, target_gas, &context).map(|(_, output, cost)| (output, cost))
+	}
+
+	fn with_rounds_and_flag(rounds: u32, flag: u8) -> Vec<u8> {
+		let mut input = INPUT.to_vec();
+		input[..4].copy_from_slice(&rounds.to_be_bytes());
+		input[212] = flag;
+		input
+	}
+
+	#[test]
+	fn rejects_wrong_input_length() {
+		assert!(execute(&[], None).is_err());
+		assert!(execute(&INPUT[..212], None).is_err());
+		assert!(execute(&[&INPUT[..], &[0]].concat(), None).is_err());
+	}
+
+	#[test]
+	fn rejects_invalid_final_block_flag() {
+		assert!(execute(&with_rounds_and_flag(12, 2), None).is_err());
+	}
+
+	#[test]
+	fn hashes_abc() {
+		assert_eq!(
+			execute(&INPUT, None).unwrap(),
+			(
+				hex!(
+					"ba80a53f981c4d0d6a2797b69f12f6e94c212f14685ac4b74b12bb6fdbffa2d1
+					7d87c5392aab792dc252d5de4533cc9518d38aa8dbf1925ab92386edd4009923"
+				)
+				.to_vec(),
+				12
+			)
+		);
+	}
+
+	#[test]
+	fn compresses_non_final_block() {
+		assert_eq!(
+			execute(&with_rounds_and_flag(12, 0), None).unwrap().0,
+			hex!(
+				"75ab69d3190a562c51aef8d88f1c2775876944407270c42c9844252c26d28752
+				98743e7f6d5ea2f2d3e8d226039cd31b4e426ac4f2d3d666a610c2116fde4735"
+			)
+			.to_vec()
+		);
+	}
+
+	#[test]
+	fn compresses_with_any_number_of_rounds() {
+		assert_eq!(
+			execute(&with_rounds_and_flag(1, 1), None).unwrap(),
+			(
+				hex!(
+					"b63a380cb2897d521994a85234ee2c181b5f844d2c624c002677e9703449d2fb
+					a551b3a8333bcdf5f2f7e08993d53923de3d64fcc68c034e717b9293fed7a421"
+				)
+				.to_vec(),
+				1
+			)
+		);
+		assert_eq!(
+			execute(&with_rounds_and_flag(0, 1), None).unwrap(),
+			(
+				hex!(
+					"08c9bcf367e6096a3ba7ca8485ae67bb2bf894fe72f36e3cf1361d5f3af54fa5
+					d282e6ad7f520e511f6c3e2b8c68059b9442be0454267ce079217e1319cde05b"
+				)
+				.to_vec(),
+				0
+			)
+		);
+	}
+
+	#[test]
+	fn charges_a_gas_per_round() {
+		assert!(matches!(
+			execute(&INPUT, Some(11)),
+			Err(ExitError::OutOfGas)
+		));
+		// Too many rounds to compute are rejected before doing any work.
+		assert!(matches!(
+			execute(&with_rounds_and_flag(u32::max_value(), 1), Some(1_000_000)),
+			Err(ExitError::OutOfGas)
+		));
+	}
+}
@@ -0,0 +1,130 @@
+// Copyright 2019-2021 PureStake Inc.
+// This file is part of Moonbeam.
+
+// Moonbeam is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Moonbeam is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Moonbeam.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Precompile to interact with an instance of the collective pallet, such as the council, from
+//! the EVM, through the Solidity interface in `CollectiveInterface.sol`.
+
+use codec::Decode;
+use evm::{Context, ExitError, ExitSucceed};
+use frame_support::dispatch::{Dispatchable, GetDispatchInfo, PostDispatchInfo};
+use pallet_evm::{AddressMapping, Precompile};
+use precompile_utils::{
+	error, selectors, Bytes, EvmDataReader, EvmDataWriter, EvmResult, Gasometer, RuntimeHelper,
+};
+use sp_core::{H160, H256};
+use sp_std::{boxed::Box, marker::PhantomData, vec::Vec};
+
+selectors! {
+	/// The functions of `CollectiveInterface.sol`.
+	enum Action {
+		IsMember = 0xb0c90f90,
+		ProposalCount = 0x9c5f8184,
+		Execute = 0x09c5eabe,
+		Propose = 0x5f90ebaf,
+		Vote = 0x2c729fd1,
+		Close = 0x077bab06,
+	}
+}
+
+/// Exposes the `Instance` of the collective pallet to the EVM. Proposals are passed as SCALE
+/// encoded calls.
+pub struct CollectiveWrapper<Runtime, Instance>(PhantomData<(Runtime, Instance)>);
+
+impl<Runtime, Instance> Precompile for CollectiveWrapper<Runtime, Instance>
+where
+	Instance: pallet_collective::Instance,
+	Runtime: pallet_collective::Config<Instance> + pallet_evm::Config,
+	Runtime::Call: Dispatchable<PostInfo = PostDispatchInfo>
+		+ GetDispatchInfo
+		+ From<pallet_collective::Call<Runtime, Instance>>,
+	<Runtime::Call as Dispatchable>::Origin: From<Option<Runtime::AccountId>>,
+	Runtime::Hash: From<H256>,
+{
+	fn execute(
+		input: &[u8],
+		target_gas: Option<u64>,
+		context: &Context,
+	) -> Result<(ExitSucceed, Vec<u8>, u64), ExitError> {
+		let (mut input, action) = EvmDataReader::new_with_selector::<Action>(input)?;
+		let mut gasometer = Gasometer::new(target_gas);
+
+		let call = match action {
+			// View functions
+			Action::IsMember => {
+				let account = Runtime::AddressMapping::into_account_id(input.read::<H160>()?);
+				gasometer.record_cost(RuntimeHelper::<Runtime>::db_read_gas_cost())?;
+				let is_member = pallet_collective::Pallet::<Runtime, Instance>::is_member(&account);
+				return Self::view(EvmDataWriter::new().write(is_member), &gasometer);
+			}
+			Action::ProposalCount => {
+				gasometer.record_cost(RuntimeHelper::<Runtime>::db_read_gas_cost())?;
+				let count = pallet_collective::Pallet::<Runtime, Instance>::proposal_count();
+				return Self::view(EvmDataWriter::new().write(count), &gasometer);
+			}
+
+			// Calls
+			Action::Execute => {
+				let (proposal, length) = Self::proposal(&mut input)?;
+				pallet_collective::Call::<Runtime, Instance>::execute(proposal, length)
+			}
+			Action::Propose => {
+				let threshold = input.read()?;
+				let (proposal, length) = Self::proposal(&mut input)?;
+				pallet_collective::Call::<Runtime, Instance>::propose(threshold, proposal, length)
+			}
+			Action::Vote => pallet_collective::Call::<Runtime, Instance>::vote(
+				input.read::<H256>()?.into(),
+				input.read()?,
+				input.read()?,
+			),
+			Action::Close => pallet_collective::Call::<Runtime, Instance>::close(
+				input.read::<H256>()?.into(),
+				input.read()?,
+				input.read()?,
+				input.read()?,
+			),
+		};
+
+		RuntimeHelper::<Runtime>::try_dispatch(context.caller, call, &mut gasometer)?;
+		Ok((ExitSucceed::Returned, Vec::new(), gasometer.used_gas()))
+	}
+}
+
+impl<Runtime, Instance> CollectiveWrapper<Runtime, Instance>
+where
+	Instance: pallet_collective::Instance,
+	Runtime: pallet_collective::Config<Instance> + pallet_evm::Config,
+{
+	/// Read a SCALE encoded proposal, returning it with its encoded length.
+	fn proposal(
+		input: &mut EvmDataReader,
+	) -> EvmResult<(
+		Box<<Runtime as pallet_collective::Config<Instance>>::Proposal>,
+		u32,
+	)> {
+		let encoded = input.read::<Bytes>()?.0;
+		let proposal =
+			Decode::decode(&mut &encoded[..]).map_err(|_| error("proposal is not a valid call"))?;
+		Ok((Box::new(proposal), encoded.len() as u32))
+	}
+
+	fn view(
+		output: EvmDataWriter,
+		gasometer: &Gasometer,
+	) -> Result<(ExitSucceed, Vec<u8>, u64), ExitError> {
+		Ok((ExitSucceed::Returned, output.build(), gasometer.used_gas()))
+	}
+}
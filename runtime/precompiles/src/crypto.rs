@@ -0,0 +1,170 @@
+// Copyright 2019-2021 PureStake Inc.
+// This file is part of Moonbeam.
+
+// Moonbeam is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Moonbeam is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Moonbeam.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Precompiles exposing the signature schemes of Substrate to the EVM, through the Solidity
+//! interfaces in `CryptoInterface.sol`: ed25519 and sr25519 verification, and recovery of the
+//! secp256k1 public key that signed any 32 byte message hash.
+//!
+//! Their gas cost is the weight of the cryptographic operation, converted with the
+//! `GasWeightMapping` of the runtime. The weights are the execution times of the `sp_io::crypto`
+//! functions measured by the `crypto` benchmark of this crate (`benches/crypto.rs`), which prints
+//! the constants below.
+
+use evm::{Context, ExitError, ExitSucceed};
+use frame_support::weights::Weight;
+use pallet_evm::{GasWeightMapping, Precompile};
+use precompile_utils::{error, selectors, Bytes, EvmDataReader, EvmDataWriter, Gasometer};
+use sp_core::{ed25519, sr25519, H256};
+use sp_std::{convert::TryFrom, marker::PhantomData, vec::Vec};
+
+/// Weight of an ed25519 signature verification.
+pub const ED25519_VERIFY_WEIGHT: Weight = 91_000_000;
+/// Weight of an sr25519 signature verification.
+pub const SR25519_VERIFY_WEIGHT: Weight = 105_000_000;
+/// Weight of the recovery of a secp256k1 public key.
+pub const ECDSA_RECOVER_WEIGHT: Weight = 320_000_000;
+/// Weight of hashing each byte of a signed message.
+pub const MESSAGE_BYTE_WEIGHT: Weight = 6_400;
+
+selectors! {
+	/// The function of the `Ed25519` and `Sr25519` interfaces.
+	enum VerifyAction {
+		Verify = 0x5bf48e3a,
+	}
+}
+
+selectors! {
+	/// The functions of the `EcdsaRecover` interface.
+	enum RecoverAction {
+		Recover = 0x19045a25,
+		RecoverCompressed = 0x30e24164,
+	}
+}
+
+/// Read the arguments of `verify(bytes32 public_key, bytes message, bytes signature)`, recording
+/// the gas cost of a verification of `weight`.
+fn read_verify_input<Runtime: pallet_evm::Config>(
+	input: &[u8],
+	weight: Weight,
+	gasometer: &mut Gasometer,
+) -> Result<(H256, Vec<u8>, [u8; 64]), ExitError> {
+	let (mut input, VerifyAction::Verify) =
+		EvmDataReader::new_with_selector::<VerifyAction>(input)?;
+	let public_key = input.read::<H256>()?;
+	let message = input.read::<Bytes>()?.0;
+	let signature = <[u8; 64]>::try_from(&input.read::<Bytes>()?.0[..])
+		.map_err(|_| error("signature must be 64 bytes"))?;
+
+	let message_weight = MESSAGE_BYTE_WEIGHT.saturating_mul(message.len() as Weight);
+	gasometer.record_cost(Runtime::GasWeightMapping::weight_to_gas(
+		weight.saturating_add(message_weight),
+	))?;
+	Ok((public_key, message, signature))
+}
+
+/// Verifies ed25519 signatures.
+pub struct Ed25519Verify<Runtime>(PhantomData<Runtime>);
+
+impl<Runtime: pallet_evm::Config> Precompile for Ed25519Verify<Runtime> {
+	fn execute(
+		input: &[u8],
+		target_gas: Option<u64>,
+		_context: &Context,
+	) -> Result<(ExitSucceed, Vec<u8>, u64), ExitError> {
+		let mut gasometer = Gasometer::new(target_gas);
+		let (public_key, message, signature) =
+			read_verify_input::<Runtime>(input, ED25519_VERIFY_WEIGHT, &mut gasometer)?;
+
+		let valid = sp_io::crypto::ed25519_verify(
+			&ed25519::Signature::from_raw(signature),
+			&message,
+			&ed25519::Public::from_raw(public_key.to_fixed_bytes()),
+		);
+		Ok((
+			ExitSucceed::Returned,
+			EvmDataWriter::new().write(valid).build(),
+			gasometer.used_gas(),
+		))
+	}
+}
+
+/// Verifies sr25519 signatures.
+pub struct Sr25519Verify<Runtime>(PhantomData<Runtime>);
+
+impl<Runtime: pallet_evm::Config> Precompile for Sr25519Verify<Runtime> {
+	fn execute(
+		input: &[u8],
+		target_gas: Option<u64>,
+		_context: &Context,
+	) -> Result<(ExitSucceed, Vec<u8>, u64), ExitError> {
+		let mut gasometer = Gasometer::new(target_gas);
+		let (public_key, message, signature) =
+			read_verify_input::<Runtime>(input, SR25519_VERIFY_WEIGHT, &mut gasometer)?;
+
+		let valid = sp_io::crypto::sr25519_verify(
+			&sr25519::Signature::from_raw(signature),
+			&message,
+			&sr25519::Public::from_raw(public_key.to_fixed_bytes()),
+		);
+		Ok((
+			ExitSucceed::Returned,
+			EvmDataWriter::new().write(valid).build(),
+			gasometer.used_gas(),
+		))
+	}
+}
+
+/// Recovers the secp256k1 public key that signed a message hash. Unlike the `ECRecover`
+/// precompile, it returns the public key itself rather than its Ethereum address, so the message
+/// may be hashed with something else than Keccak-256.
+pub struct EcdsaRecover<Runtime>(PhantomData<Runtime>);
+
+impl<Runtime: pallet_evm::Config> Precompile for EcdsaRecover<Runtime> {
+	fn execute(
+		input: &[u8],
+		target_gas: Option<u64>,
+		_context: &Context,
+	) -> Result<(ExitSucceed, Vec<u8>, u64), ExitError> {
+		let (mut input, action) = EvmDataReader::new_with_selector::<RecoverAction>(input)?;
+		let message_hash = input.read::<H256>()?;
+		let signature = <[u8; 65]>::try_from(&input.read::<Bytes>()?.0[..])
+			.map_err(|_| error("signature must be 65 bytes"))?;
+
+		let mut gasometer = Gasometer::new(target_gas);
+		gasometer.record_cost(Runtime::GasWeightMapping::weight_to_gas(
+			ECDSA_RECOVER_WEIGHT,
+		))?;
+
+		let public_key = match action {
+			RecoverAction::Recover => {
+				sp_io::crypto::secp256k1_ecdsa_recover(&signature, message_hash.as_fixed_bytes())
+					.map(|public_key| public_key.to_vec())
+			}
+			RecoverAction::RecoverCompressed => sp_io::crypto::secp256k1_ecdsa_recover_compressed(
+				&signature,
+				message_hash.as_fixed_bytes(),
+			)
+			.map(|public_key| public_key.to_vec()),
+		}
+		.map_err(|_| error("invalid signature"))?;
+
+		Ok((
+			ExitSucceed::Returned,
+			EvmDataWriter::new().write(Bytes(public_key)).build(),
+			gasometer.used_gas(),
+		))
+	}
+}
@@ -0,0 +1,191 @@
+// Copyright 2019-2021 PureStake Inc.
+// This file is part of Moonbeam.
+
+// Moonbeam is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Moonbeam is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Moonbeam.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Precompile to interact with the democracy pallet from the EVM, through the Solidity interface
+//! in `DemocracyInterface.sol`.
+
+use evm::{Context, ExitError, ExitSucceed};
+use frame_support::{
+	dispatch::{Dispatchable, GetDispatchInfo, PostDispatchInfo},
+	traits::Currency,
+};
+use pallet_democracy::{AccountVote, Conviction, ReferendumInfo, Vote};
+use pallet_evm::{AddressMapping, Precompile};
+use precompile_utils::{
+	error, selectors, EvmDataReader, EvmDataWriter, EvmResult, Gasometer, RuntimeHelper,
+};
+use sp_core::{H160, H256, U256};
+use sp_std::{convert::TryFrom, marker::PhantomData, vec::Vec};
+
+type BalanceOf<Runtime> = <<Runtime as pallet_democracy::Config>::Currency as Currency<
+	<Runtime as frame_system::Config>::AccountId,
+>>::Balance;
+
+selectors! {
+	/// The functions of `DemocracyInterface.sol`.
+	enum Action {
+		PublicPropCount = 0x56fdf547,
+		DepositOf = 0xa30305e9,
+		LowestUnbaked = 0x0388f282,
+		OngoingReferendumInfo = 0x8b93d11a,
+		FinishedReferendumInfo = 0xb1fd383f,
+		Propose = 0x7824e7d1,
+		Second = 0xc7a76601,
+		StandardVote = 0x3f3c21cc,
+		SplitVote = 0xbd584d94,
+		Delegate = 0x0185921e,
+		Undelegate = 0x92ab89bb,
+		RemoveVote = 0x2042f50b,
+	}
+}
+
+/// Exposes the democracy pallet to the EVM. View functions read the pallet's storage and the
+/// other functions dispatch the matching call with the caller as the signed origin.
+pub struct DemocracyWrapper<Runtime>(PhantomData<Runtime>);
+
+impl<Runtime> Precompile for DemocracyWrapper<Runtime>
+where
+	Runtime: pallet_democracy::Config + pallet_evm::Config,
+	Runtime::Call: Dispatchable<PostInfo = PostDispatchInfo>
+		+ GetDispatchInfo
+		+ From<pallet_democracy::Call<Runtime>>,
+	<Runtime::Call as Dispatchable>::Origin: From<Option<Runtime::AccountId>>,
+	Runtime::BlockNumber: Into<U256>,
+	Runtime::Hash: From<H256> + Into<H256>,
+	BalanceOf<Runtime>: TryFrom<u128> + Into<U256>,
+{
+	fn execute(
+		input: &[u8],
+		target_gas: Option<u64>,
+		context: &Context,
+	) -> Result<(ExitSucceed, Vec<u8>, u64), ExitError> {
+		let (mut input, action) = EvmDataReader::new_with_selector::<Action>(input)?;
+		let mut gasometer = Gasometer::new(target_gas);
+
+		let call = match action {
+			// View functions
+			Action::PublicPropCount => {
+				gasometer.record_cost(RuntimeHelper::<Runtime>::db_read_gas_cost())?;
+				let count = pallet_democracy::Pallet::<Runtime>::public_prop_count();
+				return Self::view(EvmDataWriter::new().write(count), &gasometer);
+			}
+			Action::DepositOf => {
+				let proposal = input.read::<u32>()?;
+				gasometer.record_cost(RuntimeHelper::<Runtime>::db_read_gas_cost())?;
+				let (_, deposit) = pallet_democracy::Pallet::<Runtime>::deposit_of(proposal)
+					.ok_or_else(|| error("no such proposal"))?;
+				return Self::view(
+					EvmDataWriter::new().write::<U256>(deposit.into()),
+					&gasometer,
+				);
+			}
+			Action::LowestUnbaked => {
+				gasometer.record_cost(RuntimeHelper::<Runtime>::db_read_gas_cost())?;
+				let index = pallet_democracy::Pallet::<Runtime>::lowest_unbaked();
+				return Self::view(EvmDataWriter::new().write(index), &gasometer);
+			}
+			Action::OngoingReferendumInfo => {
+				let index = input.read::<u32>()?;
+				gasometer.record_cost(RuntimeHelper::<Runtime>::db_read_gas_cost())?;
+				let status = match pallet_democracy::Pallet::<Runtime>::referendum_info(index) {
+					Some(ReferendumInfo::Ongoing(status)) => status,
+					_ => return Err(error("no such ongoing referendum")),
+				};
+				let output = EvmDataWriter::new()
+					.write::<U256>(status.end.into())
+					.write::<H256>(status.proposal_hash.into())
+					.write(status.threshold as u8)
+					.write::<U256>(status.delay.into());
+				return Self::view(output, &gasometer);
+			}
+			Action::FinishedReferendumInfo => {
+				let index = input.read::<u32>()?;
+				gasometer.record_cost(RuntimeHelper::<Runtime>::db_read_gas_cost())?;
+				let (approved, end) =
+					match pallet_democracy::Pallet::<Runtime>::referendum_info(index) {
+						Some(ReferendumInfo::Finished { approved, end }) => (approved, end),
+						_ => return Err(error("no such finished referendum")),
+					};
+				let output = EvmDataWriter::new()
+					.write(approved)
+					.write::<U256>(end.into());
+				return Self::view(output, &gasometer);
+			}
+
+			// Calls
+			Action::Propose => pallet_democracy::Call::<Runtime>::propose(
+				input.read::<H256>()?.into(),
+				Self::amount(&mut input)?,
+			),
+			Action::Second => {
+				pallet_democracy::Call::<Runtime>::second(input.read()?, input.read()?)
+			}
+			Action::StandardVote => {
+				let index = input.read()?;
+				let aye = input.read()?;
+				let balance = Self::amount(&mut input)?;
+				let conviction = Self::conviction(&mut input)?;
+				pallet_democracy::Call::<Runtime>::vote(
+					index,
+					AccountVote::Standard {
+						vote: Vote { aye, conviction },
+						balance,
+					},
+				)
+			}
+			Action::SplitVote => {
+				let index = input.read()?;
+				let aye = Self::amount(&mut input)?;
+				let nay = Self::amount(&mut input)?;
+				pallet_democracy::Call::<Runtime>::vote(index, AccountVote::Split { aye, nay })
+			}
+			Action::Delegate => {
+				let to = Runtime::AddressMapping::into_account_id(input.read::<H160>()?);
+				let conviction = Self::conviction(&mut input)?;
+				let balance = Self::amount(&mut input)?;
+				pallet_democracy::Call::<Runtime>::delegate(to, conviction, balance)
+			}
+			Action::Undelegate => pallet_democracy::Call::<Runtime>::undelegate(),
+			Action::RemoveVote => pallet_democracy::Call::<Runtime>::remove_vote(input.read()?),
+		};
+
+		RuntimeHelper::<Runtime>::try_dispatch(context.caller, call, &mut gasometer)?;
+		Ok((ExitSucceed::Returned, Vec::new(), gasometer.used_gas()))
+	}
+}
+
+impl<Runtime> DemocracyWrapper<Runtime>
+where
+	Runtime: pallet_democracy::Config + pallet_evm::Config,
+	BalanceOf<Runtime>: TryFrom<u128>,
+{
+	fn amount(input: &mut EvmDataReader) -> EvmResult<BalanceOf<Runtime>> {
+		BalanceOf::<Runtime>::try_from(input.read::<u128>()?)
+			.map_err(|_| error("amount does not fit in the balance type"))
+	}
+
+	fn conviction(input: &mut EvmDataReader) -> EvmResult<Conviction> {
+		Conviction::try_from(input.read::<u8>()?)
+			.map_err(|_| error("conviction must be between 0 and 6"))
+	}
+
+	fn view(
+		output: EvmDataWriter,
+		gasometer: &Gasometer,
+	) -> Result<(ExitSucceed, Vec<u8>, u64), ExitError> {
+		Ok((ExitSucceed::Returned, output.build(), gasometer.used_gas()))
+	}
+}
@@ -0,0 +1,55 @@
+// Copyright 2019-2021 PureStake Inc.
+// This file is part of Moonbeam.
+
+// Moonbeam is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Moonbeam is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Moonbeam.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Precompile dispatching a SCALE encoded runtime call with the EVM caller as the signed origin.
+
+use codec::Decode;
+use evm::{Context, ExitError, ExitSucceed};
+use frame_support::{
+	dispatch::{Dispatchable, GetDispatchInfo, PostDispatchInfo},
+	traits::Filter,
+};
+use pallet_evm::Precompile;
+use precompile_utils::{error, Gasometer, RuntimeHelper};
+use sp_std::{marker::PhantomData, vec::Vec};
+
+/// Dispatches the runtime call its input decodes to, if `CallFilter` lets it through. The call is
+/// only dispatched if the gas left covers its weight, and the gas used is its actual weight as
+/// reported after dispatch, so that unused weight is refunded.
+pub struct FilteredDispatch<Runtime, CallFilter>(PhantomData<(Runtime, CallFilter)>);
+
+impl<Runtime, CallFilter> Precompile for FilteredDispatch<Runtime, CallFilter>
+where
+	Runtime: pallet_evm::Config,
+	Runtime::Call: Dispatchable<PostInfo = PostDispatchInfo> + GetDispatchInfo + Decode,
+	<Runtime::Call as Dispatchable>::Origin: From<Option<Runtime::AccountId>>,
+	CallFilter: Filter<Runtime::Call>,
+{
+	fn execute(
+		input: &[u8],
+		target_gas: Option<u64>,
+		context: &Context,
+	) -> Result<(ExitSucceed, Vec<u8>, u64), ExitError> {
+		let call = Runtime::Call::decode(&mut &input[..]).map_err(|_| error("decode failed"))?;
+		if !CallFilter::filter(&call) {
+			return Err(error("call not allowed by the dispatch precompile"));
+		}
+
+		let mut gasometer = Gasometer::new(target_gas);
+		RuntimeHelper::<Runtime>::try_dispatch(context.caller, call, &mut gasometer)?;
+		Ok((ExitSucceed::Stopped, Vec::new(), gasometer.used_gas()))
+	}
+}
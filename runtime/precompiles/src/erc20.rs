@@ -0,0 +1,191 @@
+// Copyright 2019-2021 PureStake Inc.
+// This file is part of Moonbeam.
+
+// Moonbeam is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Moonbeam is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Moonbeam.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Precompile exposing the native currency held by the balances pallet as an ERC-20 token,
+//! through the interface in `ERC20Interface.sol`.
+//!
+//! Allowances are kept in the `Erc20Balances::Approves` storage map, since they have no
+//! equivalent in the balances pallet.
+//!
+//! The token does not emit the ERC-20 `Transfer` and `Approval` events, as precompiles cannot
+//! return logs to the EVM. Transfers can be followed through the `Transfer` events of the balances
+//! pallet instead.
+
+use evm::{Context, ExitError, ExitSucceed};
+use frame_support::{
+	dispatch::{Dispatchable, GetDispatchInfo, PostDispatchInfo},
+	generate_storage_alias, Blake2_128Concat,
+};
+use pallet_evm::{AddressMapping, Precompile};
+use precompile_utils::{
+	error, selectors, Bytes, EvmDataReader, EvmDataWriter, EvmResult, Gasometer, RuntimeHelper,
+};
+use sp_core::{H160, U256};
+use sp_runtime::traits::StaticLookup;
+use sp_std::{convert::TryFrom, marker::PhantomData, vec::Vec};
+
+selectors! {
+	/// The functions of `ERC20Interface.sol`.
+	enum Action {
+		Name = 0x06fdde03,
+		Symbol = 0x95d89b41,
+		Decimals = 0x313ce567,
+		TotalSupply = 0x18160ddd,
+		BalanceOf = 0x70a08231,
+		Allowance = 0xdd62ed3e,
+		Transfer = 0xa9059cbb,
+		Approve = 0x095ea7b3,
+		TransferFrom = 0x23b872dd,
+	}
+}
+
+generate_storage_alias!(
+	Erc20Balances,
+	Approves => DoubleMap<(Blake2_128Concat, H160), (Blake2_128Concat, H160), U256>
+);
+
+/// The optional ERC-20 metadata of a token.
+pub trait Erc20Metadata {
+	/// The name of the token
+	fn name() -> &'static str;
+	/// The symbol of the token
+	fn symbol() -> &'static str;
+	/// The number of decimals of the token amounts
+	fn decimals() -> u8;
+}
+
+/// Exposes the native currency to the EVM as an ERC-20 token described by `Metadata`.
+pub struct Erc20BalancesWrapper<Runtime, Metadata>(PhantomData<(Runtime, Metadata)>);
+
+impl<Runtime, Metadata> Precompile for Erc20BalancesWrapper<Runtime, Metadata>
+where
+	Runtime: pallet_balances::Config + pallet_evm::Config,
+	Runtime::Call: Dispatchable<PostInfo = PostDispatchInfo>
+		+ GetDispatchInfo
+		+ From<pallet_balances::Call<Runtime>>,
+	<Runtime::Call as Dispatchable>::Origin: From<Option<Runtime::AccountId>>,
+	Runtime::Balance: TryFrom<u128> + Into<U256>,
+	Metadata: Erc20Metadata,
+{
+	fn execute(
+		input: &[u8],
+		target_gas: Option<u64>,
+		context: &Context,
+	) -> Result<(ExitSucceed, Vec<u8>, u64), ExitError> {
+		let (mut input, action) = EvmDataReader::new_with_selector::<Action>(input)?;
+		let mut gasometer = Gasometer::new(target_gas);
+
+		let output = match action {
+			Action::Name => EvmDataWriter::new().write(Bytes::from(Metadata::name().as_bytes())),
+			Action::Symbol => {
+				EvmDataWriter::new().write(Bytes::from(Metadata::symbol().as_bytes()))
+			}
+			Action::Decimals => EvmDataWriter::new().write(Metadata::decimals()),
+			Action::TotalSupply => {
+				gasometer.record_cost(RuntimeHelper::<Runtime>::db_read_gas_cost())?;
+				let supply = pallet_balances::Pallet::<Runtime>::total_issuance();
+				EvmDataWriter::new().write::<U256>(supply.into())
+			}
+			Action::BalanceOf => {
+				let owner = Runtime::AddressMapping::into_account_id(input.read::<H160>()?);
+				gasometer.record_cost(RuntimeHelper::<Runtime>::db_read_gas_cost())?;
+				let balance = pallet_balances::Pallet::<Runtime>::free_balance(&owner);
+				EvmDataWriter::new().write::<U256>(balance.into())
+			}
+			Action::Allowance => {
+				let owner = input.read::<H160>()?;
+				let spender = input.read::<H160>()?;
+				gasometer.record_cost(RuntimeHelper::<Runtime>::db_read_gas_cost())?;
+				let allowance = Approves::get(&owner, &spender).unwrap_or_default();
+				EvmDataWriter::new().write(allowance)
+			}
+			Action::Approve => {
+				let spender = input.read::<H160>()?;
+				let value = input.read::<U256>()?;
+				gasometer.record_cost(RuntimeHelper::<Runtime>::db_write_gas_cost())?;
+
+				Approves::insert(&context.caller, &spender, value);
+				EvmDataWriter::new().write(true)
+			}
+			Action::Transfer => {
+				let to = input.read::<H160>()?;
+				let value = input.read::<U256>()?;
+				Self::transfer(context.caller, to, value, &mut gasometer)?;
+				EvmDataWriter::new().write(true)
+			}
+			Action::TransferFrom => {
+				let from = input.read::<H160>()?;
+				let to = input.read::<H160>()?;
+				let value = input.read::<U256>()?;
+
+				gasometer.record_cost(RuntimeHelper::<Runtime>::db_read_gas_cost())?;
+				let allowance = Approves::get(&from, &context.caller).unwrap_or_default();
+				if allowance < value {
+					return Err(error("trying to spend more than allowed"));
+				}
+				gasometer.record_cost(RuntimeHelper::<Runtime>::db_write_gas_cost())?;
+				Self::transfer(from, to, value, &mut gasometer)?;
+				Approves::insert(&from, &context.caller, allowance - value);
+				EvmDataWriter::new().write(true)
+			}
+		};
+
+		Ok((ExitSucceed::Returned, output.build(), gasometer.used_gas()))
+	}
+}
+
+impl<Runtime, Metadata> Erc20BalancesWrapper<Runtime, Metadata>
+where
+	Runtime: pallet_balances::Config + pallet_evm::Config,
+	Runtime::Call: Dispatchable<PostInfo = PostDispatchInfo>
+		+ GetDispatchInfo
+		+ From<pallet_balances::Call<Runtime>>,
+	<Runtime::Call as Dispatchable>::Origin: From<Option<Runtime::AccountId>>,
+	Runtime::Balance: TryFrom<u128>,
+{
+	/// Transfer `value` from `from` to `to` with the balances pallet.
+	fn transfer(from: H160, to: H160, value: U256, gasometer: &mut Gasometer) -> EvmResult {
+		if value > U256::from(u128::max_value()) {
+			return Err(error("value does not fit in 128 bits"));
+		}
+		let value = Runtime::Balance::try_from(value.low_u128())
+			.map_err(|_| error("value does not fit in the balance type"))?;
+		let dest = Runtime::Lookup::unlookup(Runtime::AddressMapping::into_account_id(to));
+
+		RuntimeHelper::<Runtime>::try_dispatch(
+			from,
+			pallet_balances::Call::<Runtime>::transfer(dest, value),
+			gasometer,
+		)
+	}
+}
+
+/// The ERC-20 metadata of GLMR, the native token of Moonbeam.
+pub struct GlmrMetadata;
+
+impl Erc20Metadata for GlmrMetadata {
+	fn name() -> &'static str {
+		"Glimmer"
+	}
+
+	fn symbol() -> &'static str {
+		"GLMR"
+	}
+
+	fn decimals() -> u8 {
+		18
+	}
+}
@@ -18,27 +18,104 @@
 
 use codec::Decode;
 use evm::{Context, ExitError, ExitSucceed};
-use frame_support::dispatch::{Dispatchable, GetDispatchInfo, PostDispatchInfo};
+use frame_support::{
+	dispatch::{Dispatchable, GetDispatchInfo, PostDispatchInfo},
+	traits::{Currency, Filter, Get},
+};
 use pallet_evm::{Config, Precompile, PrecompileSet};
 use pallet_evm_precompile_bn128::{Bn128Add, Bn128Mul, Bn128Pairing};
-use pallet_evm_precompile_dispatch::Dispatch;
 use pallet_evm_precompile_modexp::Modexp;
 use pallet_evm_precompile_simple::{ECRecover, Identity, Ripemd160, Sha256};
-use sp_core::H160;
-use sp_std::{marker::PhantomData, vec::Vec};
+use sp_core::{H160, H256, U256};
+use sp_std::{convert::TryFrom, marker::PhantomData, vec::Vec};
+
+mod batch;
+mod blake2f;
+mod collective;
+mod crypto;
+mod democracy;
+mod dispatch;
+mod erc20;
+mod modexp;
+mod multisig;
+mod randomness;
+mod staking;
+mod treasury;
+
+pub use batch::BatchPrecompile;
+pub use blake2f::Blake2F;
+pub use collective::CollectiveWrapper;
+pub use crypto::{
+	EcdsaRecover, Ed25519Verify, Sr25519Verify, ECDSA_RECOVER_WEIGHT, ED25519_VERIFY_WEIGHT,
+	MESSAGE_BYTE_WEIGHT, SR25519_VERIFY_WEIGHT,
+};
+pub use democracy::DemocracyWrapper;
+pub use dispatch::FilteredDispatch;
+pub use erc20::{Erc20BalancesWrapper, Erc20Metadata, GlmrMetadata};
+pub use modexp::Eip2565Modexp;
+pub use multisig::MultisigWrapper;
+pub use randomness::RandomnessWrapper;
+pub use staking::ParachainStakingWrapper;
+pub use treasury::TreasuryWrapper;
+
+/// The collective instance of the council.
+type CouncilInstance = pallet_collective::Instance1;
+
+/// The Moonbeam precompiles acting on behalf of their caller. Through DELEGATECALL or CALLCODE,
+/// `context.caller` is the caller of the calling contract, which the precompile would then act
+/// for without its consent, so those precompiles only accept being called directly.
+const CALLER_ACTING_PRECOMPILES: [u64; 8] = [255, 2048, 2049, 2050, 2051, 2055, 2056, 2057];
+
+/// The Ethereum hard forks whose precompiles `MoonbeamPrecompiles` can expose.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HardFork {
+	/// The nine Istanbul precompiles.
+	Istanbul,
+	/// The Istanbul precompiles with the modexp gas cost of EIP-2565.
+	Berlin,
+}
 
 /// The PrecompileSet installed in the Moonbeam runtime.
-/// We include the nine Istanbul precompiles
+/// We include the nine Ethereum precompiles of the hard fork `Fork` selects
 /// (https://github.com/ethereum/go-ethereum/blob/3c46f557/core/vm/contracts.go#L69)
-/// as well as a special precompile for dispatching Substrate extrinsics
+/// as well as a special precompile for dispatching the Substrate extrinsics `DispatchFilter` allows
+/// and precompiles exposing Moonbeam pallets through Solidity interfaces. The multisig precompile
+/// only dispatches the calls `DispatchFilter` allows too.
+///
+/// Those precompiles live from address 2048 up, to stay clear of the addresses Ethereum may use
+/// for new precompiles.
 #[derive(Debug, Clone, Copy)]
-pub struct MoonbeamPrecompiles<R>(PhantomData<R>);
+pub struct MoonbeamPrecompiles<R, DispatchFilter, Fork>(PhantomData<(R, DispatchFilter, Fork)>);
 
-impl<R> PrecompileSet for MoonbeamPrecompiles<R>
+impl<R, DispatchFilter, Fork> PrecompileSet for MoonbeamPrecompiles<R, DispatchFilter, Fork>
 where
-	R: Config,
-	R::Call: Dispatchable<PostInfo = PostDispatchInfo> + GetDispatchInfo + Decode,
+	R: Config
+		+ pallet_balances::Config
+		+ pallet_collective::Config<CouncilInstance>
+		+ pallet_democracy::Config
+		+ pallet_multisig::Config
+		+ pallet_relay_randomness::Config
+		+ pallet_treasury::Config
+		+ parachain_staking::Config,
+	R::Call: Dispatchable<PostInfo = PostDispatchInfo>
+		+ GetDispatchInfo
+		+ Decode
+		+ From<pallet_balances::Call<R>>
+		+ From<pallet_collective::Call<R, CouncilInstance>>
+		+ From<pallet_democracy::Call<R>>
+		+ From<pallet_multisig::Call<R>>
+		+ From<parachain_staking::Call<R>>,
 	<R::Call as Dispatchable>::Origin: From<Option<R::AccountId>>,
+	R::AccountId: Into<H160>,
+	R::BlockNumber: Into<U256> + From<u32>,
+	R::Hash: From<H256> + Into<H256>,
+	R::Balance: TryFrom<u128> + Into<U256>,
+	<<R as pallet_democracy::Config>::Currency as Currency<R::AccountId>>::Balance:
+		TryFrom<u128> + Into<U256>,
+	parachain_staking::BalanceOf<R>: TryFrom<u128>,
+	pallet_treasury::BalanceOf<R>: Into<U256>,
+	DispatchFilter: Filter<R::Call>,
+	Fork: Get<HardFork>,
 {
 	fn execute(
 		address: H160,
@@ -46,23 +123,112 @@ where
 		target_gas: Option<u64>,
 		context: &Context,
 	) -> Option<Result<(ExitSucceed, Vec<u8>, u64), ExitError>> {
+		if is_delegated_call_to_caller_acting(address, context) {
+			return Some(Err(ExitError::Other(
+				"precompile cannot be called with DELEGATECALL or CALLCODE".into(),
+			)));
+		}
+
 		match address {
 			// Ethereum precompiles :
 			a if a == hash(1) => Some(ECRecover::execute(input, target_gas, context)),
 			a if a == hash(2) => Some(Sha256::execute(input, target_gas, context)),
 			a if a == hash(3) => Some(Ripemd160::execute(input, target_gas, context)),
 			a if a == hash(4) => Some(Identity::execute(input, target_gas, context)),
-			a if a == hash(5) => Some(Modexp::execute(input, target_gas, context)),
+			a if a == hash(5) => Some(match Fork::get() {
+				HardFork::Istanbul => Modexp::execute(input, target_gas, context),
+				HardFork::Berlin => Eip2565Modexp::execute(input, target_gas, context),
+			}),
 			a if a == hash(6) => Some(Bn128Add::execute(input, target_gas, context)),
 			a if a == hash(7) => Some(Bn128Mul::execute(input, target_gas, context)),
 			a if a == hash(8) => Some(Bn128Pairing::execute(input, target_gas, context)),
+			a if a == hash(9) => Some(Blake2F::execute(input, target_gas, context)),
 			// Moonbeam precompiles :
-			a if a == hash(255) => Some(Dispatch::<R>::execute(input, target_gas, context)),
+			a if a == hash(255) => Some(FilteredDispatch::<R, DispatchFilter>::execute(
+				input, target_gas, context,
+			)),
+			a if a == hash(2048) => Some(ParachainStakingWrapper::<R>::execute(
+				input, target_gas, context,
+			)),
+			a if a == hash(2049) => Some(Erc20BalancesWrapper::<R, GlmrMetadata>::execute(
+				input, target_gas, context,
+			)),
+			a if a == hash(2050) => {
+				Some(DemocracyWrapper::<R>::execute(input, target_gas, context))
+			}
+			a if a == hash(2051) => Some(CollectiveWrapper::<R, CouncilInstance>::execute(
+				input, target_gas, context,
+			)),
+			a if a == hash(2052) => Some(Ed25519Verify::<R>::execute(input, target_gas, context)),
+			a if a == hash(2053) => Some(Sr25519Verify::<R>::execute(input, target_gas, context)),
+			a if a == hash(2054) => Some(EcdsaRecover::<R>::execute(input, target_gas, context)),
+			a if a == hash(2055) => {
+				Some(RandomnessWrapper::<R>::execute(input, target_gas, context))
+			}
+			a if a == hash(2056) => Some(BatchPrecompile::<R>::execute(input, target_gas, context)),
+			a if a == hash(2057) => Some(MultisigWrapper::<R, DispatchFilter>::execute(
+				input, target_gas, context,
+			)),
+			a if a == hash(2058) => Some(TreasuryWrapper::<R>::execute(input, target_gas, context)),
 			_ => None,
 		}
 	}
 }
 
+/// Whether `address` is one of the `CALLER_ACTING_PRECOMPILES` and is executed with the code of
+/// another contract, which is what DELEGATECALL and CALLCODE do.
+fn is_delegated_call_to_caller_acting(address: H160, context: &Context) -> bool {
+	context.address != address
+		&& CALLER_ACTING_PRECOMPILES
+			.iter()
+			.any(|a| hash(*a) == address)
+}
+
 fn hash(a: u64) -> H160 {
 	H160::from_low_u64_be(a)
 }
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn context(address: H160) -> Context {
+		Context {
+			address,
+			caller: H160::repeat_byte(0x11),
+			apparent_value: U256::zero(),
+		}
+	}
+
+	#[test]
+	fn direct_calls_are_accepted() {
+		for a in CALLER_ACTING_PRECOMPILES.iter() {
+			assert!(!is_delegated_call_to_caller_acting(
+				hash(*a),
+				&context(hash(*a))
+			));
+		}
+	}
+
+	#[test]
+	fn delegated_calls_to_caller_acting_precompiles_are_rejected() {
+		let calling_contract = H160::repeat_byte(0x22);
+		for a in CALLER_ACTING_PRECOMPILES.iter() {
+			assert!(is_delegated_call_to_caller_acting(
+				hash(*a),
+				&context(calling_contract)
+			));
+		}
+	}
+
+	#[test]
+	fn delegated_calls_to_other_precompiles_are_accepted() {
+		let calling_contract = H160::repeat_byte(0x22);
+		for a in [1u64, 9, 2052, 2058].iter() {
+			assert!(!is_delegated_call_to_caller_acting(
+				hash(*a),
+				&context(calling_contract)
+			));
+		}
+	}
+}
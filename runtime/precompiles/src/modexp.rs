@@ -0,0 +1,164 @@
+// Copyright 2019-2021 PureStake Inc.
+// This file is part of Moonbeam.
+
+// Moonbeam is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Moonbeam is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Moonbeam.  If not, see <http://www.gnu.org/licenses/>.
+
+//! The modular exponentiation precompile at address 5, with the gas cost of EIP-2565
+//! (https://eips.ethereum.org/EIPS/eip-2565) introduced in Berlin.
+
+use evm::{Context, ExitError, ExitSucceed};
+use pallet_evm::Precompile;
+use pallet_evm_precompile_modexp::Modexp;
+use precompile_utils::Gasometer;
+use sp_core::U256;
+use sp_std::vec::Vec;
+
+/// The lowest cost of a modular exponentiation.
+const MIN_GAS_COST: u64 = 200;
+
+/// `length` bytes of `input` from `start`, right padded with zeros past its end.
+fn padded(input: &[u8], start: usize, length: usize) -> Vec<u8> {
+	let mut bytes = input
+		.get(start..)
+		.map(|rest| rest.iter().take(length).copied().collect::<Vec<_>>())
+		.unwrap_or_default();
+	bytes.resize(length, 0);
+	bytes
+}
+
+/// The EIP-2565 gas cost of the modular exponentiation of the input.
+fn gas_cost(input: &[u8]) -> U256 {
+	let base_length = U256::from_big_endian(&padded(input, 0, 32));
+	let exponent_length = U256::from_big_endian(&padded(input, 32, 32));
+	let modulus_length = U256::from_big_endian(&padded(input, 64, 32));
+
+	// Lengths this large cost more gas than there is in a block anyway.
+	let limit = U256::from(u32::max_value());
+	if base_length > limit || exponent_length > limit || modulus_length > limit {
+		return U256::max_value();
+	}
+	let (base_length, exponent_length) = (base_length.low_u64(), exponent_length.low_u64());
+
+	let words = (base_length.max(modulus_length.low_u64()) + 7) / 8;
+	let multiplication_complexity = U256::from(words) * U256::from(words);
+
+	// The iteration count depends on the highest 32 bytes of the exponent.
+	let exponent_head_length = exponent_length.min(32);
+	let exponent_head = U256::from_big_endian(&padded(
+		input,
+		(base_length as usize).saturating_add(96),
+		exponent_head_length as usize,
+	));
+	let head_bits = if exponent_head.is_zero() {
+		0
+	} else {
+		exponent_head.bits() as u64 - 1
+	};
+	let iteration_count = if exponent_length <= 32 {
+		head_bits
+	} else {
+		8 * (exponent_length - 32) + head_bits
+	}
+	.max(1);
+
+	(multiplication_complexity * U256::from(iteration_count) / 3).max(U256::from(MIN_GAS_COST))
+}
+
+/// The modular exponentiation precompile repriced by EIP-2565.
+pub struct Eip2565Modexp;
+
+impl Precompile for Eip2565Modexp {
+	fn execute(
+		input: &[u8],
+		target_gas: Option<u64>,
+		context: &Context,
+	) -> Result<(ExitSucceed, Vec<u8>, u64), ExitError> {
+		let cost = gas_cost(input);
+		if cost > U256::from(u64::max_value()) {
+			return Err(ExitError::OutOfGas);
+		}
+		let mut gasometer = Gasometer::new(target_gas);
+		gasometer.record_cost(cost.low_u64())?;
+
+		// Only the pricing changed, the result is computed by the Istanbul precompile.
+		let (exit, output, _) = Modexp::execute(input, None, context)?;
+		Ok((exit, output, gasometer.used_gas()))
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use hex_literal::hex;
+
+	#[test]
+	fn costs_at_least_the_minimum() {
+		// 3 ^ 1 mod 5
+		let input = hex!(
+			"0000000000000000000000000000000000000000000000000000000000000001
+			0000000000000000000000000000000000000000000000000000000000000001
+			0000000000000000000000000000000000000000000000000000000000000001
+			03
+			01
+			05"
+		);
+		assert_eq!(gas_cost(&input), U256::from(MIN_GAS_COST));
+	}
+
+	#[test]
+	fn prices_like_eip_2565() {
+		// A 256 byte base and modulus with the exponent 2^255 + 1: 32 words, so a complexity of
+		// 1024, and 255 iterations.
+		let mut input = hex!(
+			"0000000000000000000000000000000000000000000000000000000000000100
+			0000000000000000000000000000000000000000000000000000000000000020
+			0000000000000000000000000000000000000000000000000000000000000100"
+		)
+		.to_vec();
+		input.extend_from_slice(&[0xff; 256]);
+		input.extend_from_slice(&hex!(
+			"8000000000000000000000000000000000000000000000000000000000000001"
+		));
+		input.extend_from_slice(&[0xff; 256]);
+		assert_eq!(gas_cost(&input), U256::from(1024 * 255 / 3));
+	}
+
+	#[test]
+	fn counts_iterations_of_long_exponents() {
+		// A 40 byte exponent whose highest 32 bytes are 1: 8 iterations for each of the 8 extra
+		// bytes, and none for its highest 32 bytes.
+		let mut input = hex!(
+			"0000000000000000000000000000000000000000000000000000000000000040
+			0000000000000000000000000000000000000000000000000000000000000028
+			0000000000000000000000000000000000000000000000000000000000000040"
+		)
+		.to_vec();
+		input.extend_from_slice(&[0xff; 64]);
+		input.extend_from_slice(&[0; 31]);
+		input.push(0x01);
+		input.extend_from_slice(&[0xff; 8]);
+		input.extend_from_slice(&[0xff; 64]);
+		assert_eq!(gas_cost(&input), U256::from(64 * 64 / 3));
+	}
+
+	#[test]
+	fn huge_lengths_cost_all_the_gas() {
+		let input = hex!(
+			"0000000000000000000000000000000000000000000000000000000100000000
+			0000000000000000000000000000000000000000000000000000000000000001
+			0000000000000000000000000000000000000000000000000000000000000001"
+		);
+		assert_eq!(gas_cost(&input), U256::max_value());
+	}
+}
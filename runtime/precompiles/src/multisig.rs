@@ -0,0 +1,167 @@
+// Copyright 2019-2021 PureStake Inc.
+// This file is part of Moonbeam.
+
+// Moonbeam is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Moonbeam is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Moonbeam.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Precompile to interact with the multisig pallet from the EVM, through the Solidity interface
+//! in `MultisigInterface.sol`.
+
+use codec::Decode;
+use evm::{Context, ExitError, ExitSucceed};
+use frame_support::{
+	dispatch::{Dispatchable, GetDispatchInfo, PostDispatchInfo},
+	traits::Filter,
+};
+use pallet_evm::{AddressMapping, Precompile};
+use pallet_multisig::Timepoint;
+use precompile_utils::{
+	error, selectors, Bytes, EvmDataReader, EvmDataWriter, EvmResult, Gasometer, RuntimeHelper,
+};
+use sp_core::{H160, H256};
+use sp_std::{marker::PhantomData, vec::Vec};
+
+selectors! {
+	/// The functions of `MultisigInterface.sol`.
+	enum Action {
+		AsMulti = 0xff3a5c35,
+		ApproveAsMulti = 0xe7c54377,
+		CancelAsMulti = 0xfadf1450,
+		MultisigAddress = 0x7bee4775,
+	}
+}
+
+/// Exposes the multisig pallet to the EVM. The signatories are sorted by the precompile, so they
+/// can be given in any order. Contracts can only get the multisig account to dispatch the calls
+/// `CallFilter` lets through, as with the dispatch precompile.
+pub struct MultisigWrapper<Runtime, CallFilter>(PhantomData<(Runtime, CallFilter)>);
+
+impl<Runtime, CallFilter> Precompile for MultisigWrapper<Runtime, CallFilter>
+where
+	Runtime: pallet_multisig::Config + pallet_evm::Config,
+	Runtime::Call: Dispatchable<PostInfo = PostDispatchInfo>
+		+ GetDispatchInfo
+		+ Decode
+		+ From<pallet_multisig::Call<Runtime>>,
+	<Runtime::Call as Dispatchable>::Origin: From<Option<Runtime::AccountId>>,
+	Runtime::AccountId: Into<H160>,
+	Runtime::BlockNumber: From<u32>,
+	CallFilter: Filter<Runtime::Call>,
+{
+	fn execute(
+		input: &[u8],
+		target_gas: Option<u64>,
+		context: &Context,
+	) -> Result<(ExitSucceed, Vec<u8>, u64), ExitError> {
+		let (mut input, action) = EvmDataReader::new_with_selector::<Action>(input)?;
+		let mut gasometer = Gasometer::new(target_gas);
+
+		let call = match action {
+			// View functions
+			Action::MultisigAddress => {
+				let signatories = Self::signatories(&mut input)?;
+				let threshold = input.read::<u16>()?;
+				// Deriving the address costs about as much as reading a storage item.
+				gasometer.record_cost(RuntimeHelper::<Runtime>::db_read_gas_cost())?;
+				let multisig =
+					pallet_multisig::Pallet::<Runtime>::multi_account_id(&signatories, threshold);
+				let output = EvmDataWriter::new().write::<H160>(multisig.into());
+				return Ok((ExitSucceed::Returned, output.build(), gasometer.used_gas()));
+			}
+
+			// Calls
+			Action::AsMulti => {
+				let threshold = input.read()?;
+				let other_signatories = Self::signatories(&mut input)?;
+				let maybe_timepoint = Self::timepoint(&mut input)?;
+				let call = input.read::<Bytes>()?.0;
+				let decoded = Runtime::Call::decode(&mut &call[..])
+					.map_err(|_| error("call decode failed"))?;
+				if !CallFilter::filter(&decoded) {
+					return Err(error("call not allowed by the multisig precompile"));
+				}
+				let max_weight = input.read()?;
+				pallet_multisig::Call::<Runtime>::as_multi(
+					threshold,
+					other_signatories,
+					maybe_timepoint,
+					call,
+					false,
+					max_weight,
+				)
+			}
+			Action::ApproveAsMulti => {
+				let threshold = input.read()?;
+				let other_signatories = Self::signatories(&mut input)?;
+				let maybe_timepoint = Self::timepoint(&mut input)?;
+				let call_hash = input.read::<H256>()?.0;
+				let max_weight = input.read()?;
+				pallet_multisig::Call::<Runtime>::approve_as_multi(
+					threshold,
+					other_signatories,
+					maybe_timepoint,
+					call_hash,
+					max_weight,
+				)
+			}
+			Action::CancelAsMulti => {
+				let threshold = input.read()?;
+				let other_signatories = Self::signatories(&mut input)?;
+				let timepoint = Self::timepoint(&mut input)?
+					.ok_or_else(|| error("cancelling needs a timepoint"))?;
+				let call_hash = input.read::<H256>()?.0;
+				pallet_multisig::Call::<Runtime>::cancel_as_multi(
+					threshold,
+					other_signatories,
+					timepoint,
+					call_hash,
+				)
+			}
+		};
+
+		RuntimeHelper::<Runtime>::try_dispatch(context.caller, call, &mut gasometer)?;
+		Ok((ExitSucceed::Returned, Vec::new(), gasometer.used_gas()))
+	}
+}
+
+impl<Runtime, CallFilter> MultisigWrapper<Runtime, CallFilter>
+where
+	Runtime: pallet_multisig::Config + pallet_evm::Config,
+	Runtime::BlockNumber: From<u32>,
+{
+	/// Read a list of signatories, sorted as the multisig pallet expects them.
+	fn signatories(input: &mut EvmDataReader) -> EvmResult<Vec<Runtime::AccountId>> {
+		let mut signatories: Vec<_> = input
+			.read::<Vec<H160>>()?
+			.into_iter()
+			.map(Runtime::AddressMapping::into_account_id)
+			.collect();
+		signatories.sort();
+		Ok(signatories)
+	}
+
+	/// Read the block number and extrinsic index of the first approval of an operation. A block
+	/// number of 0 stands for no timepoint, which is what the first approval gives.
+	fn timepoint(input: &mut EvmDataReader) -> EvmResult<Option<Timepoint<Runtime::BlockNumber>>> {
+		let height = input.read::<u32>()?;
+		let index = input.read::<u32>()?;
+		Ok(if height == 0 {
+			None
+		} else {
+			Some(Timepoint {
+				height: height.into(),
+				index,
+			})
+		})
+	}
+}
@@ -0,0 +1,95 @@
+// Copyright 2019-2021 PureStake Inc.
+// This file is part of Moonbeam.
+
+// Moonbeam is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Moonbeam is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Moonbeam.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Precompile exposing the relay chain BABE randomness of the relay randomness pallet to the EVM,
+//! through the Solidity interface in `RandomnessInterface.sol`. See the documentation of the
+//! pallet for how biasable it is.
+
+use evm::{Context, ExitError, ExitSucceed};
+use pallet_evm::{AddressMapping, Precompile};
+use precompile_utils::{error, selectors, EvmDataReader, EvmDataWriter, Gasometer, RuntimeHelper};
+use sp_core::H256;
+use sp_std::{marker::PhantomData, vec::Vec};
+
+selectors! {
+	/// The functions of `RandomnessInterface.sol`.
+	enum Action {
+		CurrentEpochRandomness = 0x72de708e,
+		RequestRandomness = 0x915da532,
+		FulfilRandomness = 0x88444e93,
+		IsReady = 0x2bbfe85e,
+	}
+}
+
+/// Exposes the epoch randomness, and requests for the randomness of future blocks made and
+/// fulfilled by the caller, who pays the request deposit.
+///
+/// It must be called with `CALL`: requests would otherwise be made on behalf of the caller of the
+/// contract using `DELEGATECALL` or `CALLCODE`, which `MoonbeamPrecompiles` prevents.
+pub struct RandomnessWrapper<Runtime>(PhantomData<Runtime>);
+
+impl<Runtime> Precompile for RandomnessWrapper<Runtime>
+where
+	Runtime: pallet_relay_randomness::Config + pallet_evm::Config,
+{
+	fn execute(
+		input: &[u8],
+		target_gas: Option<u64>,
+		context: &Context,
+	) -> Result<(ExitSucceed, Vec<u8>, u64), ExitError> {
+		let (mut input, action) = EvmDataReader::new_with_selector::<Action>(input)?;
+		let mut gasometer = Gasometer::new(target_gas);
+		let read_cost = RuntimeHelper::<Runtime>::db_read_gas_cost();
+		let write_cost = RuntimeHelper::<Runtime>::db_write_gas_cost();
+
+		let output = match action {
+			Action::CurrentEpochRandomness => {
+				gasometer.record_cost(read_cost)?;
+				let randomness = pallet_relay_randomness::Pallet::<Runtime>::epoch_randomness()
+					.ok_or_else(|| error("no epoch randomness yet"))?;
+				EvmDataWriter::new().write(randomness)
+			}
+			Action::RequestRandomness => {
+				let block = input.read::<u32>()?;
+				// Reserving the deposit reads and writes the account of the requester.
+				gasometer.record_cost(read_cost * 3 + write_cost * 5)?;
+				let requester = Runtime::AddressMapping::into_account_id(context.caller);
+				let id = pallet_relay_randomness::Pallet::<Runtime>::request_randomness(
+					requester,
+					block.into(),
+				)
+				.map_err(|e| error(<&'static str>::from(e)))?;
+				EvmDataWriter::new().write(id)
+			}
+			Action::FulfilRandomness => {
+				let id = input.read::<u64>()?;
+				gasometer.record_cost(read_cost * 4 + write_cost * 5)?;
+				let requester = Runtime::AddressMapping::into_account_id(context.caller);
+				let randomness: H256 =
+					pallet_relay_randomness::Pallet::<Runtime>::fulfil_randomness(&requester, id)
+						.map_err(|e| error(<&'static str>::from(e)))?;
+				EvmDataWriter::new().write(randomness)
+			}
+			Action::IsReady => {
+				let id = input.read::<u64>()?;
+				gasometer.record_cost(read_cost * 2)?;
+				EvmDataWriter::new().write(pallet_relay_randomness::Pallet::<Runtime>::is_ready(id))
+			}
+		};
+
+		Ok((ExitSucceed::Returned, output.build(), gasometer.used_gas()))
+	}
+}
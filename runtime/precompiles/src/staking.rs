@@ -0,0 +1,171 @@
+// Copyright 2019-2021 PureStake Inc.
+// This file is part of Moonbeam.
+
+// Moonbeam is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Moonbeam is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Moonbeam.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Precompile to interact with the parachain staking pallet from the EVM, through the Solidity
+//! interface in `StakingInterface.sol`.
+
+use evm::{Context, ExitError, ExitSucceed};
+use frame_support::dispatch::{Dispatchable, GetDispatchInfo, PostDispatchInfo};
+use pallet_evm::{AddressMapping, Precompile};
+use parachain_staking::BalanceOf;
+use precompile_utils::{
+	error, selectors, EvmDataReader, EvmDataWriter, EvmResult, Gasometer, RuntimeHelper,
+};
+use sp_core::{H160, U256};
+use sp_std::{convert::TryFrom, marker::PhantomData, vec::Vec};
+
+selectors! {
+	/// The functions of `StakingInterface.sol`.
+	enum Action {
+		IsNominator = 0x8e5080e7,
+		IsCandidate = 0x8545c833,
+		IsSelectedCandidate = 0x8f6d27c7,
+		CollatorNominationCount = 0x0ad6a7be,
+		RoundInfo = 0xf8aa8ddd,
+		JoinCandidates = 0xad76ed5a,
+		LeaveCandidates = 0xb7694219,
+		GoOffline = 0x767e0450,
+		GoOnline = 0xd2f73ceb,
+		CandidateBondMore = 0xc57bd3a8,
+		CandidateBondLess = 0x289b6ba7,
+		Nominate = 0x82f2c8df,
+		LeaveNominators = 0xe8d68a37,
+		RevokeNomination = 0x4b65c34b,
+		NominatorBondMore = 0x971d44c8,
+		NominatorBondLess = 0xf6a52569,
+	}
+}
+
+/// Exposes the parachain staking pallet to the EVM. View functions read the pallet's storage and
+/// the other functions dispatch the matching call with the caller as the signed origin.
+pub struct ParachainStakingWrapper<Runtime>(PhantomData<Runtime>);
+
+impl<Runtime> Precompile for ParachainStakingWrapper<Runtime>
+where
+	Runtime: parachain_staking::Config + pallet_evm::Config,
+	Runtime::Call: Dispatchable<PostInfo = PostDispatchInfo>
+		+ GetDispatchInfo
+		+ From<parachain_staking::Call<Runtime>>,
+	<Runtime::Call as Dispatchable>::Origin: From<Option<Runtime::AccountId>>,
+	Runtime::BlockNumber: Into<U256>,
+	BalanceOf<Runtime>: TryFrom<u128>,
+{
+	fn execute(
+		input: &[u8],
+		target_gas: Option<u64>,
+		context: &Context,
+	) -> Result<(ExitSucceed, Vec<u8>, u64), ExitError> {
+		let (mut input, action) = EvmDataReader::new_with_selector::<Action>(input)?;
+		let mut gasometer = Gasometer::new(target_gas);
+
+		let call = match action {
+			// View functions
+			Action::IsNominator => {
+				let account = Self::account(&mut input)?;
+				gasometer.record_cost(RuntimeHelper::<Runtime>::db_read_gas_cost())?;
+				let is_nominator = parachain_staking::Pallet::<Runtime>::is_nominator(&account);
+				return Self::view(EvmDataWriter::new().write(is_nominator), &gasometer);
+			}
+			Action::IsCandidate => {
+				let account = Self::account(&mut input)?;
+				gasometer.record_cost(RuntimeHelper::<Runtime>::db_read_gas_cost())?;
+				let is_candidate = parachain_staking::Pallet::<Runtime>::is_candidate(&account);
+				return Self::view(EvmDataWriter::new().write(is_candidate), &gasometer);
+			}
+			Action::IsSelectedCandidate => {
+				let account = Self::account(&mut input)?;
+				gasometer.record_cost(RuntimeHelper::<Runtime>::db_read_gas_cost())?;
+				let is_selected =
+					parachain_staking::Pallet::<Runtime>::is_selected_candidate(&account);
+				return Self::view(EvmDataWriter::new().write(is_selected), &gasometer);
+			}
+			Action::CollatorNominationCount => {
+				let account = Self::account(&mut input)?;
+				gasometer.record_cost(RuntimeHelper::<Runtime>::db_read_gas_cost())?;
+				let count = parachain_staking::Pallet::<Runtime>::collator_state(&account)
+					.map(|state| state.nominators.0.len())
+					.unwrap_or_default();
+				return Self::view(EvmDataWriter::new().write(count as u32), &gasometer);
+			}
+			Action::RoundInfo => {
+				gasometer.record_cost(RuntimeHelper::<Runtime>::db_read_gas_cost())?;
+				let round = parachain_staking::Pallet::<Runtime>::round();
+				let output = EvmDataWriter::new()
+					.write(round.current)
+					.write::<U256>(round.first.into())
+					.write(round.length);
+				return Self::view(output, &gasometer);
+			}
+
+			// Calls
+			Action::JoinCandidates => {
+				parachain_staking::Call::<Runtime>::join_candidates(Self::amount(&mut input)?)
+			}
+			Action::LeaveCandidates => parachain_staking::Call::<Runtime>::leave_candidates(),
+			Action::GoOffline => parachain_staking::Call::<Runtime>::go_offline(),
+			Action::GoOnline => parachain_staking::Call::<Runtime>::go_online(),
+			Action::CandidateBondMore => {
+				parachain_staking::Call::<Runtime>::candidate_bond_more(Self::amount(&mut input)?)
+			}
+			Action::CandidateBondLess => {
+				parachain_staking::Call::<Runtime>::candidate_bond_less(Self::amount(&mut input)?)
+			}
+			Action::Nominate => parachain_staking::Call::<Runtime>::nominate(
+				Self::account(&mut input)?,
+				Self::amount(&mut input)?,
+			),
+			Action::LeaveNominators => parachain_staking::Call::<Runtime>::leave_nominators(),
+			Action::RevokeNomination => {
+				parachain_staking::Call::<Runtime>::revoke_nomination(Self::account(&mut input)?)
+			}
+			Action::NominatorBondMore => parachain_staking::Call::<Runtime>::nominator_bond_more(
+				Self::account(&mut input)?,
+				Self::amount(&mut input)?,
+			),
+			Action::NominatorBondLess => parachain_staking::Call::<Runtime>::nominator_bond_less(
+				Self::account(&mut input)?,
+				Self::amount(&mut input)?,
+			),
+		};
+
+		RuntimeHelper::<Runtime>::try_dispatch(context.caller, call, &mut gasometer)?;
+		Ok((ExitSucceed::Returned, Vec::new(), gasometer.used_gas()))
+	}
+}
+
+impl<Runtime> ParachainStakingWrapper<Runtime>
+where
+	Runtime: parachain_staking::Config + pallet_evm::Config,
+	BalanceOf<Runtime>: TryFrom<u128>,
+{
+	fn account(input: &mut EvmDataReader) -> EvmResult<Runtime::AccountId> {
+		Ok(Runtime::AddressMapping::into_account_id(
+			input.read::<H160>()?,
+		))
+	}
+
+	fn amount(input: &mut EvmDataReader) -> EvmResult<BalanceOf<Runtime>> {
+		BalanceOf::<Runtime>::try_from(input.read::<u128>()?)
+			.map_err(|_| error("amount does not fit in the balance type"))
+	}
+
+	fn view(
+		output: EvmDataWriter,
+		gasometer: &Gasometer,
+	) -> Result<(ExitSucceed, Vec<u8>, u64), ExitError> {
+		Ok((ExitSucceed::Returned, output.build(), gasometer.used_gas()))
+	}
+}
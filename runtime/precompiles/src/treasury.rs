@@ -0,0 +1,65 @@
+// Copyright 2019-2021 PureStake Inc.
+// This file is part of Moonbeam.
+
+// Moonbeam is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Moonbeam is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Moonbeam.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Precompile exposing the treasury to the EVM, through the Solidity interface in
+//! `TreasuryInterface.sol`.
+
+use evm::{Context, ExitError, ExitSucceed};
+use pallet_evm::Precompile;
+use precompile_utils::{selectors, EvmDataReader, EvmDataWriter, Gasometer, RuntimeHelper};
+use sp_core::{H160, U256};
+use sp_std::{marker::PhantomData, vec::Vec};
+
+selectors! {
+	/// The functions of `TreasuryInterface.sol`.
+	enum Action {
+		Balance = 0xb69ef8a8,
+		Account = 0x5dab2420,
+	}
+}
+
+/// Exposes the funds of the treasury.
+pub struct TreasuryWrapper<Runtime>(PhantomData<Runtime>);
+
+impl<Runtime> Precompile for TreasuryWrapper<Runtime>
+where
+	Runtime: pallet_treasury::Config + pallet_evm::Config,
+	Runtime::AccountId: Into<H160>,
+	pallet_treasury::BalanceOf<Runtime>: Into<U256>,
+{
+	fn execute(
+		input: &[u8],
+		target_gas: Option<u64>,
+		_context: &Context,
+	) -> Result<(ExitSucceed, Vec<u8>, u64), ExitError> {
+		let (_, action) = EvmDataReader::new_with_selector::<Action>(input)?;
+		let mut gasometer = Gasometer::new(target_gas);
+
+		let output = match action {
+			Action::Balance => {
+				gasometer.record_cost(RuntimeHelper::<Runtime>::db_read_gas_cost())?;
+				let pot: U256 = pallet_treasury::Pallet::<Runtime>::pot().into();
+				EvmDataWriter::new().write(pot)
+			}
+			Action::Account => {
+				let account: H160 = pallet_treasury::Pallet::<Runtime>::account_id().into();
+				EvmDataWriter::new().write(account)
+			}
+		};
+
+		Ok((ExitSucceed::Returned, output.build(), gasometer.used_gas()))
+	}
+}
@@ -0,0 +1,266 @@
+// Copyright 2019-2021 PureStake Inc.
+// This file is part of Moonbeam.
+
+// Moonbeam is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Moonbeam is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Moonbeam.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Solidity ABI encoding and decoding.
+//!
+//! Static values (`address`, `uintN`, `bool`, `bytes32`) take one 32 byte word. Dynamic values
+//! (`bytes` and arrays) take a word holding the offset of their data, which is stored after all
+//! the words of the enclosing tuple.
+
+use crate::{error, EvmResult};
+use sp_core::{H160, H256, U256};
+use sp_std::{convert::TryFrom, vec, vec::Vec};
+
+/// Reads ABI encoded values.
+pub struct EvmDataReader<'a> {
+	input: &'a [u8],
+	cursor: usize,
+}
+
+impl<'a> EvmDataReader<'a> {
+	/// A reader of the ABI encoded `input`.
+	pub fn new(input: &'a [u8]) -> Self {
+		EvmDataReader { input, cursor: 0 }
+	}
+
+	/// Read the selector of the called function from `input`, returning it with a reader of the
+	/// function arguments. Fails if `S` has no function with this selector.
+	pub fn new_with_selector<S: TryFrom<u32>>(input: &'a [u8]) -> EvmResult<(Self, S)> {
+		if input.len() < 4 {
+			return Err(error("tried to parse selector out of bounds"));
+		}
+		let mut selector = [0u8; 4];
+		selector.copy_from_slice(&input[..4]);
+		let selector =
+			S::try_from(u32::from_be_bytes(selector)).map_err(|_| error("unknown selector"))?;
+		Ok((Self::new(&input[4..]), selector))
+	}
+
+	/// Read a value.
+	pub fn read<T: EvmData>(&mut self) -> EvmResult<T> {
+		T::read(self)
+	}
+
+	/// Read the next `len` bytes.
+	fn read_raw_bytes(&mut self, len: usize) -> EvmResult<&'a [u8]> {
+		let end = self
+			.cursor
+			.checked_add(len)
+			.ok_or_else(|| error("tried to parse raw bytes out of bounds"))?;
+		let data = self
+			.input
+			.get(self.cursor..end)
+			.ok_or_else(|| error("tried to parse raw bytes out of bounds"))?;
+		self.cursor = end;
+		Ok(data)
+	}
+
+	fn read_word(&mut self) -> EvmResult<&'a [u8]> {
+		self.read_raw_bytes(32)
+	}
+
+	/// Read the offset of a dynamic value, returning a reader starting at its data.
+	fn read_pointer(&mut self) -> EvmResult<Self> {
+		let offset: u32 = self.read()?;
+		let input = self
+			.input
+			.get(offset as usize..)
+			.ok_or_else(|| error("pointer points out of bounds"))?;
+		Ok(Self::new(input))
+	}
+
+	/// A reader of what comes after the cursor.
+	fn remaining(&self) -> Self {
+		Self::new(&self.input[self.cursor..])
+	}
+}
+
+/// Writes ABI encoded values.
+#[derive(Default)]
+pub struct EvmDataWriter {
+	data: Vec<u8>,
+	/// The data of the dynamic values, with the position of the word holding their offset.
+	tails: Vec<(usize, Vec<u8>)>,
+}
+
+impl EvmDataWriter {
+	/// An empty writer.
+	pub fn new() -> Self {
+		Self::default()
+	}
+
+	/// Write a value.
+	pub fn write<T: EvmData>(mut self, value: T) -> Self {
+		T::write(&mut self, value);
+		self
+	}
+
+	/// The ABI encoding of the written values.
+	pub fn build(self) -> Vec<u8> {
+		let mut output = self.data;
+		for (position, tail) in self.tails {
+			U256::from(output.len()).to_big_endian(&mut output[position..position + 32]);
+			output.extend(tail);
+		}
+		output
+	}
+
+	fn write_word(&mut self, word: &[u8; 32]) {
+		self.data.extend_from_slice(word);
+	}
+
+	/// Write the offset of a dynamic value with this data.
+	fn write_pointer(&mut self, tail: Vec<u8>) {
+		self.tails.push((self.data.len(), tail));
+		self.write_word(&[0u8; 32]);
+	}
+}
+
+/// A type with a Solidity ABI encoding.
+pub trait EvmData: Sized {
+	fn read(reader: &mut EvmDataReader) -> EvmResult<Self>;
+	fn write(writer: &mut EvmDataWriter, value: Self);
+}
+
+impl EvmData for H256 {
+	fn read(reader: &mut EvmDataReader) -> EvmResult<Self> {
+		Ok(H256::from_slice(reader.read_word()?))
+	}
+
+	fn write(writer: &mut EvmDataWriter, value: Self) {
+		writer.write_word(value.as_fixed_bytes());
+	}
+}
+
+impl EvmData for H160 {
+	fn read(reader: &mut EvmDataReader) -> EvmResult<Self> {
+		let word = reader.read_word()?;
+		if word[..12].iter().any(|byte| *byte != 0) {
+			return Err(error("address has dirty high bytes"));
+		}
+		Ok(H160::from_slice(&word[12..]))
+	}
+
+	fn write(writer: &mut EvmDataWriter, value: Self) {
+		H256::write(writer, value.into());
+	}
+}
+
+impl EvmData for U256 {
+	fn read(reader: &mut EvmDataReader) -> EvmResult<Self> {
+		Ok(U256::from_big_endian(reader.read_word()?))
+	}
+
+	fn write(writer: &mut EvmDataWriter, value: Self) {
+		let mut word = [0u8; 32];
+		value.to_big_endian(&mut word);
+		writer.write_word(&word);
+	}
+}
+
+impl EvmData for bool {
+	fn read(reader: &mut EvmDataReader) -> EvmResult<Self> {
+		match U256::read(reader)? {
+			value if value.is_zero() => Ok(false),
+			value if value == U256::one() => Ok(true),
+			_ => Err(error("bool is neither 0 nor 1")),
+		}
+	}
+
+	fn write(writer: &mut EvmDataWriter, value: Self) {
+		U256::write(writer, if value { U256::one() } else { U256::zero() });
+	}
+}
+
+macro_rules! impl_evmdata_for_uints {
+	($($uint:ty),*) => {
+		$(
+			impl EvmData for $uint {
+				fn read(reader: &mut EvmDataReader) -> EvmResult<Self> {
+					let value = U256::read(reader)?;
+					if value > U256::from(<$uint>::max_value()) {
+						return Err(error(concat!("value does not fit in ", stringify!($uint))));
+					}
+					Ok(value.low_u128() as $uint)
+				}
+
+				fn write(writer: &mut EvmDataWriter, value: Self) {
+					U256::write(writer, value.into());
+				}
+			}
+		)*
+	};
+}
+
+impl_evmdata_for_uints!(u8, u16, u32, u64, u128);
+
+/// A Solidity `bytes` value, as opposed to `Vec<u8>` which is a `uint8[]`.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Bytes(pub Vec<u8>);
+
+impl From<Vec<u8>> for Bytes {
+	fn from(bytes: Vec<u8>) -> Self {
+		Bytes(bytes)
+	}
+}
+
+impl From<&[u8]> for Bytes {
+	fn from(bytes: &[u8]) -> Self {
+		Bytes(bytes.to_vec())
+	}
+}
+
+impl EvmData for Bytes {
+	fn read(reader: &mut EvmDataReader) -> EvmResult<Self> {
+		let mut inner = reader.read_pointer()?;
+		let len: u32 = inner.read()?;
+		Ok(Bytes(inner.read_raw_bytes(len as usize)?.to_vec()))
+	}
+
+	fn write(writer: &mut EvmDataWriter, value: Self) {
+		let mut tail = EvmDataWriter::new().write(value.0.len() as u32).build();
+		tail.extend_from_slice(&value.0);
+		// Pad the data to a whole number of words.
+		tail.resize((tail.len() + 31) / 32 * 32, 0);
+		writer.write_pointer(tail);
+	}
+}
+
+impl<T: EvmData> EvmData for Vec<T> {
+	fn read(reader: &mut EvmDataReader) -> EvmResult<Self> {
+		let mut inner = reader.read_pointer()?;
+		let len: u32 = inner.read()?;
+		// The offsets of dynamic items are relative to the start of the items.
+		let mut items = inner.remaining();
+		// Every item takes at least a word, so `len` is not trusted to allocate.
+		let mut values = vec![];
+		for _ in 0..len {
+			values.push(items.read()?);
+		}
+		Ok(values)
+	}
+
+	fn write(writer: &mut EvmDataWriter, value: Self) {
+		let mut items = EvmDataWriter::new();
+		let len = value.len() as u32;
+		for item in value {
+			items = items.write(item);
+		}
+		let mut tail = EvmDataWriter::new().write(len).build();
+		tail.extend(items.build());
+		writer.write_pointer(tail);
+	}
+}
@@ -0,0 +1,189 @@
+// Copyright 2019-2021 PureStake Inc.
+// This file is part of Moonbeam.
+
+// Moonbeam is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Moonbeam is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Moonbeam.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Utilities to write precompiles exposing Substrate functionality through a Solidity interface:
+//! decoding of the ABI encoded input, encoding of the output, selection of the called function and
+//! gas accounting of storage accesses and dispatched calls.
+
+#![cfg_attr(not(feature = "std"), no_std)]
+
+use evm::ExitError;
+use frame_support::{
+	dispatch::{Dispatchable, GetDispatchInfo, PostDispatchInfo},
+	traits::Get,
+};
+use pallet_evm::{AddressMapping, GasWeightMapping};
+use sp_core::H160;
+use sp_std::{borrow::Cow, marker::PhantomData};
+
+mod data;
+
+pub use data::{Bytes, EvmData, EvmDataReader, EvmDataWriter};
+
+#[cfg(test)]
+mod tests;
+
+/// The result of the functions of a precompile.
+pub type EvmResult<T = ()> = Result<T, ExitError>;
+
+/// An `ExitError` carrying the revert reason `text`.
+pub fn error<T: Into<Cow<'static, str>>>(text: T) -> ExitError {
+	ExitError::Other(text.into())
+}
+
+/// Declare the functions of a Solidity interface, as an enum with the selector of each function as
+/// the discriminant of its variant. The selector is the first four bytes of the Keccak-256 hash of
+/// the function signature. The enum can be read with `EvmDataReader::new_with_selector`.
+///
+/// ```
+/// precompile_utils::selectors! {
+/// 	pub enum Action {
+/// 		/// `transfer(address,uint256)`
+/// 		Transfer = 0xa9059cbb,
+/// 	}
+/// }
+///
+/// assert_eq!(Action::Transfer.selector(), 0xa9059cbb);
+/// ```
+#[macro_export]
+macro_rules! selectors {
+	(
+		$(#[$meta:meta])*
+		$vis:vis enum $name:ident {
+			$( $(#[$variant_meta:meta])* $variant:ident = $selector:literal, )*
+		}
+	) => {
+		$(#[$meta])*
+		#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+		#[repr(u32)]
+		$vis enum $name {
+			$( $(#[$variant_meta])* $variant = $selector, )*
+		}
+
+		impl $name {
+			/// The selector of the function.
+			#[allow(dead_code)]
+			pub fn selector(self) -> u32 {
+				self as u32
+			}
+		}
+
+		impl ::core::convert::TryFrom<u32> for $name {
+			type Error = ();
+
+			fn try_from(selector: u32) -> Result<Self, ()> {
+				match selector {
+					$( $selector => Ok($name::$variant), )*
+					_ => Err(()),
+				}
+			}
+		}
+	};
+}
+
+/// Keeps track of the gas used by a precompile, failing once it exceeds the gas it was given.
+#[derive(Clone, Copy, Debug)]
+pub struct Gasometer {
+	used_gas: u64,
+	target_gas: Option<u64>,
+}
+
+impl Gasometer {
+	/// A gasometer of a precompile given `target_gas`, or unlimited gas when `None`.
+	pub fn new(target_gas: Option<u64>) -> Self {
+		Gasometer {
+			used_gas: 0,
+			target_gas,
+		}
+	}
+
+	/// The gas used so far.
+	pub fn used_gas(&self) -> u64 {
+		self.used_gas
+	}
+
+	/// Record `cost` as used. Fails with `OutOfGas` if it is more than the remaining gas.
+	pub fn record_cost(&mut self, cost: u64) -> EvmResult {
+		let used_gas = self.used_gas.checked_add(cost).ok_or(ExitError::OutOfGas)?;
+		match self.target_gas {
+			Some(target_gas) if used_gas > target_gas => Err(ExitError::OutOfGas),
+			_ => {
+				self.used_gas = used_gas;
+				Ok(())
+			}
+		}
+	}
+
+	/// The gas left, or `None` when the gas is unlimited.
+	pub fn remaining_gas(&self) -> Option<u64> {
+		self.target_gas
+			.map(|target_gas| target_gas.saturating_sub(self.used_gas))
+	}
+}
+
+/// Gas accounting based on the weights of a runtime.
+pub struct RuntimeHelper<Runtime>(PhantomData<Runtime>);
+
+impl<Runtime> RuntimeHelper<Runtime>
+where
+	Runtime: pallet_evm::Config,
+{
+	/// The gas cost of a storage read.
+	pub fn db_read_gas_cost() -> u64 {
+		Runtime::GasWeightMapping::weight_to_gas(
+			<Runtime as frame_system::Config>::DbWeight::get().read,
+		)
+	}
+
+	/// The gas cost of a storage write.
+	pub fn db_write_gas_cost() -> u64 {
+		Runtime::GasWeightMapping::weight_to_gas(
+			<Runtime as frame_system::Config>::DbWeight::get().write,
+		)
+	}
+}
+
+impl<Runtime> RuntimeHelper<Runtime>
+where
+	Runtime: pallet_evm::Config,
+	Runtime::Call: Dispatchable<PostInfo = PostDispatchInfo> + GetDispatchInfo,
+	<Runtime::Call as Dispatchable>::Origin: From<Option<Runtime::AccountId>>,
+{
+	/// Dispatch `call` with the account of `origin` as the signed origin, recording its weight as
+	/// gas. The call is not dispatched if its weight is more than the remaining gas, and its
+	/// actual weight is recorded when it reports one. A failed call reverts with its error.
+	pub fn try_dispatch<Call>(origin: H160, call: Call, gasometer: &mut Gasometer) -> EvmResult
+	where
+		Call: Into<Runtime::Call>,
+	{
+		let call = call.into();
+		let info = call.get_dispatch_info();
+		if let Some(gas) = gasometer.remaining_gas() {
+			if info.weight > Runtime::GasWeightMapping::gas_to_weight(gas) {
+				return Err(ExitError::OutOfGas);
+			}
+		}
+
+		let origin = Runtime::AddressMapping::into_account_id(origin);
+		let post_info = call
+			.dispatch(Some(origin).into())
+			.map_err(|e| error(<&'static str>::from(e.error)))?;
+
+		gasometer.record_cost(Runtime::GasWeightMapping::weight_to_gas(
+			post_info.actual_weight.unwrap_or(info.weight),
+		))
+	}
+}
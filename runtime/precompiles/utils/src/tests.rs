@@ -0,0 +1,182 @@
+// Copyright 2019-2021 PureStake Inc.
+// This file is part of Moonbeam.
+
+// Moonbeam is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Moonbeam is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Moonbeam.  If not, see <http://www.gnu.org/licenses/>.
+
+use crate::*;
+use evm::ExitError;
+use hex_literal::hex;
+use sp_core::{H160, H256, U256};
+
+selectors! {
+	enum Action {
+		Transfer = 0xa9059cbb,
+		BalanceOf = 0x70a08231,
+	}
+}
+
+#[test]
+fn reads_selector_and_arguments() {
+	// transfer(0x1111..., 42)
+	let input = hex!(
+		"a9059cbb
+		0000000000000000000000001111111111111111111111111111111111111111
+		000000000000000000000000000000000000000000000000000000000000002a"
+	);
+
+	let (mut reader, action) = EvmDataReader::new_with_selector::<Action>(&input).unwrap();
+	assert_eq!(action, Action::Transfer);
+	assert_eq!(reader.read::<H160>().unwrap(), H160::repeat_byte(0x11));
+	assert_eq!(reader.read::<U256>().unwrap(), U256::from(42));
+	assert!(reader.read::<U256>().is_err());
+}
+
+#[test]
+fn rejects_unknown_or_truncated_selector() {
+	assert!(EvmDataReader::new_with_selector::<Action>(&hex!("12345678")).is_err());
+	assert!(EvmDataReader::new_with_selector::<Action>(&hex!("a9059c")).is_err());
+	assert_eq!(Action::BalanceOf.selector(), 0x70a08231);
+}
+
+#[test]
+fn rejects_address_with_dirty_high_bytes() {
+	let input = hex!("0000000000000000000000011111111111111111111111111111111111111111");
+	assert!(EvmDataReader::new(&input).read::<H160>().is_err());
+}
+
+#[test]
+fn checks_uint_range() {
+	let input = EvmDataWriter::new()
+		.write(U256::from(u32::max_value()) + 1)
+		.build();
+	assert!(EvmDataReader::new(&input).read::<u32>().is_err());
+	assert_eq!(
+		EvmDataReader::new(&input).read::<u64>().unwrap(),
+		u32::max_value() as u64 + 1
+	);
+}
+
+#[test]
+fn checks_bool_value() {
+	let input = EvmDataWriter::new().write(U256::from(2)).build();
+	assert!(EvmDataReader::new(&input).read::<bool>().is_err());
+}
+
+#[test]
+fn static_values_round_trip() {
+	let output = EvmDataWriter::new()
+		.write(H160::repeat_byte(0x22))
+		.write(H256::repeat_byte(0x33))
+		.write(true)
+		.write(7u8)
+		.write(u128::max_value())
+		.build();
+	assert_eq!(output.len(), 5 * 32);
+
+	let mut reader = EvmDataReader::new(&output);
+	assert_eq!(reader.read::<H160>().unwrap(), H160::repeat_byte(0x22));
+	assert_eq!(reader.read::<H256>().unwrap(), H256::repeat_byte(0x33));
+	assert_eq!(reader.read::<bool>().unwrap(), true);
+	assert_eq!(reader.read::<u8>().unwrap(), 7);
+	assert_eq!(reader.read::<u128>().unwrap(), u128::max_value());
+}
+
+#[test]
+fn writes_bytes_like_solidity() {
+	// abi.encode(uint256(1), bytes("hello"))
+	let expected = hex!(
+		"0000000000000000000000000000000000000000000000000000000000000001
+		0000000000000000000000000000000000000000000000000000000000000040
+		0000000000000000000000000000000000000000000000000000000000000005
+		68656c6c6f000000000000000000000000000000000000000000000000000000"
+	);
+	let output = EvmDataWriter::new()
+		.write(U256::one())
+		.write(Bytes::from(&b"hello"[..]))
+		.build();
+	assert_eq!(output, expected.to_vec());
+
+	let mut reader = EvmDataReader::new(&output);
+	assert_eq!(reader.read::<U256>().unwrap(), U256::one());
+	assert_eq!(reader.read::<Bytes>().unwrap(), Bytes(b"hello".to_vec()));
+}
+
+#[test]
+fn rejects_bytes_out_of_bounds() {
+	// A length of 5 with only 4 bytes of data.
+	let input = hex!(
+		"0000000000000000000000000000000000000000000000000000000000000020
+		0000000000000000000000000000000000000000000000000000000000000005
+		68656c6c"
+	);
+	assert!(EvmDataReader::new(&input).read::<Bytes>().is_err());
+}
+
+#[test]
+fn writes_arrays_like_solidity() {
+	// abi.encode(new uint256[](2) = [1, 2])
+	let expected = hex!(
+		"0000000000000000000000000000000000000000000000000000000000000020
+		0000000000000000000000000000000000000000000000000000000000000002
+		0000000000000000000000000000000000000000000000000000000000000001
+		0000000000000000000000000000000000000000000000000000000000000002"
+	);
+	let output = EvmDataWriter::new()
+		.write(vec![U256::from(1), U256::from(2)])
+		.build();
+	assert_eq!(output, expected.to_vec());
+	assert_eq!(
+		EvmDataReader::new(&output).read::<Vec<U256>>().unwrap(),
+		vec![U256::from(1), U256::from(2)]
+	);
+}
+
+#[test]
+fn arrays_of_dynamic_values_round_trip() {
+	let values = vec![
+		Bytes(b"a".to_vec()),
+		Bytes(vec![0xff; 40]),
+		Bytes(Vec::new()),
+	];
+	let output = EvmDataWriter::new()
+		.write(values.clone())
+		.write(H160::repeat_byte(0x44))
+		.build();
+
+	let mut reader = EvmDataReader::new(&output);
+	assert_eq!(reader.read::<Vec<Bytes>>().unwrap(), values);
+	assert_eq!(reader.read::<H160>().unwrap(), H160::repeat_byte(0x44));
+}
+
+#[test]
+fn gasometer_fails_past_target_gas() {
+	let mut gasometer = Gasometer::new(Some(100));
+	assert!(gasometer.record_cost(60).is_ok());
+	assert_eq!(gasometer.remaining_gas(), Some(40));
+	assert!(matches!(
+		gasometer.record_cost(41),
+		Err(ExitError::OutOfGas)
+	));
+	assert_eq!(gasometer.used_gas(), 60);
+	assert!(gasometer.record_cost(40).is_ok());
+	assert_eq!(gasometer.remaining_gas(), Some(0));
+}
+
+#[test]
+fn gasometer_without_target_gas_is_unlimited() {
+	let mut gasometer = Gasometer::new(None);
+	assert!(gasometer.record_cost(u64::max_value()).is_ok());
+	assert_eq!(gasometer.remaining_gas(), None);
+	assert!(gasometer.record_cost(1).is_err());
+}
@@ -28,12 +28,16 @@
 #[cfg(feature = "std")]
 include!(concat!(env!("OUT_DIR"), "/wasm_binary.rs"));
 
+use cumulus_token_dealer::{
+	AccountKey20Aliases, MappedAccountId32, ParentIsAccount, ParkFailedDeposits,
+	ZeroPaddedAccountKey20,
+};
 use fp_rpc::TransactionStatus;
 use frame_support::{
 	construct_runtime,
 	pallet_prelude::PhantomData,
 	parameter_types,
-	traits::{Get, Randomness},
+	traits::{Filter, Get, GetCallMetadata, InstanceFilter, Randomness, StorageMapShim},
 	weights::{constants::WEIGHT_PER_SECOND, IdentityFee, Weight},
 };
 use frame_system::{EnsureOneOf, EnsureRoot};
@@ -47,19 +51,31 @@ use pallet_evm::{
 use pallet_transaction_payment::CurrencyAdapter;
 pub use parachain_staking::{InflationInfo, Range};
 use parity_scale_codec::{Decode, Encode};
+use polkadot_parachain::primitives::Sibling;
+use precompiles::HardFork;
 use sha3::{Digest, Keccak256};
 use sp_api::impl_runtime_apis;
 use sp_core::{u32_trait::*, OpaqueMetadata, H160, H256, U256};
 use sp_runtime::{
 	create_runtime_str, generic, impl_opaque_keys,
-	traits::{BlakeTwo256, Block as BlockT, IdentifyAccount, IdentityLookup, Verify},
-	transaction_validity::{TransactionSource, TransactionValidity},
-	ApplyExtrinsicResult, Perbill,
+	traits::{
+		AccountIdConversion, BlakeTwo256, Block as BlockT, IdentifyAccount, IdentityLookup, Verify,
+	},
+	transaction_validity::{
+		InvalidTransaction, TransactionPriority, TransactionSource, TransactionValidity,
+	},
+	ApplyExtrinsicResult, ModuleId, Perbill, Permill, RuntimeDebug,
 };
 use sp_std::{convert::TryFrom, prelude::*};
 #[cfg(feature = "std")]
 use sp_version::NativeVersion;
 use sp_version::RuntimeVersion;
+use xcm::v0::{Junction, MultiLocation, NetworkId};
+use xcm_builder::{
+	CurrencyAdapter as XcmCurrencyAdapter, IsConcrete, LocationInverter, NativeAsset,
+	SiblingParachainConvertsVia, SovereignSignedViaLocation,
+};
+use xcm_executor::XcmExecutor;
 
 #[cfg(any(feature = "std", test))]
 pub use sp_runtime::BuildStorage;
@@ -68,7 +84,32 @@ pub use sp_runtime::BuildStorage;
 pub type BlockNumber = u32;
 
 /// Alias to 512-bit hash when used in the context of a transaction signature on the chain.
-pub type Signature = account::EthereumSignature;
+pub type Signature = account::MultiEthereumSignature<SigningDomain>;
+
+/// The chain wallets sign EIP-712 typed data for: the domain of its Ethereum chain id and runtime
+/// spec version, and its calls.
+#[derive(Clone, Eq, PartialEq, Debug)]
+pub struct SigningDomain;
+
+impl account::Eip712Chain for SigningDomain {
+	fn domain() -> account::Eip712Domain {
+		account::Eip712Domain {
+			chain_id: <Runtime as pallet_evm::Config>::ChainId::get(),
+			spec_version: VERSION.spec_version,
+		}
+	}
+
+	fn call_info(payload: &[u8]) -> Option<account::CallInfo> {
+		let mut input = payload;
+		let call = Call::decode(&mut input).ok()?;
+		let metadata = call.get_call_metadata();
+		Some(account::CallInfo {
+			pallet: metadata.pallet_name,
+			function: metadata.function_name,
+			len: payload.len() - input.len(),
+		})
+	}
+}
 
 /// Some way of identifying an account on the chain. We intentionally make it equivalent
 /// to the public key of our transaction signing scheme.
@@ -104,7 +145,9 @@ pub mod opaque {
 	pub type Block = generic::Block<Header, UncheckedExtrinsic>;
 
 	impl_opaque_keys! {
-		pub struct SessionKeys {}
+		pub struct SessionKeys {
+			pub author_inherent: AuthorInherent,
+		}
 	}
 }
 
@@ -113,10 +156,11 @@ pub const VERSION: RuntimeVersion = RuntimeVersion {
 	spec_name: create_runtime_str!("moonbeam"),
 	impl_name: create_runtime_str!("moonbeam"),
 	authoring_version: 3,
-	spec_version: 32,
+	spec_version: 33,
 	impl_version: 1,
 	apis: RUNTIME_API_VERSIONS,
-	transaction_version: 2,
+	// Extrinsics are signed with `MultiEthereumSignature`, which encodes differently.
+	transaction_version: 3,
 };
 
 /// The version information used to identify this runtime when compiled natively.
@@ -189,6 +233,112 @@ impl pallet_utility::Config for Runtime {
 	type WeightInfo = ();
 }
 
+parameter_types! {
+	// A pending multisig operation is one storage item of 88 bytes, and each signatory adds the
+	// 20 bytes of its account.
+	pub const MultisigDepositBase: Balance = GLMR + 88 * PreimageByteDeposit::get();
+	pub const MultisigDepositFactor: Balance = 20 * PreimageByteDeposit::get();
+	pub const MaxSignatories: u16 = 100;
+}
+
+/// Multisig accounts are H160 accounts like any other: the account of a set of signatories and a
+/// threshold is the first 20 bytes of
+/// `blake2_256(b"modlpy/utilisuba" ++ SCALE(sorted signatories) ++ SCALE(threshold as u16))`.
+impl pallet_multisig::Config for Runtime {
+	type Event = Event;
+	type Call = Call;
+	type Currency = Balances;
+	type DepositBase = MultisigDepositBase;
+	type DepositFactor = MultisigDepositFactor;
+	type MaxSignatories = MaxSignatories;
+	type WeightInfo = ();
+}
+
+/// The calls a proxy account can make on behalf of the account it is a proxy of.
+#[derive(Copy, Clone, Eq, PartialEq, Ord, PartialOrd, Encode, Decode, RuntimeDebug)]
+pub enum ProxyType {
+	/// All calls.
+	Any,
+	/// All calls except those moving funds: balance and relay token transfers, EVM calls and XCM
+	/// transfers.
+	NonTransfer,
+	/// Democracy and council calls.
+	Governance,
+	/// Staking calls, such as nominating or going offline, keeping the stash key cold.
+	Staking,
+	/// Author mapping calls, managing the author keys of a collator.
+	AuthorMapping,
+}
+
+impl Default for ProxyType {
+	fn default() -> Self {
+		ProxyType::Any
+	}
+}
+
+impl InstanceFilter<Call> for ProxyType {
+	fn filter(&self, c: &Call) -> bool {
+		match self {
+			ProxyType::Any => true,
+			ProxyType::NonTransfer => !matches!(
+				c,
+				Call::Balances(..)
+					| Call::RelayToken(..)
+					| Call::EVM(..) | Call::Ethereum(..)
+					| Call::TokenDealer(..)
+			),
+			ProxyType::Governance => matches!(
+				c,
+				Call::Democracy(..)
+					| Call::CouncilCollective(..)
+					| Call::TechComitteeCollective(..)
+					| Call::Utility(..)
+			),
+			ProxyType::Staking => matches!(c, Call::ParachainStaking(..) | Call::Utility(..)),
+			ProxyType::AuthorMapping => matches!(c, Call::AuthorMapping(..)),
+		}
+	}
+
+	fn is_superset(&self, o: &Self) -> bool {
+		match (self, o) {
+			(x, y) if x == y => true,
+			(ProxyType::Any, _) => true,
+			(_, ProxyType::Any) => false,
+			// The other types only allow calls that do not move funds
+			(ProxyType::NonTransfer, _) => true,
+			_ => false,
+		}
+	}
+}
+
+parameter_types! {
+	// A list of proxies is one storage item, and each proxy adds its 20 bytes account, its proxy
+	// type and its 4 bytes delay.
+	pub const ProxyDepositBase: Balance = GLMR + 8 * PreimageByteDeposit::get();
+	pub const ProxyDepositFactor: Balance = 25 * PreimageByteDeposit::get();
+	pub const MaxProxies: u16 = 32;
+	// Each announcement adds the 20 bytes account of the proxied account, the 32 bytes call hash
+	// and the 4 bytes block number.
+	pub const AnnouncementDepositBase: Balance = GLMR + 8 * PreimageByteDeposit::get();
+	pub const AnnouncementDepositFactor: Balance = 56 * PreimageByteDeposit::get();
+	pub const MaxPending: u32 = 32;
+}
+
+impl pallet_proxy::Config for Runtime {
+	type Event = Event;
+	type Call = Call;
+	type Currency = Balances;
+	type ProxyType = ProxyType;
+	type ProxyDepositBase = ProxyDepositBase;
+	type ProxyDepositFactor = ProxyDepositFactor;
+	type MaxProxies = MaxProxies;
+	type WeightInfo = ();
+	type MaxPending = MaxPending;
+	type CallHasher = BlakeTwo256;
+	type AnnouncementDepositBase = AnnouncementDepositBase;
+	type AnnouncementDepositFactor = AnnouncementDepositFactor;
+}
+
 parameter_types! {
 	pub const MinimumPeriod: u64 = 1;
 }
@@ -223,7 +373,7 @@ parameter_types! {
 }
 
 impl pallet_transaction_payment::Config for Runtime {
-	type OnChargeTransaction = CurrencyAdapter<Balances, ()>;
+	type OnChargeTransaction = CurrencyAdapter<Balances, Treasury>;
 	type TransactionByteFee = TransactionByteFee;
 	type WeightToFee = IdentityFee<Balance>;
 	type FeeMultiplierUpdate = ();
@@ -262,8 +412,72 @@ parameter_types! {
 		= U256::from(NORMAL_DISPATCH_RATIO * MAXIMUM_BLOCK_WEIGHT / WEIGHT_PER_GAS);
 }
 
+/// The gas used by the previous block, as its last Ethereum receipt records it.
+pub struct EthereumPreviousBlockGas;
+
+impl pallet_fee_market::PreviousBlockGas for EthereumPreviousBlockGas {
+	fn gas_used() -> U256 {
+		// The receipts of the previous block stay until the current block is stored.
+		Ethereum::current_receipts()
+			.and_then(|receipts| receipts.last().map(|receipt| receipt.used_gas))
+			.unwrap_or_default()
+	}
+}
+
+parameter_types! {
+	pub const MaxGasPriceChangeDenominator: u32 = 8;
+}
+
+impl pallet_fee_market::Config for Runtime {
+	type Event = Event;
+	type BlockGasLimit = BlockGasLimit;
+	type PreviousBlockGas = EthereumPreviousBlockGas;
+	// Root or 2/3 of the council tune the fee market.
+	type GovernanceOrigin = EnsureOneOf<
+		AccountId,
+		EnsureRoot<AccountId>,
+		pallet_collective::EnsureProportionAtLeast<_2, _3, AccountId, CouncilInstance>,
+	>;
+	type MaxChangeDenominator = MaxGasPriceChangeDenominator;
+	type Currency = Balances;
+	type Treasury = Treasury;
+	type FindAuthor = AuthorInherent;
+}
+
+/// The calls contracts can make through the dispatch precompile. Anything touching governance,
+/// staking or privileged pallets stays out of reach of contracts.
+pub struct DispatchPrecompileFilter;
+
+impl Filter<Call> for DispatchPrecompileFilter {
+	fn filter(call: &Call) -> bool {
+		matches!(
+			call,
+			Call::System(frame_system::Call::remark(..))
+				| Call::Balances(pallet_balances::Call::transfer(..))
+				| Call::Balances(pallet_balances::Call::transfer_keep_alive(..))
+		)
+	}
+}
+
+/// The spec versions from which the EVM precompiles follow each Ethereum hard fork, most recent
+/// first. Switching to a new hard fork takes adding it here with the spec version introducing it.
+const PRECOMPILES_HARD_FORKS: &[(u32, HardFork)] = &[(0, HardFork::Istanbul)];
+
+/// The hard fork of the EVM precompiles at the current spec version.
+pub struct PrecompilesHardFork;
+
+impl Get<HardFork> for PrecompilesHardFork {
+	fn get() -> HardFork {
+		PRECOMPILES_HARD_FORKS
+			.iter()
+			.find(|(spec_version, _)| VERSION.spec_version >= *spec_version)
+			.map(|(_, fork)| *fork)
+			.unwrap_or(HardFork::Istanbul)
+	}
+}
+
 impl pallet_evm::Config for Runtime {
-	type FeeCalculator = ();
+	type FeeCalculator = FeeMarket;
 	type GasWeightMapping = MoonbeamGasWeightMapping;
 	type CallOrigin = EnsureAddressRoot<AccountId>;
 	type WithdrawOrigin = EnsureAddressNever<AccountId>;
@@ -271,9 +485,10 @@ impl pallet_evm::Config for Runtime {
 	type Currency = Balances;
 	type Event = Event;
 	type Runner = pallet_evm::runner::stack::Runner<Self>;
-	type Precompiles = precompiles::MoonbeamPrecompiles<Self>;
+	type Precompiles =
+		precompiles::MoonbeamPrecompiles<Self, DispatchPrecompileFilter, PrecompilesHardFork>;
 	type ChainId = EthereumChainId;
-	type OnChargeTransaction = ();
+	type OnChargeTransaction = FeeMarket;
 	type BlockGasLimit = BlockGasLimit;
 }
 
@@ -396,7 +611,7 @@ impl pallet_democracy::Config for Runtime {
 	type VetoOrigin = pallet_collective::EnsureMember<AccountId, TechCommitteeInstance>;
 	type CooloffPeriod = CooloffPeriod;
 	type PreimageByteDeposit = PreimageByteDeposit;
-	type Slash = ();
+	type Slash = Treasury;
 	type InstantAllowed = InstantAllowed;
 	type Scheduler = Scheduler;
 	type MaxVotes = MaxVotes;
@@ -406,6 +621,49 @@ impl pallet_democracy::Config for Runtime {
 	type MaxProposals = MaxProposals;
 }
 
+parameter_types! {
+	pub const TreasuryModuleId: ModuleId = ModuleId(*b"py/trsry");
+	pub const ProposalBond: Permill = Permill::from_percent(5);
+	pub const ProposalBondMinimum: Balance = GLMR;
+	pub const SpendPeriod: BlockNumber = 6 * BLOCKS_PER_DAY;
+	pub const TreasuryBurn: Permill = Permill::zero();
+	pub const MaxApprovals: u32 = 100;
+}
+
+/// The treasury collects the substrate transaction fees, the treasury share of the EVM fees and
+/// the slashed funds of collators and democracy proposals.
+impl pallet_treasury::Config for Runtime {
+	type ModuleId = TreasuryModuleId;
+	type Currency = Balances;
+	// Root or 3/5 of the council approve spending proposals.
+	type ApproveOrigin = EnsureOneOf<
+		AccountId,
+		EnsureRoot<AccountId>,
+		pallet_collective::EnsureProportionAtLeast<_3, _5, AccountId, CouncilInstance>,
+	>;
+	// Root, a majority of the council or 2/3 of the technical committee reject them.
+	type RejectOrigin = EnsureOneOf<
+		AccountId,
+		EnsureRoot<AccountId>,
+		EnsureOneOf<
+			AccountId,
+			pallet_collective::EnsureProportionMoreThan<_1, _2, AccountId, CouncilInstance>,
+			pallet_collective::EnsureProportionAtLeast<_2, _3, AccountId, TechCommitteeInstance>,
+		>,
+	>;
+	type Event = Event;
+	// The bonds of rejected proposals stay in the treasury.
+	type OnSlash = Treasury;
+	type ProposalBond = ProposalBond;
+	type ProposalBondMinimum = ProposalBondMinimum;
+	type SpendPeriod = SpendPeriod;
+	type Burn = TreasuryBurn;
+	type BurnDestination = ();
+	type SpendFunds = ();
+	type WeightInfo = ();
+	type MaxApprovals = MaxApprovals;
+}
+
 pub struct TransactionConverter;
 
 impl fp_rpc::ConvertTransaction<UncheckedExtrinsic> for TransactionConverter {
@@ -442,12 +700,112 @@ impl cumulus_pallet_parachain_system::Config for Runtime {
 	type Event = Event;
 	type OnValidationData = ();
 	type SelfParaId = ParachainInfo;
-	type DownwardMessageHandlers = ();
-	type HrmpMessageHandlers = ();
+	type DownwardMessageHandlers = TokenDealer;
+	type HrmpMessageHandlers = TokenDealer;
 }
 
 impl parachain_info::Config for Runtime {}
 
+/// The relay parent from the validation data of the parachain system inherent.
+pub struct ValidationDataRelayParent;
+
+impl pallet_relay_randomness::RelayParent for ValidationDataRelayParent {
+	fn relay_parent_storage_root() -> Option<H256> {
+		ParachainSystem::validation_data().map(|data| data.relay_parent_storage_root)
+	}
+}
+
+parameter_types! {
+	pub const MaxRandomnessRequestDelay: BlockNumber = BLOCKS_PER_DAY;
+	pub const RandomnessRequestDeposit: Balance = 1 * GLMR;
+	pub const RandomnessRequestExpiry: BlockNumber = BLOCKS_PER_DAY;
+}
+
+impl pallet_relay_randomness::Config for Runtime {
+	type Event = Event;
+	type RelayParent = ValidationDataRelayParent;
+	type MaxRequestDelay = MaxRandomnessRequestDelay;
+	type Currency = Balances;
+	type RequestDeposit = RandomnessRequestDeposit;
+	type RequestExpiry = RandomnessRequestExpiry;
+}
+
+parameter_types! {
+	pub const RelayNetwork: NetworkId = NetworkId::Polkadot;
+	pub RelayLocation: MultiLocation = MultiLocation::X1(Junction::Parent);
+	pub Ancestry: MultiLocation = Junction::Parachain {
+		id: ParachainInfo::parachain_id().into()
+	}.into();
+	pub const TokenDealerModuleId: ModuleId = ModuleId(*b"tkdealer");
+	pub const TransferPeriod: BlockNumber = BLOCKS_PER_DAY;
+	pub RelayChainAccount: AccountId = ModuleId(*b"py/relay").into_account();
+}
+
+/// The balances instance holding the relay chain token.
+pub type RelayTokenInstance = pallet_balances::Instance1;
+
+/// The relay chain token is held in a balances instance of its own, in the relay chain's units
+/// (12 decimals), so that it is never credited as GLMR (18 decimals).
+impl pallet_balances::Config<RelayTokenInstance> for Runtime {
+	type MaxLocks = MaxLocks;
+	type Balance = Balance;
+	type Event = Event;
+	type DustRemoval = ();
+	type ExistentialDeposit = ExistentialDeposit;
+	type AccountStore = StorageMapShim<
+		pallet_balances::Account<Runtime, RelayTokenInstance>,
+		frame_system::Provider<Runtime>,
+		AccountId,
+		pallet_balances::AccountData<Balance>,
+	>;
+	type WeightInfo = ();
+}
+
+/// The relay chain's sovereign account is derived from a `ModuleId`, the sibling parachains' are
+/// derived from their ids, and local accounts are `AccountKey20` junctions or zero padded
+/// `AccountId32` junctions.
+pub type LocationToAccountId = (
+	ParentIsAccount<AccountId, RelayChainAccount>,
+	SiblingParachainConvertsVia<Sibling, AccountId>,
+	AccountKey20Aliases<RelayNetwork, AccountId>,
+	MappedAccountId32<RelayNetwork, AccountId, ZeroPaddedAccountKey20>,
+);
+
+pub struct XcmConfig;
+impl xcm_executor::Config for XcmConfig {
+	type Call = Call;
+	type XcmSender = TokenDealer;
+	// The relay chain token is held by `RelayToken`. Deposits to beneficiaries without a local
+	// account are parked by the token dealer.
+	type AssetTransactor = ParkFailedDeposits<
+		XcmCurrencyAdapter<RelayToken, IsConcrete<RelayLocation>, LocationToAccountId, AccountId>,
+		IsConcrete<RelayLocation>,
+		Runtime,
+	>;
+	type OriginConverter = SovereignSignedViaLocation<LocationToAccountId, Origin>;
+	type IsReserve = NativeAsset;
+	type IsTeleporter = ();
+	type LocationInverter = LocationInverter<Ancestry>;
+}
+
+impl cumulus_token_dealer::Config for Runtime {
+	type Event = Event;
+	type Currency = RelayToken;
+	type UpwardMessageSender = ParachainSystem;
+	type HrmpMessageSender = ParachainSystem;
+	type XcmExecutor = XcmExecutor<XcmConfig>;
+	type AccountIdConverter = LocationToAccountId;
+	type RelayNetwork = RelayNetwork;
+	type ModuleId = TokenDealerModuleId;
+	// Root or 2/3 of the council manage the transfer controls.
+	type GovernanceOrigin = EnsureOneOf<
+		AccountId,
+		EnsureRoot<AccountId>,
+		pallet_collective::EnsureProportionAtLeast<_2, _3, AccountId, CouncilInstance>,
+	>;
+	type TransferPeriod = TransferPeriod;
+}
+
 /// GLMR, the native token, uses 18 decimals of precision.
 pub const GLMR: Balance = 1_000_000_000_000_000_000;
 
@@ -470,6 +828,10 @@ parameter_types! {
 	pub const MinCollatorStk: u128 = 1_000 * GLMR;
 	/// Minimum stake required to be reserved to be a nominator is 5
 	pub const MinNominatorStk: u128 = 5 * GLMR;
+	/// Collators lose 10% of their own bond for equivocating
+	pub const SlashFraction: Perbill = Perbill::from_percent(10);
+	/// 30% of the issuance of each round goes to the treasury
+	pub const StakingReserveFraction: Perbill = Perbill::from_percent(30);
 }
 impl parachain_staking::Config for Runtime {
 	type Event = Event;
@@ -485,6 +847,10 @@ impl parachain_staking::Config for Runtime {
 	type MinCollatorCandidateStk = MinCollatorStk;
 	type MinNomination = MinNominatorStk;
 	type MinNominatorStk = MinNominatorStk;
+	type SlashFraction = SlashFraction;
+	type OnSlash = Treasury;
+	type ReserveFraction = StakingReserveFraction;
+	type OnReserve = Treasury;
 }
 impl author_inherent::Config for Runtime {
 	type EventHandler = ParachainStaking;
@@ -493,6 +859,7 @@ impl author_inherent::Config for Runtime {
 	// in the preliminary check. The final check including the filtering happens during execution.
 	type PreliminaryCanAuthor = ParachainStaking;
 	type FinalCanAuthor = AuthorFilter;
+	type AccountLookup = AuthorMapping;
 }
 
 impl pallet_author_filter::Config for Runtime {
@@ -500,6 +867,46 @@ impl pallet_author_filter::Config for Runtime {
 	type RandomnessSource = RandomnessCollectiveFlip;
 }
 
+parameter_types! {
+	pub const AuthorDepositAmount: Balance = 100 * GLMR;
+}
+
+impl pallet_author_mapping::Config for Runtime {
+	type Event = Event;
+	type DepositCurrency = Balances;
+	type DepositAmount = AuthorDepositAmount;
+}
+
+parameter_types! {
+	pub const EquivocationReportWindow: BlockNumber = 7 * BLOCKS_PER_DAY;
+	pub const MaxEquivocationProofSize: u32 = 4 * 1024;
+	/// Equivocation reports pay no fees, so they come after the transactions that do.
+	pub const EquivocationReportPriority: TransactionPriority = TransactionPriority::min_value();
+}
+
+impl pallet_author_equivocation::Config for Runtime {
+	type Event = Event;
+	type AccountLookup = AuthorMapping;
+	type OnEquivocation = ParachainStaking;
+	// Root or 2/3 of the council confirm that reports are actual equivocations.
+	type SlashOrigin = EnsureOneOf<
+		AccountId,
+		EnsureRoot<AccountId>,
+		pallet_collective::EnsureProportionAtLeast<_2, _3, AccountId, CouncilInstance>,
+	>;
+	type ReportWindow = EquivocationReportWindow;
+	type MaxProofSize = MaxEquivocationProofSize;
+	type UnsignedPriority = EquivocationReportPriority;
+}
+
+impl<C> frame_system::offchain::SendTransactionTypes<C> for Runtime
+where
+	Call: From<C>,
+{
+	type OverarchingCall = Call;
+	type Extrinsic = UncheckedExtrinsic;
+}
+
 construct_runtime! {
 	pub enum Runtime where
 		Block = Block,
@@ -528,7 +935,17 @@ construct_runtime! {
 		// The order matters here. Inherents will be included in the order specified here.
 		// Concretely we need the author inherent to come after the parachain_upgrade inherent.
 		AuthorInherent: author_inherent::{Pallet, Call, Storage, Inherent},
-		AuthorFilter: pallet_author_filter::{Pallet, Call, Storage, Event<T>,}
+		AuthorFilter: pallet_author_filter::{Pallet, Call, Storage, Event<T>,},
+		AuthorMapping: pallet_author_mapping::{Pallet, Call, Storage, Config<T>, Event<T>},
+		AuthorEquivocation:
+			pallet_author_equivocation::{Pallet, Call, Storage, Event<T>, ValidateUnsigned},
+		TokenDealer: cumulus_token_dealer::{Pallet, Call, Storage, Event<T>},
+		RelayRandomness: pallet_relay_randomness::{Pallet, Call, Storage, Inherent, Event<T>},
+		Multisig: pallet_multisig::{Pallet, Call, Storage, Event<T>},
+		Proxy: pallet_proxy::{Pallet, Call, Storage, Event<T>},
+		FeeMarket: pallet_fee_market::{Pallet, Call, Storage, Config, Event<T>},
+		Treasury: pallet_treasury::{Pallet, Call, Storage, Config, Event<T>},
+		RelayToken: pallet_balances::<Instance1>::{Pallet, Call, Storage, Event<T>},
 	}
 }
 
@@ -620,6 +1037,12 @@ impl_runtime_apis! {
 			source: TransactionSource,
 			tx: <Block as BlockT>::Extrinsic,
 		) -> TransactionValidity {
+			// Ethereum transactions below the minimum gas price of the fee market would fail.
+			if let Call::Ethereum(transact(transaction)) = &tx.function {
+				if transaction.gas_price < FeeMarket::min_gas_price() {
+					return InvalidTransaction::Payment.into();
+				}
+			}
 			Executive::validate_transaction(source, tx)
 		}
 	}
@@ -915,6 +1338,30 @@ impl_runtime_apis! {
 		}
 	}
 
+	impl moonbeam_primitives_author_equivocation::AuthorEquivocationApi<Block> for Runtime {
+		fn submit_report_equivocation_unsigned_extrinsic(
+			equivocation_proof: author_inherent::EquivocationProof<<Block as BlockT>::Header>,
+		) -> Option<()> {
+			AuthorEquivocation::submit_unsigned_equivocation_report(equivocation_proof)
+		}
+	}
+
+	impl moonbeam_primitives_eip712::Eip712Api<Block> for Runtime {
+		fn typed_data(payload: Vec<u8>) -> Option<Vec<u8>> {
+			// Long payloads are signed through their hash, as `SignedPayload` does.
+			let hash;
+			let payload = if payload.len() > 256 {
+				hash = sp_io::hashing::blake2_256(&payload);
+				&hash[..]
+			} else {
+				&payload[..]
+			};
+			account::Eip712Message::of::<SigningDomain>(payload).map(|message| {
+				<SigningDomain as account::Eip712Chain>::domain().typed_data(&message)
+			})
+		}
+	}
+
 	impl fp_rpc::EthereumRuntimeRPCApi<Block> for Runtime {
 		fn chain_id() -> u64 {
 			<Runtime as pallet_evm::Config>::ChainId::get()
@@ -1049,4 +1496,8 @@ impl_runtime_apis! {
 	}
 }
 
-cumulus_pallet_parachain_system::register_validate_block!(Runtime, Executive);
+// The author's seal is checked and stripped before the block reaches the executive.
+cumulus_pallet_parachain_system::register_validate_block!(
+	Runtime,
+	author_inherent::BlockExecutor<Executive>
+);
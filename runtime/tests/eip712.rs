@@ -0,0 +1,56 @@
+// Copyright 2019-2021 PureStake Inc.
+// This file is part of Moonbeam.
+
+// Moonbeam is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Moonbeam is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Moonbeam.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Tests of how signing payloads are split into EIP-712 typed data.
+
+use account::{CallInfo, Eip712Call, Eip712Chain, Eip712Message};
+use moonbeam_runtime::{Call, Runtime, SigningDomain};
+use parity_scale_codec::Encode;
+
+#[test]
+fn signing_payloads_are_split_after_their_call() {
+	let call = Call::System(frame_system::Call::<Runtime>::remark(vec![0x2a]));
+	let mut payload = call.encode();
+	payload.extend_from_slice(&[0xaa; 80]);
+
+	assert_eq!(
+		SigningDomain::call_info(&payload),
+		Some(CallInfo {
+			pallet: "System",
+			function: "remark",
+			len: call.encode().len(),
+		})
+	);
+	assert_eq!(
+		Eip712Message::of::<SigningDomain>(&payload),
+		Some(Eip712Message::Transaction {
+			call: Eip712Call {
+				pallet: "System",
+				function: "remark",
+				pallet_index: payload[0],
+				function_index: payload[1],
+				arguments: &[4, 0x2a],
+			},
+			extensions: &[0xaa; 80],
+		})
+	);
+}
+
+#[test]
+fn signing_payloads_must_start_with_a_call() {
+	assert_eq!(SigningDomain::call_info(&[0xff; 80]), None);
+	assert_eq!(Eip712Message::of::<SigningDomain>(&[0xff; 80]), None);
+}
@@ -0,0 +1,413 @@
+// Copyright 2019-2021 PureStake Inc.
+// This file is part of Moonbeam.
+
+// Moonbeam is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Moonbeam is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Moonbeam.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Tests of the precompiles as they are installed in the Moonbeam runtime.
+
+use evm::{Context, ExitError};
+use frame_support::traits::Currency;
+use moonbeam_runtime::{AccountId, Call, Runtime, WEIGHT_PER_GAS};
+use pallet_evm::PrecompileSet;
+use parity_scale_codec::Encode;
+use sp_core::{ecdsa, ed25519, sr25519, Pair, H160, H256, U256};
+
+type Precompiles = <Runtime as pallet_evm::Config>::Precompiles;
+
+fn multisig_precompile() -> H160 {
+	H160::from_low_u64_be(2057)
+}
+
+fn context(address: H160) -> Context {
+	Context {
+		address,
+		caller: H160::repeat_byte(0x11),
+		apparent_value: U256::zero(),
+	}
+}
+
+fn word(value: u64) -> [u8; 32] {
+	let mut word = [0u8; 32];
+	U256::from(value).to_big_endian(&mut word);
+	word
+}
+
+/// ABI encoded input of `multisig_address(address[],uint16)`.
+fn multisig_address_input(signatories: &[H160], threshold: u16) -> Vec<u8> {
+	let mut input = hex_literal::hex!("7bee4775").to_vec();
+	// Offset of the signatories, after the two head words
+	input.extend_from_slice(&word(64));
+	input.extend_from_slice(&word(threshold.into()));
+	input.extend_from_slice(&word(signatories.len() as u64));
+	for signatory in signatories {
+		input.extend_from_slice(&[0u8; 12]);
+		input.extend_from_slice(signatory.as_bytes());
+	}
+	input
+}
+
+#[test]
+fn multisig_address_matches_the_multisig_pallet() {
+	sp_io::TestExternalities::default().execute_with(|| {
+		let signatories = vec![
+			H160::repeat_byte(0x33),
+			H160::repeat_byte(0x22),
+			H160::repeat_byte(0x44),
+		];
+
+		let (_, output, _) = Precompiles::execute(
+			multisig_precompile(),
+			&multisig_address_input(&signatories, 2),
+			None,
+			&context(multisig_precompile()),
+		)
+		.expect("the multisig precompile is installed")
+		.expect("multisig_address succeeds");
+
+		let mut sorted: Vec<AccountId> = signatories;
+		sorted.sort();
+		let expected = pallet_multisig::Pallet::<Runtime>::multi_account_id(&sorted, 2);
+		assert_eq!(&output[..12], &[0u8; 12]);
+		assert_eq!(&output[12..], expected.as_bytes());
+	});
+}
+
+#[test]
+fn multisig_precompile_cannot_be_called_with_delegatecall() {
+	sp_io::TestExternalities::default().execute_with(|| {
+		let calling_contract = H160::repeat_byte(0x55);
+
+		let result = Precompiles::execute(
+			multisig_precompile(),
+			&multisig_address_input(&[H160::repeat_byte(0x22)], 1),
+			None,
+			&context(calling_contract),
+		);
+
+		assert!(matches!(result, Some(Err(ExitError::Other(_)))));
+	});
+}
+
+/// ABI encoded input of `as_multi(uint16,address[],uint32,uint32,bytes,uint64)` opening an
+/// operation, so without timepoint.
+fn as_multi_input(threshold: u16, other_signatories: &[H160], call: &[u8]) -> Vec<u8> {
+	let mut input = hex_literal::hex!("ff3a5c35").to_vec();
+	input.extend_from_slice(&word(threshold.into()));
+	// Offsets of the signatories and the call, after the six head words
+	input.extend_from_slice(&word(192));
+	input.extend_from_slice(&word(0));
+	input.extend_from_slice(&word(0));
+	input.extend_from_slice(&word(224 + 32 * other_signatories.len() as u64));
+	input.extend_from_slice(&word(1_000_000_000));
+	input.extend_from_slice(&word(other_signatories.len() as u64));
+	for signatory in other_signatories {
+		input.extend_from_slice(&[0u8; 12]);
+		input.extend_from_slice(signatory.as_bytes());
+	}
+	input.extend_from_slice(&abi_bytes(call));
+	input
+}
+
+#[test]
+fn multisig_precompile_rejects_calls_the_dispatch_filter_rejects() {
+	sp_io::TestExternalities::default().execute_with(|| {
+		let call = Call::System(frame_system::Call::set_code(Vec::new())).encode();
+
+		let result = Precompiles::execute(
+			multisig_precompile(),
+			&as_multi_input(2, &[H160::repeat_byte(0x22)], &call),
+			None,
+			&context(multisig_precompile()),
+		);
+
+		match result {
+			Some(Err(ExitError::Other(reason))) => {
+				assert_eq!(reason, "call not allowed by the multisig precompile")
+			}
+			_ => panic!("the call should be rejected"),
+		}
+	});
+}
+
+fn batch_precompile() -> H160 {
+	H160::from_low_u64_be(2056)
+}
+
+/// ABI encoded input of the batch function with `selector`, without gas limits.
+fn batch_input(selector: [u8; 4], to: &[H160], value: &[u64], call_data: &[Vec<u8>]) -> Vec<u8> {
+	let mut to_array = word(to.len() as u64).to_vec();
+	for address in to {
+		to_array.extend_from_slice(H256::from(*address).as_bytes());
+	}
+	let mut value_array = word(value.len() as u64).to_vec();
+	for value in value {
+		value_array.extend_from_slice(&word(*value));
+	}
+	// The offsets of the call data, after the offset words, then the call data
+	let mut call_data_array = word(call_data.len() as u64).to_vec();
+	let mut tails = Vec::new();
+	for data in call_data {
+		call_data_array.extend_from_slice(&word((32 * call_data.len() + tails.len()) as u64));
+		tails.extend_from_slice(&abi_bytes(data));
+	}
+	call_data_array.extend_from_slice(&tails);
+	let gas_limit_array = word(0).to_vec();
+
+	let arrays = [to_array, value_array, call_data_array, gas_limit_array];
+	let mut input = selector.to_vec();
+	// Offsets of the arrays, after the four head words
+	let mut offset = 128;
+	for array in &arrays {
+		input.extend_from_slice(&word(offset));
+		offset += array.len() as u64;
+	}
+	for array in &arrays {
+		input.extend_from_slice(array);
+	}
+	input
+}
+
+/// Execute the batch precompile with `input` on behalf of the caller of `context`.
+fn execute_batch(input: &[u8]) -> Option<Result<(), ExitError>> {
+	Precompiles::execute(
+		batch_precompile(),
+		input,
+		Some(1_000_000),
+		&context(batch_precompile()),
+	)
+	.map(|result| result.map(|_| ()))
+}
+
+#[test]
+fn batch_subcalls_do_not_use_nonces_of_the_caller() {
+	sp_io::TestExternalities::default().execute_with(|| {
+		let caller = context(batch_precompile()).caller;
+		pallet_balances::Pallet::<Runtime>::make_free_balance_be(&caller, 1_000);
+		let alice = H160::repeat_byte(0x22);
+		let bob = H160::repeat_byte(0x33);
+
+		let input = batch_input(
+			hex_literal::hex!("c8e8ae79"),
+			&[alice, bob],
+			&[100, 200],
+			&[],
+		);
+		assert!(matches!(execute_batch(&input), Some(Ok(()))));
+
+		assert_eq!(
+			pallet_balances::Pallet::<Runtime>::free_balance(&alice),
+			100
+		);
+		assert_eq!(pallet_balances::Pallet::<Runtime>::free_balance(&bob), 200);
+		// Each subcall ran as a transaction of its own, bumping the nonce, which is restored
+		assert_eq!(frame_system::Pallet::<Runtime>::account_nonce(&caller), 0);
+	});
+}
+
+#[test]
+fn batches_cannot_be_nested() {
+	sp_io::TestExternalities::default().execute_with(|| {
+		let caller = context(batch_precompile()).caller;
+		pallet_balances::Pallet::<Runtime>::make_free_balance_be(&caller, 1_000);
+		let alice = H160::repeat_byte(0x22);
+		let nested = batch_input(hex_literal::hex!("c8e8ae79"), &[alice], &[100], &[]);
+
+		// The nested batch fails, and batch_all fails with it
+		let input = batch_input(
+			hex_literal::hex!("d30c5f76"),
+			&[batch_precompile()],
+			&[],
+			&[nested.clone()],
+		);
+		assert!(matches!(
+			execute_batch(&input),
+			Some(Err(ExitError::Other(_)))
+		));
+
+		// batch_some carries on without the nested batch
+		let input = batch_input(
+			hex_literal::hex!("c8e8ae79"),
+			&[batch_precompile(), alice],
+			&[0, 1],
+			&[nested],
+		);
+		assert!(matches!(execute_batch(&input), Some(Ok(()))));
+		assert_eq!(pallet_balances::Pallet::<Runtime>::free_balance(&alice), 1);
+		assert_eq!(frame_system::Pallet::<Runtime>::account_nonce(&caller), 0);
+	});
+}
+
+fn ed25519_precompile() -> H160 {
+	H160::from_low_u64_be(2052)
+}
+
+fn sr25519_precompile() -> H160 {
+	H160::from_low_u64_be(2053)
+}
+
+fn ecdsa_recover_precompile() -> H160 {
+	H160::from_low_u64_be(2054)
+}
+
+/// The ABI encoding of the tail of a `bytes` argument: its length and its padded data.
+fn abi_bytes(data: &[u8]) -> Vec<u8> {
+	let mut encoded = word(data.len() as u64).to_vec();
+	encoded.extend_from_slice(data);
+	encoded.resize(32 + (data.len() + 31) / 32 * 32, 0);
+	encoded
+}
+
+/// ABI encoded input of `verify(bytes32,bytes,bytes)`.
+fn verify_input(public_key: [u8; 32], message: &[u8], signature: &[u8]) -> Vec<u8> {
+	let message = abi_bytes(message);
+	let mut input = hex_literal::hex!("5bf48e3a").to_vec();
+	input.extend_from_slice(&public_key);
+	// Offsets of the message and the signature, after the three head words
+	input.extend_from_slice(&word(96));
+	input.extend_from_slice(&word(96 + message.len() as u64));
+	input.extend_from_slice(&message);
+	input.extend_from_slice(&abi_bytes(signature));
+	input
+}
+
+/// ABI encoded input of `recover(bytes32,bytes)` or `recover_compressed(bytes32,bytes)`.
+fn recover_input(selector: [u8; 4], message_hash: [u8; 32], signature: &[u8]) -> Vec<u8> {
+	let mut input = selector.to_vec();
+	input.extend_from_slice(&message_hash);
+	// Offset of the signature, after the two head words
+	input.extend_from_slice(&word(64));
+	input.extend_from_slice(&abi_bytes(signature));
+	input
+}
+
+/// Execute the precompile at `address` with `input`, returning its output and the gas it used.
+fn execute(address: H160, input: &[u8]) -> (Vec<u8>, u64) {
+	let (_, output, used_gas) = Precompiles::execute(address, input, None, &context(address))
+		.expect("the precompile is installed")
+		.expect("the precompile succeeds");
+	(output, used_gas)
+}
+
+/// The gas charged for a cryptographic operation of `weight` on a message of `message_len` bytes.
+fn crypto_gas(weight: u64, message_len: u64) -> u64 {
+	(weight + precompiles::MESSAGE_BYTE_WEIGHT * message_len) / WEIGHT_PER_GAS
+}
+
+#[test]
+fn ed25519_verify_checks_the_rfc_8032_vector() {
+	sp_io::TestExternalities::default().execute_with(|| {
+		// Test 1 of RFC 8032, section 7.1
+		let public_key =
+			hex_literal::hex!("d75a980182b10ab7d54bfed3c964073a0ee172f3daa62325af021a68f707511a");
+		let signature = hex_literal::hex!(
+			"e5564300c360ac729086e2cc806e828a84877f1eb8e5d974d873e065224901555fb8821590a33bacc61e39701cf9b46bd25bf5f0595bbe24655141438e7a100b"
+		);
+		let (output, used_gas) = execute(
+			ed25519_precompile(),
+			&verify_input(public_key, b"", &signature),
+		);
+		assert_eq!(output, word(1).to_vec());
+		assert_eq!(used_gas, crypto_gas(precompiles::ED25519_VERIFY_WEIGHT, 0));
+
+		let (output, _) = execute(
+			ed25519_precompile(),
+			&verify_input(public_key, b"tampered", &signature),
+		);
+		assert_eq!(output, word(0).to_vec());
+	});
+}
+
+#[test]
+fn ed25519_verify_charges_for_the_message_length() {
+	sp_io::TestExternalities::default().execute_with(|| {
+		let pair = ed25519::Pair::from_seed(&[1u8; 32]);
+		let message = [0xabu8; 1_000];
+		let signature = pair.sign(&message);
+		let (output, used_gas) = execute(
+			ed25519_precompile(),
+			&verify_input(pair.public().0, &message, signature.as_ref()),
+		);
+		assert_eq!(output, word(1).to_vec());
+		assert_eq!(
+			used_gas,
+			crypto_gas(precompiles::ED25519_VERIFY_WEIGHT, 1_000)
+		);
+		assert!(used_gas > crypto_gas(precompiles::ED25519_VERIFY_WEIGHT, 0));
+	});
+}
+
+#[test]
+fn sr25519_verify_works() {
+	sp_io::TestExternalities::default().execute_with(|| {
+		let pair = sr25519::Pair::from_seed(&[2u8; 32]);
+		let signature = pair.sign(b"moonbeam");
+		let (output, used_gas) = execute(
+			sr25519_precompile(),
+			&verify_input(pair.public().0, b"moonbeam", signature.as_ref()),
+		);
+		assert_eq!(output, word(1).to_vec());
+		assert_eq!(used_gas, crypto_gas(precompiles::SR25519_VERIFY_WEIGHT, 8));
+
+		let other = sr25519::Pair::from_seed(&[3u8; 32]);
+		let (output, _) = execute(
+			sr25519_precompile(),
+			&verify_input(other.public().0, b"moonbeam", signature.as_ref()),
+		);
+		assert_eq!(output, word(0).to_vec());
+	});
+}
+
+#[test]
+fn ecdsa_recover_gives_the_public_key_of_the_signer() {
+	sp_io::TestExternalities::default().execute_with(|| {
+		// Gerald's key of the development chain
+		let pair = ecdsa::Pair::from_seed(&hex_literal::hex!(
+			"99b3c12287537e38c90a9219d4cb074a89a16e9cdb20bf85728ebd97c343e342"
+		));
+		// `sign` signs the blake2-256 hash of the message
+		let message_hash = sp_io::hashing::blake2_256(b"moonbeam");
+		let signature = pair.sign(b"moonbeam");
+
+		let (output, used_gas) = execute(
+			ecdsa_recover_precompile(),
+			&recover_input(
+				hex_literal::hex!("19045a25"),
+				message_hash,
+				signature.as_ref(),
+			),
+		);
+		assert_eq!(used_gas, crypto_gas(precompiles::ECDSA_RECOVER_WEIGHT, 0));
+		// The uncompressed key, whose Keccak-256 hash ends with Gerald's address
+		assert_eq!(output[32..64], word(64));
+		let address = H256::from(sp_io::hashing::keccak_256(&output[64..128]));
+		assert_eq!(
+			H160::from(address),
+			H160::from(hex_literal::hex!(
+				"6be02d1d3665660d22ff9624b7be0551ee1ac91b"
+			))
+		);
+
+		let (output, used_gas) = execute(
+			ecdsa_recover_precompile(),
+			&recover_input(
+				hex_literal::hex!("30e24164"),
+				message_hash,
+				signature.as_ref(),
+			),
+		);
+		assert_eq!(used_gas, crypto_gas(precompiles::ECDSA_RECOVER_WEIGHT, 0));
+		assert_eq!(output[32..64], word(33));
+		assert_eq!(&output[64..97], pair.public().as_ref());
+	});
+}
@@ -0,0 +1,46 @@
+// Copyright 2019-2021 PureStake Inc.
+// This file is part of Moonbeam.
+
+// Moonbeam is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Moonbeam is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Moonbeam.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Tests of the calls each proxy type allows.
+
+use frame_support::traits::InstanceFilter;
+use moonbeam_runtime::{Call, ProxyType, RelayTokenInstance, Runtime};
+use sp_core::H160;
+
+#[test]
+fn non_transfer_proxies_cannot_move_the_native_token() {
+	let call = Call::Balances(pallet_balances::Call::<Runtime>::transfer(
+		H160::repeat_byte(0x22),
+		1,
+	));
+	assert!(!ProxyType::NonTransfer.filter(&call));
+	assert!(ProxyType::Any.filter(&call));
+}
+
+#[test]
+fn non_transfer_proxies_cannot_move_the_relay_token() {
+	let call = Call::RelayToken(
+		pallet_balances::Call::<Runtime, RelayTokenInstance>::transfer(H160::repeat_byte(0x22), 1),
+	);
+	assert!(!ProxyType::NonTransfer.filter(&call));
+	assert!(ProxyType::Any.filter(&call));
+}
+
+#[test]
+fn non_transfer_proxies_can_make_other_calls() {
+	let call = Call::System(frame_system::Call::<Runtime>::remark(Vec::new()));
+	assert!(ProxyType::NonTransfer.filter(&call));
+}
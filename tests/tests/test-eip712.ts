@@ -0,0 +1,66 @@
+import { expect } from "chai";
+import { Wallet } from "ethers";
+
+import { GENESIS_ACCOUNT, GENESIS_ACCOUNT_PRIVATE_KEY } from "./constants";
+import {
+  createAndFinalizeBlock,
+  describeWithMoonbeam,
+  eip712Domain,
+  eip712Signer,
+  eip712TypedData,
+} from "./util";
+
+describeWithMoonbeam("Moonbeam (EIP-712 signed extrinsics)", `simple-specs.json`, (context) => {
+  it("accepts extrinsics signed as EIP-712 typed data", async function () {
+    this.timeout(15000);
+    const wallet = new Wallet(GENESIS_ACCOUNT_PRIVATE_KEY);
+    const signer = eip712Signer(context.polkadotApi, wallet);
+
+    const hash = await context.polkadotApi.tx.system
+      .remark("0x01")
+      .signAndSend(GENESIS_ACCOUNT, { signer });
+    await createAndFinalizeBlock(context.polkadotApi);
+
+    const block = await context.polkadotApi.rpc.chain.getBlock();
+    const included = block.block.extrinsics.some((extrinsic) => extrinsic.hash.eq(hash));
+    expect(included).to.be.true;
+  });
+
+  it("shows the call of extrinsics to wallets", async function () {
+    const wallet = new Wallet(GENESIS_ACCOUNT_PRIVATE_KEY);
+    const signer = eip712Signer(context.polkadotApi, wallet);
+    let typedData;
+    const recordingSigner = {
+      signPayload: async (payload) => {
+        typedData = await eip712TypedData(context.polkadotApi, payload);
+        return signer.signPayload(payload);
+      },
+    };
+
+    await context.polkadotApi.tx.system
+      .remark("0x03")
+      .signAndSend(GENESIS_ACCOUNT, { signer: recordingSigner });
+    await createAndFinalizeBlock(context.polkadotApi);
+
+    expect(typedData.primaryType).to.equal("Transaction");
+    expect(typedData.domain).to.deep.equal(await eip712Domain(context.polkadotApi));
+    expect(typedData.message.call.pallet).to.equal("System");
+    expect(typedData.message.call.function).to.equal("remark");
+    // The compact encoded length of the remark, then the remark
+    expect(typedData.message.call.arguments).to.equal("0x0403");
+  });
+
+  it("rejects EIP-712 signatures of another chain", async function () {
+    const wallet = new Wallet(GENESIS_ACCOUNT_PRIVATE_KEY);
+    const domain = { ...(await eip712Domain(context.polkadotApi)), chainId: 1 };
+    const signer = eip712Signer(context.polkadotApi, wallet, domain);
+
+    let error;
+    try {
+      await context.polkadotApi.tx.system.remark("0x02").signAndSend(GENESIS_ACCOUNT, { signer });
+    } catch (e) {
+      error = e;
+    }
+    expect(error).to.not.be.undefined;
+  });
+});
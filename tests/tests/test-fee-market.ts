@@ -0,0 +1,67 @@
+import { expect } from "chai";
+
+import { GENESIS_ACCOUNT, GENESIS_ACCOUNT_PRIVATE_KEY } from "./constants";
+import { createAndFinalizeBlock, customRequest, describeWithMoonbeam } from "./util";
+
+const TEST_ACCOUNT = "0x1111111111111111111111111111111111111114";
+// The account of the "py/trsry" module id
+const TREASURY_ACCOUNT = "0x6d6f646c70792f74727372790000000000000000";
+
+describeWithMoonbeam("Moonbeam (Fee market)", `simple-specs.json`, (context) => {
+  it("starts the minimum gas price at the genesis floor", async function () {
+    expect(await context.web3.eth.getGasPrice()).to.equal("1");
+    expect((await context.polkadotApi.query.feeMarket.gasPriceFloor()).toString()).to.equal("1");
+  });
+
+  it("rejects transactions below the minimum gas price", async function () {
+    const tx = await context.web3.eth.accounts.signTransaction(
+      {
+        from: GENESIS_ACCOUNT,
+        to: TEST_ACCOUNT,
+        value: "0x200",
+        gasPrice: "0x00",
+        gas: "0x100000",
+      },
+      GENESIS_ACCOUNT_PRIVATE_KEY
+    );
+    const result = await customRequest(context.web3, "eth_sendRawTransaction", [
+      tx.rawTransaction,
+    ]);
+    expect(result.error).to.not.be.undefined;
+
+    await createAndFinalizeBlock(context.polkadotApi);
+    expect(await context.web3.eth.getBalance(TEST_ACCOUNT)).to.equal("0");
+  });
+
+  it("keeps the minimum gas price at the floor while blocks are empty", async function () {
+    this.timeout(15000);
+    await createAndFinalizeBlock(context.polkadotApi);
+    await createAndFinalizeBlock(context.polkadotApi);
+    expect(await context.web3.eth.getGasPrice()).to.equal("1");
+  });
+
+  it("pays the fee of the used gas to the treasury without block author", async function () {
+    this.timeout(15000);
+    const treasuryBefore = BigInt(await context.web3.eth.getBalance(TREASURY_ACCOUNT));
+    const senderBefore = BigInt(await context.web3.eth.getBalance(GENESIS_ACCOUNT));
+    const tx = await context.web3.eth.accounts.signTransaction(
+      {
+        from: GENESIS_ACCOUNT,
+        to: TEST_ACCOUNT,
+        value: "0x200",
+        gasPrice: "0x01",
+        gas: "0x100000",
+      },
+      GENESIS_ACCOUNT_PRIVATE_KEY
+    );
+    await customRequest(context.web3, "eth_sendRawTransaction", [tx.rawTransaction]);
+    await createAndFinalizeBlock(context.polkadotApi);
+
+    // The manual seal blocks of the tests have no author, so the whole fee goes to the treasury
+    // and the unused gas is refunded.
+    const treasuryAfter = BigInt(await context.web3.eth.getBalance(TREASURY_ACCOUNT));
+    const senderAfter = BigInt(await context.web3.eth.getBalance(GENESIS_ACCOUNT));
+    expect(treasuryAfter - treasuryBefore).to.equal(21000n);
+    expect(senderBefore - senderAfter).to.equal(21000n + 512n);
+  });
+});
@@ -59,7 +59,7 @@ describeWithMoonbeam("Moonbeam Polkadot API", `simple-specs.json`, (context) =>
           break;
         case 2:
           expect(message).to.eq(
-            `authorInherent.setAuthor(0x6Be02d1d3665660d22FF9624b7BE0551ee1Ac91b)`
+            `authorInherent.setAuthor(0x03624f720eae676a04111631c9ca338c11d0f5a80ee42210c6be72983ceb620fbf)`
           );
           break;
         case 3:
@@ -0,0 +1,68 @@
+import { expect } from "chai";
+
+import { GENESIS_ACCOUNT, GENESIS_ACCOUNT_PRIVATE_KEY } from "./constants";
+import { createAndFinalizeBlock, customRequest, describeWithMoonbeam } from "./util";
+
+const BATCH_PRECOMPILE = "0x0000000000000000000000000000000000000808";
+
+// Selector of batch_some(address[],uint256[],bytes[],uint64[])
+const BATCH_SOME = "0xc8e8ae79";
+// Selector of batch_all(address[],uint256[],bytes[],uint64[])
+const BATCH_ALL = "0xd30c5f76";
+
+const ALICE = "0x2222222222222222222222222222222222222222";
+const BOB = "0x3333333333333333333333333333333333333333";
+
+describeWithMoonbeam("Moonbeam (Batch precompile)", `simple-specs.json`, (context) => {
+  function batchData(selector: string, to: string[], value: string[], callData: string[] = []) {
+    const args = context.web3.eth.abi.encodeParameters(
+      ["address[]", "uint256[]", "bytes[]", "uint64[]"],
+      [to, value, callData, []]
+    );
+    return `${selector}${args.slice(2)}`;
+  }
+
+  async function batch(selector: string, to: string[], value: string[], callData: string[] = []) {
+    const tx = await context.web3.eth.accounts.signTransaction(
+      {
+        from: GENESIS_ACCOUNT,
+        to: BATCH_PRECOMPILE,
+        data: batchData(selector, to, value, callData),
+        gasPrice: "0x01",
+        gas: "0x100000",
+      },
+      GENESIS_ACCOUNT_PRIVATE_KEY
+    );
+    await customRequest(context.web3, "eth_sendRawTransaction", [tx.rawTransaction]);
+    await createAndFinalizeBlock(context.polkadotApi);
+  }
+
+  it("makes the subcalls on behalf of the caller", async function () {
+    this.timeout(15000);
+    const nonce = await context.web3.eth.getTransactionCount(GENESIS_ACCOUNT);
+    await batch(BATCH_SOME, [ALICE, BOB], ["0x100", "0x200"]);
+
+    expect(await context.web3.eth.getBalance(ALICE)).to.equal("256");
+    expect(await context.web3.eth.getBalance(BOB)).to.equal("512");
+    // The subcalls do not use nonces of the caller
+    expect(await context.web3.eth.getTransactionCount(GENESIS_ACCOUNT)).to.equal(nonce + 1);
+  });
+
+  it("reverts all the subcalls of batch_all when one fails", async function () {
+    this.timeout(15000);
+    // The caller cannot afford the second transfer
+    await batch(BATCH_ALL, [ALICE, BOB], ["0x100", "0x" + (2n ** 100n).toString(16)]);
+
+    expect(await context.web3.eth.getBalance(ALICE)).to.equal("256");
+    expect(await context.web3.eth.getBalance(BOB)).to.equal("512");
+  });
+
+  it("rejects nested batches", async function () {
+    this.timeout(15000);
+    // The nested batch would pay Alice again, but fails, and batch_all reverts with it
+    const nested = batchData(BATCH_SOME, [ALICE], ["0x100"]);
+    await batch(BATCH_ALL, [BATCH_PRECOMPILE], ["0x0"], [nested]);
+
+    expect(await context.web3.eth.getBalance(ALICE)).to.equal("256");
+  });
+});
@@ -0,0 +1,53 @@
+import { expect } from "chai";
+import { Keyring } from "@polkadot/keyring";
+import { u8aToHex } from "@polkadot/util";
+
+import { GENESIS_ACCOUNT } from "./constants";
+import { customRequest, describeWithMoonbeam } from "./util";
+
+const ED25519_PRECOMPILE = "0x0000000000000000000000000000000000000804";
+const SR25519_PRECOMPILE = "0x0000000000000000000000000000000000000805";
+
+// Selector of verify(bytes32,bytes,bytes)
+const VERIFY = "0x5bf48e3a";
+
+describeWithMoonbeam("Moonbeam (Crypto precompiles)", `simple-specs.json`, (context) => {
+  async function verify(
+    precompile: string,
+    publicKey: string,
+    message: string,
+    signature: string
+  ) {
+    const args = context.web3.eth.abi.encodeParameters(
+      ["bytes32", "bytes", "bytes"],
+      [publicKey, message, signature]
+    );
+    const tx_call = await customRequest(context.web3, "eth_call", [
+      {
+        from: GENESIS_ACCOUNT,
+        value: "0x0",
+        gas: "0x10000",
+        gasPrice: "0x01",
+        to: precompile,
+        data: `${VERIFY}${args.slice(2)}`,
+      },
+    ]);
+    return context.web3.eth.abi.decodeParameter("bool", tx_call.result);
+  }
+
+  for (const [type, precompile] of [
+    ["ed25519", ED25519_PRECOMPILE],
+    ["sr25519", SR25519_PRECOMPILE],
+  ]) {
+    it(`verifies ${type} signatures`, async function () {
+      const pair = new Keyring({ type: type as "ed25519" | "sr25519" }).addFromUri("//Alice");
+      const publicKey = u8aToHex(pair.publicKey);
+      const message = u8aToHex(Buffer.from("moonbeam"));
+      const other = u8aToHex(Buffer.from("other"));
+      const signature = u8aToHex(pair.sign(message));
+
+      expect(await verify(precompile, publicKey, message, signature)).to.be.true;
+      expect(await verify(precompile, publicKey, other, signature)).to.be.false;
+    });
+  }
+});
@@ -0,0 +1,58 @@
+import { expect } from "chai";
+
+import { GENESIS_ACCOUNT, GENESIS_ACCOUNT_PRIVATE_KEY } from "./constants";
+import {
+  callContractFunctionMS,
+  customRequest,
+  deployDelegateCaller,
+  describeWithMoonbeam,
+} from "./util";
+
+const DISPATCH_PRECOMPILE = "0x00000000000000000000000000000000000000ff";
+
+const ALICE = "0x2222222222222222222222222222222222222222";
+
+describeWithMoonbeam("Moonbeam (Dispatch precompile)", `simple-specs.json`, (context) => {
+  async function call(data: string, to: string = DISPATCH_PRECOMPILE) {
+    return customRequest(context.web3, "eth_call", [
+      {
+        from: GENESIS_ACCOUNT,
+        value: "0x0",
+        gas: "0x100000",
+        gasPrice: "0x01",
+        to,
+        data,
+      },
+    ]);
+  }
+
+  it("dispatches allowed calls", async function () {
+    const remark = context.polkadotApi.tx.system.remark("0x01").method.toHex();
+    const result = await call(remark);
+    expect(result.error).to.be.undefined;
+    expect(result.result).equals("0x");
+  });
+
+  it("rejects calls outside of the allowlist", async function () {
+    const remark = context.polkadotApi.tx.system.remark("0x01");
+    const sudo = context.polkadotApi.tx.sudo.sudo(remark).method.toHex();
+    const result = await call(sudo);
+    expect(result.error).to.not.be.undefined;
+  });
+
+  it("cannot be called with DELEGATECALL", async function () {
+    this.timeout(15000);
+    const forwarder = await deployDelegateCaller(context, DISPATCH_PRECOMPILE);
+
+    // Through DELEGATECALL, the transfer would be dispatched from the caller of the forwarder
+    const transfer = context.polkadotApi.tx.balances.transfer(ALICE, 0x100).method.toHex();
+    const result = await call(transfer, forwarder.options.address);
+    expect(result.result).equals(`0x${"0".repeat(64)}`);
+    await callContractFunctionMS(context, forwarder.options.address, transfer, {
+      account: GENESIS_ACCOUNT,
+      privateKey: GENESIS_ACCOUNT_PRIVATE_KEY,
+    });
+
+    expect(await context.web3.eth.getBalance(ALICE)).to.equal("0");
+  });
+});
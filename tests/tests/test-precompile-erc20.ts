@@ -0,0 +1,153 @@
+import { expect } from "chai";
+
+import {
+  GENESIS_ACCOUNT,
+  GENESIS_ACCOUNT_BALANCE,
+  GENESIS_ACCOUNT_PRIVATE_KEY,
+  TEST_ACCOUNT,
+} from "./constants";
+import {
+  createAndFinalizeBlock,
+  customRequest,
+  deployDelegateCaller,
+  describeWithMoonbeam,
+} from "./util";
+
+const ERC20_PRECOMPILE = "0x0000000000000000000000000000000000000801";
+
+// Selectors of the functions of ERC20Interface.sol
+const SYMBOL = "95d89b41";
+const DECIMALS = "313ce567";
+const BALANCE_OF = "70a08231";
+const ALLOWANCE = "dd62ed3e";
+const TRANSFER = "a9059cbb";
+const APPROVE = "095ea7b3";
+const TRANSFER_FROM = "23b872dd";
+
+const ALICE = "0x2222222222222222222222222222222222222222";
+
+function word(value: bigint | string) {
+  const hex = typeof value === "string" ? value.slice(2) : value.toString(16);
+  return hex.padStart(64, "0");
+}
+
+describeWithMoonbeam("Moonbeam (ERC-20 precompile)", `simple-specs.json`, (context) => {
+  async function call(data: string, to: string = ERC20_PRECOMPILE) {
+    const tx_call = await customRequest(context.web3, "eth_call", [
+      {
+        from: GENESIS_ACCOUNT,
+        value: "0x0",
+        gas: "0x10000",
+        gasPrice: "0x01",
+        to,
+        data: `0x${data}`,
+      },
+    ]);
+    return tx_call.result;
+  }
+
+  async function send(
+    data: string,
+    from: string = GENESIS_ACCOUNT,
+    privateKey: string = GENESIS_ACCOUNT_PRIVATE_KEY,
+    to: string = ERC20_PRECOMPILE
+  ) {
+    const tx = await context.web3.eth.accounts.signTransaction(
+      {
+        from,
+        to,
+        data: `0x${data}`,
+        gasPrice: "0x01",
+        gas: "0x100000",
+      },
+      privateKey
+    );
+    await customRequest(context.web3, "eth_sendRawTransaction", [tx.rawTransaction]);
+    await createAndFinalizeBlock(context.polkadotApi);
+    return context.web3.eth.getTransactionReceipt(tx.transactionHash);
+  }
+
+  it("exposes the GLMR metadata", async function () {
+    expect(await call(DECIMALS)).equals(`0x${word(18n)}`);
+    expect(await call(SYMBOL)).equals(
+      `0x${word(32n)}${word(4n)}${Buffer.from("GLMR").toString("hex").padEnd(64, "0")}`
+    );
+  });
+
+  it("balanceOf returns the free balance", async function () {
+    expect(await call(`${BALANCE_OF}${word(GENESIS_ACCOUNT)}`)).equals(
+      `0x${word(GENESIS_ACCOUNT_BALANCE)}`
+    );
+    expect(await call(`${BALANCE_OF}${word(TEST_ACCOUNT)}`)).equals(`0x${word(0n)}`);
+  });
+
+  it("allowances are empty by default", async function () {
+    expect(await call(`${ALLOWANCE}${word(GENESIS_ACCOUNT)}${word(TEST_ACCOUNT)}`)).equals(
+      `0x${word(0n)}`
+    );
+  });
+
+  it("transfer moves the balance of the caller", async function () {
+    this.timeout(15000);
+    const receipt = await send(`${TRANSFER}${word(TEST_ACCOUNT)}${word(0x200n)}`);
+
+    expect(receipt.status).to.be.true;
+    expect(await context.web3.eth.getBalance(TEST_ACCOUNT)).to.equal("512");
+  });
+
+  it("approve sets the allowance of the spender", async function () {
+    this.timeout(15000);
+    const receipt = await send(`${APPROVE}${word(ALICE)}${word(0x300n)}`);
+
+    expect(receipt.status).to.be.true;
+    expect(await call(`${ALLOWANCE}${word(GENESIS_ACCOUNT)}${word(ALICE)}`)).equals(
+      `0x${word(0x300n)}`
+    );
+  });
+
+  it("transferFrom spends the allowance", async function () {
+    this.timeout(30000);
+    const spender = context.web3.eth.accounts.create();
+    await send(`${APPROVE}${word(spender.address)}${word(0x300n)}`);
+    // Fund the spender for the fees of its transactions
+    await send(`${TRANSFER}${word(spender.address)}${word(0x1000000n)}`);
+
+    const receipt = await send(
+      `${TRANSFER_FROM}${word(GENESIS_ACCOUNT)}${word(ALICE)}${word(0x100n)}`,
+      spender.address,
+      spender.privateKey
+    );
+
+    expect(receipt.status).to.be.true;
+    expect(await context.web3.eth.getBalance(ALICE)).to.equal("256");
+    expect(await call(`${ALLOWANCE}${word(GENESIS_ACCOUNT)}${word(spender.address)}`)).equals(
+      `0x${word(0x200n)}`
+    );
+
+    // The remaining allowance does not cover a second transfer of 0x300
+    const failed = await send(
+      `${TRANSFER_FROM}${word(GENESIS_ACCOUNT)}${word(ALICE)}${word(0x300n)}`,
+      spender.address,
+      spender.privateKey
+    );
+
+    expect(failed.status).to.be.false;
+    expect(await context.web3.eth.getBalance(ALICE)).to.equal("256");
+    expect(await call(`${ALLOWANCE}${word(GENESIS_ACCOUNT)}${word(spender.address)}`)).equals(
+      `0x${word(0x200n)}`
+    );
+  });
+
+  it("cannot be called with DELEGATECALL", async function () {
+    this.timeout(15000);
+    const forwarder = await deployDelegateCaller(context, ERC20_PRECOMPILE);
+    const before = await context.web3.eth.getBalance(TEST_ACCOUNT);
+
+    // Through DELEGATECALL, the precompile would see the caller of the forwarder as its caller
+    const data = `${TRANSFER}${word(TEST_ACCOUNT)}${word(0x100n)}`;
+    expect(await call(data, forwarder.options.address)).equals(`0x${word(0n)}`);
+    await send(data, GENESIS_ACCOUNT, GENESIS_ACCOUNT_PRIVATE_KEY, forwarder.options.address);
+
+    expect(await context.web3.eth.getBalance(TEST_ACCOUNT)).to.equal(before);
+  });
+});
@@ -0,0 +1,210 @@
+import { expect } from "chai";
+import { step } from "mocha-steps";
+import { Keyring } from "@polkadot/keyring";
+
+import { GENESIS_ACCOUNT, GENESIS_ACCOUNT_PRIVATE_KEY, GLMR } from "./constants";
+import {
+  createAndFinalizeBlock,
+  customRequest,
+  deployDelegateCaller,
+  describeWithMoonbeam,
+} from "./util";
+
+const DEMOCRACY_PRECOMPILE = "0x0000000000000000000000000000000000000802";
+const COUNCIL_PRECOMPILE = "0x0000000000000000000000000000000000000803";
+
+// Selectors of view functions of DemocracyInterface.sol and CollectiveInterface.sol
+const PUBLIC_PROP_COUNT = "56fdf547";
+const LOWEST_UNBAKED = "0388f282";
+const IS_MEMBER = "b0c90f90";
+const PROPOSAL_COUNT = "9c5f8184";
+// Selectors of calls of DemocracyInterface.sol and CollectiveInterface.sol
+const STANDARD_VOTE = "3f3c21cc";
+const SPLIT_VOTE = "bd584d94";
+const DELEGATE = "0185921e";
+const COUNCIL_PROPOSE = "5f90ebaf";
+
+const ZERO = "0x0000000000000000000000000000000000000000000000000000000000000000";
+
+function word(value: bigint | string) {
+  const hex = typeof value === "string" ? value.slice(2) : value.toString(16);
+  return hex.padStart(64, "0");
+}
+
+describeWithMoonbeam("Moonbeam (Governance precompiles)", `simple-specs.json`, (context) => {
+  const keyring = new Keyring({ type: "ethereum" });
+  const genesis = keyring.addFromUri(GENESIS_ACCOUNT_PRIVATE_KEY, null, "ethereum");
+
+  async function call(to: string, data: string) {
+    const tx_call = await customRequest(context.web3, "eth_call", [
+      {
+        from: GENESIS_ACCOUNT,
+        value: "0x0",
+        gas: "0x10000",
+        gasPrice: "0x01",
+        to,
+        data: `0x${data}`,
+      },
+    ]);
+    return tx_call.result;
+  }
+
+  async function send(to: string, data: string, from: string, privateKey: string) {
+    const tx = await context.web3.eth.accounts.signTransaction(
+      {
+        from,
+        to,
+        data,
+        gasPrice: "0x01",
+        gas: "0x100000",
+      },
+      privateKey
+    );
+    await customRequest(context.web3, "eth_sendRawTransaction", [tx.rawTransaction]);
+    await createAndFinalizeBlock(context.polkadotApi);
+    return context.web3.eth.getTransactionReceipt(tx.transactionHash);
+  }
+
+  // Create an account holding 100 GLMR
+  async function fundedAccount() {
+    const account = context.web3.eth.accounts.create();
+    await context.polkadotApi.tx.balances
+      .transfer(account.address, 100n * GLMR)
+      .signAndSend(genesis);
+    await createAndFinalizeBlock(context.polkadotApi);
+    return account;
+  }
+
+  async function votingOf(account: string) {
+    return (await context.polkadotApi.query.democracy.votingOf(account)) as any;
+  }
+
+  it("democracy starts without proposals nor referenda", async function () {
+    expect(await call(DEMOCRACY_PRECOMPILE, PUBLIC_PROP_COUNT)).equals(ZERO);
+    expect(await call(DEMOCRACY_PRECOMPILE, LOWEST_UNBAKED)).equals(ZERO);
+  });
+
+  it("council starts without proposals", async function () {
+    expect(await call(COUNCIL_PRECOMPILE, PROPOSAL_COUNT)).equals(ZERO);
+    expect(
+      await call(COUNCIL_PRECOMPILE, `${IS_MEMBER}${GENESIS_ACCOUNT.slice(2).padStart(64, "0")}`)
+    ).equals(ZERO);
+  });
+
+  step("the council starts an external referendum through the precompile", async function () {
+    this.timeout(30000);
+    // The genesis account becomes the only member of both collectives
+    await context.polkadotApi.tx.sudo
+      .sudo(context.polkadotApi.tx.councilCollective.setMembers([GENESIS_ACCOUNT], null, 0))
+      .signAndSend(genesis);
+    await createAndFinalizeBlock(context.polkadotApi);
+    await context.polkadotApi.tx.sudo
+      .sudo(context.polkadotApi.tx.techComitteeCollective.setMembers([GENESIS_ACCOUNT], null, 0))
+      .signAndSend(genesis);
+    await createAndFinalizeBlock(context.polkadotApi);
+
+    // With a threshold of 1, the council proposal is executed right away
+    const hash = context.polkadotApi.tx.system.remark("0x01").method.hash.toHex();
+    const external = context.polkadotApi.tx.democracy.externalProposeMajority(hash).method.toHex();
+    const args = context.web3.eth.abi.encodeParameters(["uint256", "bytes"], [1, external]);
+    const receipt = await send(
+      COUNCIL_PRECOMPILE,
+      `0x${COUNCIL_PROPOSE}${args.slice(2)}`,
+      GENESIS_ACCOUNT,
+      GENESIS_ACCOUNT_PRIVATE_KEY
+    );
+    expect(receipt.status).to.be.true;
+
+    const fastTrack = context.polkadotApi.tx.democracy.fastTrack(hash, 1000, 100);
+    await context.polkadotApi.tx.techComitteeCollective
+      .propose(1, fastTrack, fastTrack.encodedLength)
+      .signAndSend(genesis);
+    await createAndFinalizeBlock(context.polkadotApi);
+
+    const referendum = (await context.polkadotApi.query.democracy.referendumInfoOf(0)) as any;
+    expect(referendum.unwrap().isOngoing).to.be.true;
+  });
+
+  step("standard_vote votes with conviction", async function () {
+    this.timeout(15000);
+    const receipt = await send(
+      DEMOCRACY_PRECOMPILE,
+      `0x${STANDARD_VOTE}${word(0n)}${word(1n)}${word(10n * GLMR)}${word(1n)}`,
+      GENESIS_ACCOUNT,
+      GENESIS_ACCOUNT_PRIVATE_KEY
+    );
+    expect(receipt.status).to.be.true;
+
+    const votes = (await votingOf(GENESIS_ACCOUNT)).asDirect.votes;
+    expect(votes.length).to.equal(1);
+    const [index, vote] = votes[0];
+    expect(index.toNumber()).to.equal(0);
+    expect(vote.asStandard.vote.isAye).to.be.true;
+    expect(vote.asStandard.vote.conviction.toString()).to.equal("Locked1x");
+    expect(vote.asStandard.balance.toBigInt()).to.equal(10n * GLMR);
+
+    const referendum = (await context.polkadotApi.query.democracy.referendumInfoOf(0)) as any;
+    expect(referendum.unwrap().asOngoing.tally.ayes.toBigInt()).to.equal(10n * GLMR);
+  });
+
+  step("split_vote votes both ways without conviction", async function () {
+    this.timeout(30000);
+    const voter = await fundedAccount();
+    const receipt = await send(
+      DEMOCRACY_PRECOMPILE,
+      `0x${SPLIT_VOTE}${word(0n)}${word(2n * GLMR)}${word(3n * GLMR)}`,
+      voter.address,
+      voter.privateKey
+    );
+    expect(receipt.status).to.be.true;
+
+    const [[index, vote]] = (await votingOf(voter.address)).asDirect.votes;
+    expect(index.toNumber()).to.equal(0);
+    expect(vote.asSplit.aye.toBigInt()).to.equal(2n * GLMR);
+    expect(vote.asSplit.nay.toBigInt()).to.equal(3n * GLMR);
+  });
+
+  step("delegate delegates the voting power", async function () {
+    this.timeout(30000);
+    const delegator = await fundedAccount();
+    const receipt = await send(
+      DEMOCRACY_PRECOMPILE,
+      `0x${DELEGATE}${word(GENESIS_ACCOUNT)}${word(2n)}${word(5n * GLMR)}`,
+      delegator.address,
+      delegator.privateKey
+    );
+    expect(receipt.status).to.be.true;
+
+    const delegating = (await votingOf(delegator.address)).asDelegating;
+    expect(delegating.target.toString().toLowerCase()).to.equal(GENESIS_ACCOUNT);
+    expect(delegating.conviction.toString()).to.equal("Locked2x");
+    expect(delegating.balance.toBigInt()).to.equal(5n * GLMR);
+  });
+
+  step("standard_vote reverts for an unknown referendum", async function () {
+    this.timeout(15000);
+    const receipt = await send(
+      DEMOCRACY_PRECOMPILE,
+      `0x${STANDARD_VOTE}${word(7n)}${word(1n)}${word(GLMR)}${word(0n)}`,
+      GENESIS_ACCOUNT,
+      GENESIS_ACCOUNT_PRIVATE_KEY
+    );
+    expect(receipt.status).to.be.false;
+  });
+
+  step("democracy cannot be called with DELEGATECALL", async function () {
+    this.timeout(30000);
+    const forwarder = await deployDelegateCaller(context, DEMOCRACY_PRECOMPILE);
+    const voter = await fundedAccount();
+
+    // Through DELEGATECALL, the vote would be made on behalf of the caller of the forwarder
+    await send(
+      forwarder.options.address,
+      `0x${STANDARD_VOTE}${word(0n)}${word(1n)}${word(GLMR)}${word(0n)}`,
+      voter.address,
+      voter.privateKey
+    );
+
+    expect((await votingOf(voter.address)).asDirect.votes.length).to.equal(0);
+  });
+});
@@ -0,0 +1,131 @@
+import { expect } from "chai";
+import { step } from "mocha-steps";
+
+import { GENESIS_ACCOUNT, GENESIS_ACCOUNT_PRIVATE_KEY, GLMR } from "./constants";
+import { createAndFinalizeBlock, customRequest, describeWithMoonbeam } from "./util";
+
+const MULTISIG_PRECOMPILE = "0x0000000000000000000000000000000000000809";
+
+// Selector of multisig_address(address[],uint16)
+const MULTISIG_ADDRESS = "0x7bee4775";
+// Selector of as_multi(uint16,address[],uint32,uint32,bytes,uint64)
+const AS_MULTI = "0xff3a5c35";
+
+const ALICE = "0x2222222222222222222222222222222222222222";
+const BOB = "0x3333333333333333333333333333333333333333";
+const CHARLIE = "0x4444444444444444444444444444444444444444";
+
+describeWithMoonbeam("Moonbeam (Multisig precompile)", `simple-specs.json`, (context) => {
+  async function multisigAddress(signatories: string[], threshold: number) {
+    const args = context.web3.eth.abi.encodeParameters(
+      ["address[]", "uint16"],
+      [signatories, threshold]
+    );
+    const result = await customRequest(context.web3, "eth_call", [
+      {
+        to: MULTISIG_PRECOMPILE,
+        data: `${MULTISIG_ADDRESS}${args.slice(2)}`,
+      },
+    ]);
+    return context.web3.eth.abi.decodeParameter("address", result.result);
+  }
+
+  async function send(to: string, data: string, value: bigint, from: string, privateKey: string) {
+    const tx = await context.web3.eth.accounts.signTransaction(
+      {
+        from,
+        to,
+        data,
+        value: `0x${value.toString(16)}`,
+        gasPrice: "0x01",
+        gas: "0x100000",
+      },
+      privateKey
+    );
+    await customRequest(context.web3, "eth_sendRawTransaction", [tx.rawTransaction]);
+    await createAndFinalizeBlock(context.polkadotApi);
+    return context.web3.eth.getTransactionReceipt(tx.transactionHash);
+  }
+
+  async function asMulti(
+    otherSignatories: string[],
+    timepoint: [number, number],
+    call: string,
+    maxWeight: number,
+    from: string,
+    privateKey: string
+  ) {
+    const args = context.web3.eth.abi.encodeParameters(
+      ["uint16", "address[]", "uint32", "uint32", "bytes", "uint64"],
+      [2, otherSignatories, timepoint[0], timepoint[1], call, maxWeight]
+    );
+    return send(MULTISIG_PRECOMPILE, `${AS_MULTI}${args.slice(2)}`, 0n, from, privateKey);
+  }
+
+  it("derives the multisig account whatever the order of the signatories", async function () {
+    const multisig = await multisigAddress([ALICE, BOB, CHARLIE], 2);
+    expect(multisig).to.match(/^0x[0-9a-fA-F]{40}$/);
+    expect(await multisigAddress([CHARLIE, ALICE, BOB], 2)).to.equal(multisig);
+  });
+
+  it("derives different accounts for different thresholds", async function () {
+    expect(await multisigAddress([ALICE, BOB, CHARLIE], 2)).to.not.equal(
+      await multisigAddress([ALICE, BOB, CHARLIE], 3)
+    );
+  });
+
+  describe("as_multi", function () {
+    const signatory = { address: "", privateKey: "" };
+    const transfer = () => context.polkadotApi.tx.balances.transfer(CHARLIE, 0x100).method;
+    let multisig: string;
+
+    step("funds a 2 of 2 multisig account", async function () {
+      this.timeout(30000);
+      Object.assign(signatory, context.web3.eth.accounts.create());
+      await send(signatory.address, "0x", 10n * GLMR, GENESIS_ACCOUNT, GENESIS_ACCOUNT_PRIVATE_KEY);
+      multisig = (await multisigAddress([GENESIS_ACCOUNT, signatory.address], 2)) as any;
+      await send(multisig, "0x", GLMR, GENESIS_ACCOUNT, GENESIS_ACCOUNT_PRIVATE_KEY);
+
+      expect(await context.web3.eth.getBalance(multisig)).to.equal(GLMR.toString());
+    });
+
+    step("the first approval opens the operation without dispatching it", async function () {
+      this.timeout(15000);
+      const receipt = await asMulti(
+        [signatory.address],
+        [0, 0],
+        transfer().toHex(),
+        0,
+        GENESIS_ACCOUNT,
+        GENESIS_ACCOUNT_PRIVATE_KEY
+      );
+
+      expect(receipt.status).to.be.true;
+      const operation = (await context.polkadotApi.query.multisig.multisigs(
+        multisig,
+        transfer().hash
+      )) as any;
+      expect(operation.isSome).to.be.true;
+      expect(await context.web3.eth.getBalance(CHARLIE)).to.equal("0");
+    });
+
+    step("the last approval dispatches the call from the multisig account", async function () {
+      this.timeout(15000);
+      const operation = (
+        (await context.polkadotApi.query.multisig.multisigs(multisig, transfer().hash)) as any
+      ).unwrap();
+      const receipt = await asMulti(
+        [GENESIS_ACCOUNT],
+        [operation.when.height.toNumber(), operation.when.index.toNumber()],
+        transfer().toHex(),
+        1_000_000_000,
+        signatory.address,
+        signatory.privateKey
+      );
+
+      expect(receipt.status).to.be.true;
+      expect(await context.web3.eth.getBalance(CHARLIE)).to.equal("256");
+      expect(await context.web3.eth.getBalance(multisig)).to.equal((GLMR - 0x100n).toString());
+    });
+  });
+});
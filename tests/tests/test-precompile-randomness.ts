@@ -0,0 +1,54 @@
+import { expect } from "chai";
+
+import { GENESIS_ACCOUNT } from "./constants";
+import {
+  createAndFinalizeBlock,
+  customRequest,
+  deployDelegateCaller,
+  describeWithMoonbeam,
+} from "./util";
+
+const RANDOMNESS_PRECOMPILE = "0x0000000000000000000000000000000000000807";
+
+// Selector of current_epoch_randomness()
+const CURRENT_EPOCH_RANDOMNESS = "0x72de708e";
+// Selector of is_ready(uint256)
+const IS_READY = "0x2bbfe85e";
+
+describeWithMoonbeam("Moonbeam (Randomness precompile)", `simple-specs.json`, (context) => {
+  async function call(data: string, to: string = RANDOMNESS_PRECOMPILE) {
+    return customRequest(context.web3, "eth_call", [
+      {
+        from: GENESIS_ACCOUNT,
+        value: "0x0",
+        gas: "0x10000",
+        gasPrice: "0x01",
+        to,
+        data,
+      },
+    ]);
+  }
+
+  it("exposes the epoch randomness once a block is built", async function () {
+    await createAndFinalizeBlock(context.polkadotApi);
+    const tx_call = await call(CURRENT_EPOCH_RANDOMNESS);
+    expect(tx_call.result).to.have.lengthOf(66);
+    expect(tx_call.result).to.not.equal(`0x${"00".repeat(32)}`);
+  });
+
+  it("reports unknown requests as not ready", async function () {
+    const args = context.web3.eth.abi.encodeParameters(["uint256"], [42]);
+    const tx_call = await call(`${IS_READY}${args.slice(2)}`);
+    expect(context.web3.eth.abi.decodeParameter("bool", tx_call.result)).to.be.false;
+  });
+
+  it("cannot be called with DELEGATECALL", async function () {
+    this.timeout(15000);
+    const forwarder = await deployDelegateCaller(context, RANDOMNESS_PRECOMPILE);
+
+    // Requests would otherwise be made on behalf of the caller of the forwarder. The forwarder
+    // returns whether its DELEGATECALL succeeded.
+    const tx_call = await call(CURRENT_EPOCH_RANDOMNESS, forwarder.options.address);
+    expect(tx_call.result).equals(`0x${"0".repeat(64)}`);
+  });
+});
@@ -0,0 +1,130 @@
+import { expect } from "chai";
+
+import { GENESIS_ACCOUNT, GENESIS_ACCOUNT_PRIVATE_KEY, GLMR, TEST_ACCOUNT } from "./constants";
+import {
+  createAndFinalizeBlock,
+  customRequest,
+  deployDelegateCaller,
+  describeWithMoonbeam,
+} from "./util";
+
+const STAKING_PRECOMPILE = "0x0000000000000000000000000000000000000800";
+
+// Selectors of the view functions of StakingInterface.sol
+const IS_NOMINATOR = "8e5080e7";
+const IS_CANDIDATE = "8545c833";
+const IS_SELECTED_CANDIDATE = "8f6d27c7";
+const COLLATOR_NOMINATION_COUNT = "0ad6a7be";
+// Selector of nominate(address,uint256)
+const NOMINATE = "82f2c8df";
+
+const TRUE = "0x0000000000000000000000000000000000000000000000000000000000000001";
+const FALSE = "0x0000000000000000000000000000000000000000000000000000000000000000";
+
+function word(value: bigint | string) {
+  const hex = typeof value === "string" ? value.slice(2) : value.toString(16);
+  return hex.padStart(64, "0");
+}
+
+describeWithMoonbeam("Moonbeam (Staking precompile)", `simple-specs.json`, (context) => {
+  async function call(selector: string, account: string) {
+    const tx_call = await customRequest(context.web3, "eth_call", [
+      {
+        from: GENESIS_ACCOUNT,
+        value: "0x0",
+        gas: "0x10000",
+        gasPrice: "0x01",
+        to: STAKING_PRECOMPILE,
+        data: `0x${selector}${account.slice(2).padStart(64, "0")}`,
+      },
+    ]);
+    return tx_call.result;
+  }
+
+  it("genesis collator is a selected candidate", async function () {
+    expect(await call(IS_CANDIDATE, GENESIS_ACCOUNT)).equals(TRUE);
+    expect(await call(IS_SELECTED_CANDIDATE, GENESIS_ACCOUNT)).equals(TRUE);
+    expect(await call(IS_NOMINATOR, GENESIS_ACCOUNT)).equals(FALSE);
+    expect(await call(COLLATOR_NOMINATION_COUNT, GENESIS_ACCOUNT)).equals(FALSE);
+  });
+
+  it("other accounts are neither candidates nor nominators", async function () {
+    expect(await call(IS_CANDIDATE, TEST_ACCOUNT)).equals(FALSE);
+    expect(await call(IS_NOMINATOR, TEST_ACCOUNT)).equals(FALSE);
+  });
+
+  async function send(to: string, data: string, value: bigint, from: string, privateKey: string) {
+    const tx = await context.web3.eth.accounts.signTransaction(
+      {
+        from,
+        to,
+        data,
+        value: `0x${value.toString(16)}`,
+        gasPrice: "0x01",
+        gas: "0x100000",
+      },
+      privateKey
+    );
+    await customRequest(context.web3, "eth_sendRawTransaction", [tx.rawTransaction]);
+    await createAndFinalizeBlock(context.polkadotApi);
+    return context.web3.eth.getTransactionReceipt(tx.transactionHash);
+  }
+
+  // Create an account holding `balance`
+  async function fundedAccount(balance: bigint) {
+    const account = context.web3.eth.accounts.create();
+    await send(account.address, "0x", balance, GENESIS_ACCOUNT, GENESIS_ACCOUNT_PRIVATE_KEY);
+    return account;
+  }
+
+  it("nominate makes the caller a nominator", async function () {
+    this.timeout(30000);
+    const nominator = await fundedAccount(10n * GLMR);
+
+    const receipt = await send(
+      STAKING_PRECOMPILE,
+      `0x${NOMINATE}${word(GENESIS_ACCOUNT)}${word(5n * GLMR)}`,
+      0n,
+      nominator.address,
+      nominator.privateKey
+    );
+
+    expect(receipt.status).to.be.true;
+    expect(await call(IS_NOMINATOR, nominator.address)).equals(TRUE);
+    expect(await call(COLLATOR_NOMINATION_COUNT, GENESIS_ACCOUNT)).equals(`0x${word(1n)}`);
+  });
+
+  it("nominate reverts when the staking pallet rejects the call", async function () {
+    this.timeout(30000);
+    const nominator = await fundedAccount(10n * GLMR);
+
+    // TEST_ACCOUNT is not a candidate
+    const receipt = await send(
+      STAKING_PRECOMPILE,
+      `0x${NOMINATE}${word(TEST_ACCOUNT)}${word(5n * GLMR)}`,
+      0n,
+      nominator.address,
+      nominator.privateKey
+    );
+
+    expect(receipt.status).to.be.false;
+    expect(await call(IS_NOMINATOR, nominator.address)).equals(FALSE);
+  });
+
+  it("cannot be called with DELEGATECALL", async function () {
+    this.timeout(30000);
+    const forwarder = await deployDelegateCaller(context, STAKING_PRECOMPILE);
+    const nominator = await fundedAccount(10n * GLMR);
+
+    // Through DELEGATECALL, the nomination would be made on behalf of the caller of the forwarder
+    await send(
+      forwarder.options.address,
+      `0x${NOMINATE}${word(GENESIS_ACCOUNT)}${word(5n * GLMR)}`,
+      0n,
+      nominator.address,
+      nominator.privateKey
+    );
+
+    expect(await call(IS_NOMINATOR, nominator.address)).equals(FALSE);
+  });
+});
@@ -0,0 +1,48 @@
+import { expect } from "chai";
+
+import { GENESIS_ACCOUNT, GENESIS_ACCOUNT_PRIVATE_KEY } from "./constants";
+import { createAndFinalizeBlock, customRequest, describeWithMoonbeam } from "./util";
+
+const TREASURY_PRECOMPILE = "0x000000000000000000000000000000000000080a";
+// The account of the "py/trsry" module id
+const TREASURY_ACCOUNT = "0x6d6f646c70792f74727372790000000000000000";
+
+// Selector of balance()
+const BALANCE = "0xb69ef8a8";
+// Selector of account()
+const ACCOUNT = "0x5dab2420";
+
+describeWithMoonbeam("Moonbeam (Treasury precompile)", `simple-specs.json`, (context) => {
+  async function call(data: string) {
+    const result = await customRequest(context.web3, "eth_call", [
+      { to: TREASURY_PRECOMPILE, data },
+    ]);
+    return result.result;
+  }
+
+  it("returns the treasury account", async function () {
+    const account = context.web3.eth.abi.decodeParameter("address", await call(ACCOUNT));
+    expect(account.toLowerCase()).to.equal(TREASURY_ACCOUNT);
+  });
+
+  it("returns the funds of the treasury, which EVM fees add to", async function () {
+    this.timeout(15000);
+    const before = BigInt(await call(BALANCE));
+
+    const tx = await context.web3.eth.accounts.signTransaction(
+      {
+        from: GENESIS_ACCOUNT,
+        to: "0x1111111111111111111111111111111111111115",
+        value: "0x200",
+        gasPrice: "0x01",
+        gas: "0x100000",
+      },
+      GENESIS_ACCOUNT_PRIVATE_KEY
+    );
+    await customRequest(context.web3, "eth_sendRawTransaction", [tx.rawTransaction]);
+    await createAndFinalizeBlock(context.polkadotApi);
+
+    // Blocks of the tests have no author, so the whole fee goes to the treasury.
+    expect(BigInt(await call(BALANCE)) - before).to.equal(21000n);
+  });
+});
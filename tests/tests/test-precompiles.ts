@@ -254,6 +254,30 @@ describeWithMoonbeam("Moonbeam (Precompiles)", `simple-specs.json`, (context) =>
     );
   });
 
+  it("blake2f should be valid", async function () {
+    // Test vector 4 of EIP-152: the BLAKE2b-512 hash of "abc" in 12 rounds.
+    const tx_call = await customRequest(context.web3, "eth_call", [
+      {
+        from: GENESIS_ACCOUNT,
+        value: "0x0",
+        gas: "0x10000",
+        gasPrice: "0x01",
+        to: "0x0000000000000000000000000000000000000009",
+        data:
+          "0x0000000c48c9bdf267e6096a3ba7ca8485ae67bb2bf894fe72f36e3cf1361d5f3af54fa5d182e6ad7f520e" +
+          "511f6c3e2b8c68059b6bbd41fbabd9831f79217e1319cde05b61626300000000000000000000000000000000" +
+          "0000000000000000000000000000000000000000000000000000000000000000000000000000000000000000" +
+          "0000000000000000000000000000000000000000000000000000000000000000000000000000000000000000" +
+          "0000000000000000000000000000000000000000000300000000000000000000000000000001",
+      },
+    ]);
+
+    expect(tx_call.result).equals(
+      "0xba80a53f981c4d0d6a2797b69f12f6e94c212f14685ac4b74b12bb6fdbffa2d1" +
+        "7d87c5392aab792dc252d5de4533cc9518d38aa8dbf1925ab92386edd4009923"
+    );
+  });
+
   it("ripemd160 is valid inside a contract", async function () {
     this.timeout(15000);
     const tx = await context.web3.eth.accounts.signTransaction(
@@ -0,0 +1,52 @@
+import { expect } from "chai";
+import { step } from "mocha-steps";
+import { Keyring } from "@polkadot/keyring";
+
+import { GENESIS_ACCOUNT, GENESIS_ACCOUNT_PRIVATE_KEY, GLMR } from "./constants";
+import { createAndFinalizeBlock, describeWithMoonbeam } from "./util";
+
+const PROXY_ACCOUNT = "0xf24ff3a9cf04c71dbc94d0b566f7a27b94566cac";
+const PROXY_ACCOUNT_PRIVATE_KEY =
+  "0x5fb92d6e98884f76de468fa3f6278f8807c48bebc13595d45af5bdc4da702133";
+const TEST_ACCOUNT = "0x1111111111111111111111111111111111111113";
+
+describeWithMoonbeam("Moonbeam (Proxy)", `simple-specs.json`, (context) => {
+  const keyring = new Keyring({ type: "ethereum" });
+  const genesis = keyring.addFromUri(GENESIS_ACCOUNT_PRIVATE_KEY, null, "ethereum");
+  const proxy = keyring.addFromUri(PROXY_ACCOUNT_PRIVATE_KEY, null, "ethereum");
+
+  step("adds a staking proxy", async function () {
+    this.timeout(15000);
+    await context.polkadotApi.tx.balances.transfer(PROXY_ACCOUNT, GLMR).signAndSend(genesis);
+    await createAndFinalizeBlock(context.polkadotApi);
+    await context.polkadotApi.tx.proxy.addProxy(PROXY_ACCOUNT, "Staking", 0).signAndSend(genesis);
+    await createAndFinalizeBlock(context.polkadotApi);
+
+    const [proxies] = (await context.polkadotApi.query.proxy.proxies(GENESIS_ACCOUNT)) as any;
+    expect(proxies.length).to.equal(1);
+    expect(proxies[0].delegate.toString().toLowerCase()).to.equal(PROXY_ACCOUNT);
+    expect(proxies[0].proxyType.toString()).to.equal("Staking");
+  });
+
+  step("lets the staking proxy make staking calls", async function () {
+    this.timeout(15000);
+    const before = await context.polkadotApi.query.system.account(GENESIS_ACCOUNT);
+    await context.polkadotApi.tx.proxy
+      .proxy(GENESIS_ACCOUNT, null, context.polkadotApi.tx.parachainStaking.candidateBondMore(1))
+      .signAndSend(proxy);
+    await createAndFinalizeBlock(context.polkadotApi);
+
+    const after = await context.polkadotApi.query.system.account(GENESIS_ACCOUNT);
+    expect(after.data.reserved.toBigInt()).to.equal(before.data.reserved.toBigInt() + 1n);
+  });
+
+  step("prevents the staking proxy from transferring funds", async function () {
+    this.timeout(15000);
+    await context.polkadotApi.tx.proxy
+      .proxy(GENESIS_ACCOUNT, null, context.polkadotApi.tx.balances.transfer(TEST_ACCOUNT, 1))
+      .signAndSend(proxy);
+    await createAndFinalizeBlock(context.polkadotApi);
+
+    expect(await context.web3.eth.getBalance(TEST_ACCOUNT)).to.equal("0");
+  });
+});
@@ -0,0 +1,22 @@
+import { Contract } from "web3-eth-contract";
+import { deployContractManualSeal } from "./web3Requests";
+import { Context } from "./testWithMoonbeam";
+
+// Runtime code forwarding its calldata to `target` with DELEGATECALL and returning the success
+// flag of the DELEGATECALL as a word:
+// CALLDATASIZE 0 0 CALLDATACOPY
+// 0 0 CALLDATASIZE 0 <target> GAS DELEGATECALL
+// 0 MSTORE 0x20 0 RETURN
+function delegateCallerRuntime(target: string) {
+  return `3660006000376000600036600073${target.slice(2).toLowerCase()}5af460005260206000f3`;
+}
+
+// Deploy a contract forwarding every call it receives to `target` with DELEGATECALL, which the
+// precompiles acting on behalf of their caller must reject.
+export async function deployDelegateCaller(context: Context, target: string): Promise<Contract> {
+  const runtime = delegateCallerRuntime(target);
+  const length = (runtime.length / 2).toString(16).padStart(2, "0");
+  // PUSH1 <length> DUP1 PUSH1 0x0b PUSH1 0 CODECOPY PUSH1 0 RETURN, followed by the runtime code
+  const bytecode = `0x60${length}80600b6000396000f3${runtime}`;
+  return deployContractManualSeal(context.polkadotApi, context.web3, bytecode, []);
+}
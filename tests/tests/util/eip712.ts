@@ -0,0 +1,50 @@
+import { ApiPromise } from "@polkadot/api";
+import { SignerPayloadJSON } from "@polkadot/types/types";
+import { u8aToString } from "@polkadot/util";
+import { Wallet } from "ethers";
+
+// The EIP-712 domain of the chain: its Ethereum chain id and runtime spec version
+export async function eip712Domain(api: ApiPromise) {
+  return {
+    name: "Moonbeam",
+    version: api.runtimeVersion.specVersion.toString(),
+    chainId: (await api.rpc.eth.chainId()).toNumber(),
+  };
+}
+
+// The EIP-712 typed data of a transaction, as built by the `Eip712Api` runtime API from its
+// SCALE encoded signing payload
+export async function eip712TypedData(api: ApiPromise, payload: SignerPayloadJSON) {
+  const encoded = api
+    .createType("ExtrinsicPayload", payload, { version: payload.version })
+    .toU8a({ method: true });
+  const result = await api.rpc.state.call(
+    "Eip712Api_typed_data",
+    api.createType("Bytes", encoded).toHex()
+  );
+  const typedData = api.createType("Option<Bytes>", result);
+  if (typedData.isNone) {
+    throw new Error("The signing payload does not start with a call of the runtime");
+  }
+  return JSON.parse(u8aToString(typedData.unwrap()));
+}
+
+// A polkadot.js signer signing the transactions of `wallet` as EIP-712 typed data, in the domain
+// of the chain unless another `domain` is given
+export function eip712Signer(api: ApiPromise, wallet: Wallet, domain?: any) {
+  let id = 0;
+  return {
+    signPayload: async (payload: SignerPayloadJSON) => {
+      const typedData = await eip712TypedData(api, payload);
+      // ethers derives the domain type itself
+      const { EIP712Domain, ...types } = typedData.types;
+      const signature = await wallet._signTypedData(
+        domain || typedData.domain,
+        types,
+        typedData.message
+      );
+      // The `Eip712` variant of `MultiEthereumSignature`
+      return { id: ++id, signature: `0x01${signature.slice(2)}` };
+    },
+  };
+}
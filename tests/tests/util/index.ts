@@ -7,3 +7,5 @@ export {
   callContractFunctionMS,
 } from "./web3Requests";
 export { createAndFinalizeBlock } from "./polkadotApiRequests";
+export { eip712Domain, eip712Signer, eip712TypedData } from "./eip712";
+export { deployDelegateCaller } from "./delegateCaller";